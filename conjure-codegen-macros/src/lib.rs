@@ -0,0 +1,113 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Macros embedding Rust code generated from Conjure definitions.
+#![warn(missing_docs)]
+
+use conjure_codegen::{Config, ValidationError};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::env;
+use std::path::PathBuf;
+use structmeta::StructMeta;
+use syn::{parse_macro_input, Error, LitStr};
+
+/// Expands to the Rust code generated from a JSON-encoded Conjure IR file.
+///
+/// The generated modules are expanded inline, so the macro is typically invoked inside a module
+/// of its own, in the same way the output of [`Config::generate_files`] would be included with
+/// `include!`:
+///
+/// ```ignore
+/// #[allow(warnings)]
+/// mod api {
+///     conjure_codegen_macros::include_ir!("api.conjure.json", strip_prefix = "com.foo");
+/// }
+/// ```
+///
+/// The path to the IR file is relative to the root of the crate invoking the macro. The crate is
+/// rebuilt when the file changes.
+///
+/// # Parameters
+///
+/// In addition to the path to the IR file, the macro accepts several optional parameters
+/// corresponding to the settings of a [`Config`]:
+///
+/// * `exhaustive` - Generate exhaustively matchable enums and unions.
+/// * `staged_builders` - Generate compile-time safe builders.
+/// * `token_provider` - Generate clients which obtain bearer tokens from a `TokenProvider`.
+/// * `strip_prefix = "..."` - Strip a prefix from the package paths of types.
+#[proc_macro]
+pub fn include_ir(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(input as IncludeIrArgs);
+
+    match generate(&args) {
+        Ok(tokens) => tokens,
+        Err(e) => e.into_compile_error(),
+    }
+    .into()
+}
+
+fn generate(args: &IncludeIrArgs) -> Result<TokenStream, Error> {
+    let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+        .ok_or_else(|| Error::new(args.path.span(), "CARGO_MANIFEST_DIR is not set"))?;
+    let path = PathBuf::from(manifest_dir).join(args.path.value());
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| Error::new(args.path.span(), "IR file path is not valid UTF-8"))?;
+
+    let mut config = Config::new();
+    config
+        .exhaustive(args.exhaustive)
+        .staged_builders(args.staged_builders)
+        .token_provider(args.token_provider);
+    if let Some(strip_prefix) = &args.strip_prefix {
+        config.strip_prefix(strip_prefix.value());
+    }
+
+    let module = config.generate_module(&path).map_err(|e| {
+        // report each validation problem as a separate compile error
+        if let Some(e) = e.downcast_ref::<ValidationError>() {
+            let mut errors = e.errors().iter().map(|e| Error::new(args.path.span(), e));
+            if let Some(mut error) = errors.next() {
+                error.extend(errors);
+                return error;
+            }
+        }
+
+        let message = e
+            .iter_chain()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(": ");
+        Error::new(args.path.span(), message)
+    })?;
+    let module = module.inline_tokens();
+
+    Ok(quote! {
+        // ensures the crate is rebuilt when the IR changes
+        const _: &[u8] = include_bytes!(#path_str);
+
+        #module
+    })
+}
+
+#[derive(StructMeta)]
+struct IncludeIrArgs {
+    #[struct_meta(unnamed)]
+    path: LitStr,
+    exhaustive: bool,
+    staged_builders: bool,
+    token_provider: bool,
+    strip_prefix: Option<LitStr>,
+}
@@ -60,19 +60,60 @@ fn generate_inner(ctx: &Context, def: &ServiceDefinition, style: Style) -> Token
         .iter()
         .map(|e| generate_endpoint(ctx, def, style, e));
 
-    quote! {
-        #docs
-        #[derive(Clone, Debug)]
-        pub struct #name<T>(T);
+    let definition = if ctx.token_provider() {
+        let name_str = name.to_string();
+        let provider = match style {
+            Style::Async => quote!(AsyncTokenProvider),
+            Style::Sync => quote!(TokenProvider),
+        };
+        let provider_docs = format!(" `{}`.", provider);
+        quote! {
+            #docs
+            #[derive(Clone)]
+            pub struct #name<T>(T, std::sync::Arc<dyn conjure_http::client::#provider>);
+
+            impl<T> std::fmt::Debug for #name<T>
+            where
+                T: std::fmt::Debug,
+            {
+                fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    fmt.debug_tuple(#name_str).field(&self.0).finish()
+                }
+            }
 
-        impl<T> conjure_http::client::#service<T> for #name<T>
-        where
-            T: conjure_http::client::#client_bound,
-        {
-            fn new(client: T) -> Self {
-                #name(client)
+            impl<T> #name<T>
+            where
+                T: conjure_http::client::#client_bound,
+            {
+                /// Creates a new client which authenticates requests with tokens from the provided
+                #[doc = #provider_docs]
+                pub fn new(
+                    client: T,
+                    token_provider: std::sync::Arc<dyn conjure_http::client::#provider>,
+                ) -> Self {
+                    #name(client, token_provider)
+                }
             }
         }
+    } else {
+        quote! {
+            #docs
+            #[derive(Clone, Debug)]
+            pub struct #name<T>(T);
+
+            impl<T> conjure_http::client::#service<T> for #name<T>
+            where
+                T: conjure_http::client::#client_bound,
+            {
+                fn new(client: T) -> Self {
+                    #name(client)
+                }
+            }
+        }
+    };
+
+    quote! {
+        #definition
 
         impl<T> #name<T>
         where
@@ -111,7 +152,12 @@ fn generate_endpoint(
     let params = params(ctx, body_arg);
 
     let auth = quote!(auth_);
-    let auth_arg = auth_arg(endpoint, &auth);
+    let auth_arg = if ctx.token_provider() {
+        quote!()
+    } else {
+        auth_arg(endpoint, &auth)
+    };
+    let setup_auth = setup_auth(ctx, endpoint, style, &auth);
     let args = endpoint.args().iter().map(|a| {
         let name = ctx.field_name(a.arg_name());
         let ty = arg_type(ctx, def, a);
@@ -136,10 +182,12 @@ fn generate_endpoint(
 
     let setup_headers = setup_headers(ctx, endpoint, &request, &auth);
 
-    let setup_response_headers = setup_response_headers(&ret, &request);
+    let setup_response_headers = setup_response_headers(ctx, &ret, &request);
 
     let setup_endpoint_extension = setup_endpoint_extension(ctx, def, endpoint, &request);
 
+    let setup_safe_params = setup_safe_params(ctx, endpoint, &request);
+
     let await_ = match style {
         Style::Async => quote!(.await),
         Style::Sync => quote!(),
@@ -154,12 +202,14 @@ fn generate_endpoint(
         pub #async_ fn #name #params(&self #auth_arg #(, #args)*) -> #result<#ret_name, conjure_http::private::Error>
         #where_
         {
+            #setup_auth
             #setup_request
             *#request.method_mut() = conjure_http::private::http::Method::#method;
             #setup_path
             #setup_headers
             #setup_response_headers
             #setup_endpoint_extension
+            #setup_safe_params
 
             let #response = self.0.send(#request)#await_?;
             #decode_response
@@ -203,6 +253,25 @@ fn auth_arg(endpoint: &EndpointDefinition, auth: &TokenStream) -> TokenStream {
     }
 }
 
+fn setup_auth(
+    ctx: &Context,
+    endpoint: &EndpointDefinition,
+    style: Style,
+    auth: &TokenStream,
+) -> TokenStream {
+    if ctx.token_provider() && endpoint.auth().is_some() {
+        let await_ = match style {
+            Style::Async => quote!(.await),
+            Style::Sync => quote!(),
+        };
+        quote! {
+            let #auth = &self.1.token() #await_?;
+        }
+    } else {
+        quote!()
+    }
+}
+
 fn arg_type(ctx: &Context, def: &ServiceDefinition, arg: &ArgumentDefinition) -> TokenStream {
     if ctx.is_binary(arg.type_()) {
         quote!(U)
@@ -427,11 +496,20 @@ fn auth_header(
     }
 }
 
-fn setup_response_headers(ty: &ReturnType<'_>, request: &TokenStream) -> TokenStream {
+fn setup_response_headers(
+    ctx: &Context,
+    ty: &ReturnType<'_>,
+    request: &TokenStream,
+) -> TokenStream {
     match ty {
         ReturnType::None => quote! {
             conjure_http::private::encode_empty_response_headers(&mut #request);
         },
+        ReturnType::Json(_) if ctx.prefer_smile() => {
+            quote! {
+                conjure_http::private::encode_smile_serializable_response_headers(&mut #request);
+            }
+        }
         ReturnType::Json(_) => {
             quote! {
                 conjure_http::private::encode_serializable_response_headers(&mut #request);
@@ -460,14 +538,64 @@ fn setup_endpoint_extension(
     };
     let name = &***endpoint.endpoint_name();
     let path = &***endpoint.http_path();
+    let method = endpoint
+        .http_method()
+        .as_str()
+        .parse::<TokenStream>()
+        .unwrap();
+    // Binary bodies are streamed and must be reset before they can be resent.
+    let replayable_body = !matches!(body_arg(endpoint), Some(a) if ctx.is_binary(a.type_()));
+    let tags = endpoint.tags();
+    let markers = endpoint
+        .markers()
+        .iter()
+        .map(|marker| ctx.type_description(marker));
+
+    quote! {
+        #request.extensions_mut().insert(
+            conjure_http::client::Endpoint::new(
+                #service,
+                #version,
+                #name,
+                #path,
+            )
+            .with_method(conjure_http::private::http::Method::#method)
+            .with_replayable_body(#replayable_body)
+            .with_tags(&[#(#tags,)*])
+            .with_markers(&[#(#markers,)*]),
+        );
+    }
+}
+
+fn setup_safe_params(
+    ctx: &Context,
+    endpoint: &EndpointDefinition,
+    request: &TokenStream,
+) -> TokenStream {
+    // Binary bodies are streamed rather than serialized, so they have no loggable value.
+    let args = endpoint
+        .args()
+        .iter()
+        .filter(|arg| ctx.is_safe_arg(arg) && !ctx.is_binary(arg.type_()))
+        .collect::<Vec<_>>();
+
+    if args.is_empty() {
+        return quote!();
+    }
+
+    let safe_params = quote!(safe_params_);
+    let inserts = args.iter().map(|arg| {
+        let name = &***arg.arg_name();
+        let variable = ctx.field_name(arg.arg_name());
+        quote! {
+            #safe_params.insert(#name, &#variable);
+        }
+    });
 
     quote! {
-        #request.extensions_mut().insert(conjure_http::client::Endpoint::new(
-            #service,
-            #version,
-            #name,
-            #path,
-        ));
+        let mut #safe_params = conjure_http::SafeParams::new();
+        #(#inserts)*
+        #request.extensions_mut().insert(#safe_params);
     }
 }
 
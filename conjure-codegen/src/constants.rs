@@ -1,21 +1,119 @@
-use proc_macro2::TokenStream;
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use conjure_object::Uuid;
+use proc_macro2::{Ident, Literal, TokenStream};
 use quote::quote;
-use syn::parse_str;
+use std::str::FromStr;
+
 use crate::context::Context;
-use crate::types::ConstantDefinition;
+use crate::types::{ConstantDefinition, PrimitiveType};
 
-pub fn generate_constants(ctx: &Context, defs: &Vec<&ConstantDefinition>) -> TokenStream {
-    let constants: Vec<TokenStream> = defs.iter().map(|def| generate(ctx, def)).collect();
-    quote! {
-        #(#constants)*
+// Constant values have already been checked by the validation pass, so parsing them can't fail.
+pub fn generate(ctx: &Context, def: &ConstantDefinition) -> TokenStream {
+    let docs = ctx.docs(def.docs());
+    let name = ctx.constant_name(def.type_name().name());
+    let value = def.value();
+
+    match def.const_type() {
+        PrimitiveType::String => quote! {
+            #docs
+            pub const #name: &str = #value;
+        },
+        PrimitiveType::Integer => {
+            let value = Literal::i32_unsuffixed(value.parse().unwrap());
+            quote! {
+                #docs
+                pub const #name: i32 = #value;
+            }
+        }
+        PrimitiveType::Double => {
+            let value = double(value.parse().unwrap());
+            quote! {
+                #docs
+                pub const #name: f64 = #value;
+            }
+        }
+        PrimitiveType::Safelong => {
+            let value = Literal::i64_unsuffixed(value.parse().unwrap());
+            quote! {
+                #docs
+                pub const #name: conjure_object::SafeLong =
+                    conjure_object::private::safe_long(#value);
+            }
+        }
+        PrimitiveType::Boolean => {
+            let value = value.parse::<bool>().unwrap();
+            quote! {
+                #docs
+                pub const #name: bool = #value;
+            }
+        }
+        PrimitiveType::Uuid => {
+            let value = Uuid::from_str(value).unwrap().as_u128();
+            let value = format!("{:#034x}", value).parse::<TokenStream>().unwrap();
+            quote! {
+                #docs
+                pub const #name: conjure_object::Uuid = conjure_object::Uuid::from_u128(#value);
+            }
+        }
+        // the remaining types can't be constructed in a const context
+        PrimitiveType::Datetime => lazy(
+            &docs,
+            &name,
+            quote!(conjure_object::DateTime<conjure_object::Utc>),
+            quote! {
+                conjure_object::DateTime::parse_from_rfc3339(#value)
+                    .unwrap()
+                    .with_timezone(&conjure_object::Utc)
+            },
+        ),
+        PrimitiveType::Rid => lazy(
+            &docs,
+            &name,
+            quote!(conjure_object::ResourceIdentifier),
+            quote!(conjure_object::ResourceIdentifier::new(#value).unwrap()),
+        ),
+        PrimitiveType::Bearertoken => lazy(
+            &docs,
+            &name,
+            quote!(conjure_object::BearerToken),
+            quote!(conjure_object::BearerToken::new(#value).unwrap()),
+        ),
+        PrimitiveType::Binary | PrimitiveType::Any => {
+            unreachable!("unsupported constant type {:?}", def.const_type())
+        }
     }
 }
 
-pub fn generate(ctx: &Context, def: &ConstantDefinition) -> TokenStream {
-    let const_name = ctx.constant_name(def.type_name().name());
-    let const_type = ctx.primitive_rust_type(def.type_name(), def.const_type(), false);
-    let const_value = ctx.parse_const_value(def.const_type(), def.value());
-    let constant_str = format!("pub const {}: {} = {};", const_name, const_type, const_value);
+fn double(value: f64) -> TokenStream {
+    if value.is_nan() {
+        quote!(f64::NAN)
+    } else if value == f64::INFINITY {
+        quote!(f64::INFINITY)
+    } else if value == f64::NEG_INFINITY {
+        quote!(f64::NEG_INFINITY)
+    } else {
+        let value = Literal::f64_unsuffixed(value);
+        quote!(#value)
+    }
+}
 
-    return parse_str(&constant_str).unwrap();
-}
\ No newline at end of file
+fn lazy(docs: &TokenStream, name: &Ident, type_: TokenStream, value: TokenStream) -> TokenStream {
+    quote! {
+        conjure_object::private::lazy_static! {
+            #docs
+            pub static ref #name: #type_ = #value;
+        }
+    }
+}
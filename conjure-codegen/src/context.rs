@@ -17,13 +17,13 @@ use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
-use conjure_object::{DateTime};
+use std::collections::{BTreeMap, HashMap};
 
 use crate::types::{
-    ArgumentDefinition, ConjureDefinition, Documentation, LogSafety, PrimitiveType, Type,
-    TypeDefinition, TypeName,
+    ArgumentDefinition, ConjureDefinition, Documentation, ExternalReference, LogSafety,
+    PrimitiveType, Type, TypeDefinition, TypeName,
 };
+use crate::ExternalType;
 
 enum CachedLogSafety {
     Uncomputed,
@@ -41,23 +41,39 @@ pub struct Context {
     types: HashMap<TypeName, TypeContext>,
     exhaustive: bool,
     staged_builders: bool,
+    prefer_smile: bool,
+    streaming_response_threshold: Option<usize>,
+    token_provider: bool,
     strip_prefix: Vec<String>,
+    extern_packages: BTreeMap<String, TokenStream>,
+    external_types: HashMap<TypeName, ExternalType>,
     version: Option<String>,
 }
 
 impl Context {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         defs: &ConjureDefinition,
         exhaustive: bool,
         staged_builders: bool,
+        prefer_smile: bool,
+        streaming_response_threshold: Option<usize>,
+        token_provider: bool,
         strip_prefix: Option<&str>,
+        extern_packages: BTreeMap<String, TokenStream>,
+        external_types: HashMap<TypeName, ExternalType>,
         version: Option<&str>,
     ) -> Context {
         let mut context = Context {
             types: HashMap::new(),
             exhaustive,
             staged_builders,
+            prefer_smile,
+            streaming_response_threshold,
+            token_provider,
             strip_prefix: vec![],
+            extern_packages,
+            external_types,
             version: version.map(str::to_owned),
         };
 
@@ -96,13 +112,43 @@ impl Context {
         self.staged_builders
     }
 
+    pub fn prefer_smile(&self) -> bool {
+        self.prefer_smile
+    }
+
+    pub fn streaming_response_threshold(&self) -> Option<usize> {
+        self.streaming_response_threshold
+    }
+
+    pub fn token_provider(&self) -> bool {
+        self.token_provider
+    }
+
+    /// Returns the user-provided Rust type for an external reference, if one was configured.
+    pub fn external_type(&self, def: &ExternalReference) -> Option<&ExternalType> {
+        self.external_types.get(def.external_reference())
+    }
+
+    /// Returns all of the user-provided Rust types for external references.
+    pub fn external_types(&self) -> &HashMap<TypeName, ExternalType> {
+        &self.external_types
+    }
+
+    fn external_type_path(&self, external: &ExternalType) -> TokenStream {
+        // the path is checked to be a valid type by externals::validate
+        external.path.parse().unwrap()
+    }
+
     fn needs_box(&self, def: &Type) -> bool {
         match def {
             Type::Primitive(_) => false,
             Type::Optional(def) => self.needs_box(def.item_type()),
             Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.ref_needs_box(def),
-            Type::External(def) => self.needs_box(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.needs_box(def.fallback()),
+            },
         }
     }
 
@@ -132,7 +178,10 @@ impl Context {
             Type::Set(def) => self.has_double(def.item_type()),
             Type::Map(def) => self.has_double(def.key_type()) || self.has_double(def.value_type()),
             Type::Reference(def) => self.ref_has_double(def),
-            Type::External(def) => self.has_double(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.has_double(def.fallback()),
+            },
         }
     }
 
@@ -178,7 +227,10 @@ impl Context {
             Type::Optional(def) => self.is_copy(def.item_type()),
             Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.ref_is_copy(def),
-            Type::External(def) => self.is_copy(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(external) => external.copy,
+                None => self.is_copy(def.fallback()),
+            },
         }
     }
 
@@ -203,7 +255,10 @@ impl Context {
             Type::Primitive(_) => true,
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.ref_is_required(def),
-            Type::External(def) => self.is_required(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => true,
+                None => self.is_required(def.fallback()),
+            },
         }
     }
 
@@ -237,7 +292,10 @@ impl Context {
             Type::Primitive(def) => self.primitive_is_default(def),
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => true,
             Type::Reference(def) => self.ref_is_default(def),
-            Type::External(def) => self.is_default(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.is_default(def.fallback()),
+            },
         }
     }
 
@@ -269,7 +327,10 @@ impl Context {
             Type::Primitive(def) => self.primitive_is_display(def),
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.ref_is_display(def),
-            Type::External(def) => self.is_display(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.is_display(def.fallback()),
+            },
         }
     }
 
@@ -282,20 +343,6 @@ impl Context {
         }
     }
 
-    pub fn parse_const_value(&self, type_: &PrimitiveType, value: &String) -> String {
-        match type_{
-            PrimitiveType::Integer => value.parse::<i32>().unwrap().to_string(),
-            PrimitiveType::Datetime => {
-                let dt = DateTime::parse_from_rfc3339(value).unwrap();
-                format!("DateTime::parse_from_rfc3339({:?}).unwrap()", dt.to_rfc3339())
-            }
-            PrimitiveType::Double => value.parse::<f64>().unwrap().to_string(),
-            PrimitiveType::Safelong => value.parse::<i64>().unwrap().to_string(),
-            PrimitiveType::Boolean => value.parse::<bool>().unwrap().to_string(),
-            _ => String::from(value),
-        }
-    }
-
     pub fn rust_type(&self, this_type: &TypeName, def: &Type) -> TokenStream {
         self.rust_type_inner(this_type, def, false)
     }
@@ -345,7 +392,10 @@ impl Context {
                 quote!(std::collections::BTreeMap<#key, #value>)
             }
             Type::Reference(def) => self.type_path(this_type, def),
-            Type::External(def) => self.rust_type_inner(this_type, def.fallback(), key),
+            Type::External(def) => match self.external_type(def) {
+                Some(external) => self.external_type_path(external),
+                None => self.rust_type_inner(this_type, def.fallback(), key),
+            },
         }
     }
 
@@ -357,7 +407,9 @@ impl Context {
                 quote!(#option<#item>)
             }
             Type::Reference(def) => self.ref_boxed_rust_type(this_type, def),
-            Type::External(def) => self.boxed_rust_type(this_type, def.fallback()),
+            Type::External(def) if self.external_type(def).is_none() => {
+                self.boxed_rust_type(this_type, def.fallback())
+            }
             def => self.rust_type(this_type, def),
         }
     }
@@ -384,11 +436,13 @@ impl Context {
         }
     }
 
-    #[allow(clippy::only_used_in_recursion)]
     pub fn option_inner_type<'a>(&self, def: &'a Type) -> Option<&'a Type> {
         match def {
             Type::Optional(def) => Some(def.item_type()),
-            Type::External(def) => self.option_inner_type(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => None,
+                None => self.option_inner_type(def.fallback()),
+            },
             _ => None,
         }
     }
@@ -427,7 +481,17 @@ impl Context {
                 quote!(&std::collections::BTreeMap<#key, #value>)
             }
             Type::Reference(def) => self.borrowed_rust_type_ref(this_type, def),
-            Type::External(def) => self.borrowed_rust_type(this_type, def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(external) => {
+                    let type_ = self.external_type_path(external);
+                    if external.copy {
+                        type_
+                    } else {
+                        quote!(&#type_)
+                    }
+                }
+                None => self.borrowed_rust_type(this_type, def.fallback()),
+            },
         }
     }
 
@@ -471,7 +535,11 @@ impl Context {
             Type::List(_) => quote!(&*#value),
             Type::Set(_) | Type::Map(_) => quote!(&#value),
             Type::Reference(def) => self.borrow_rust_type_ref(value, def),
-            Type::External(def) => self.borrow_rust_type(value, def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(external) if external.copy => value,
+                Some(_) => quote!(&#value),
+                None => self.borrow_rust_type(value, def.fallback()),
+            },
         }
     }
 
@@ -609,7 +677,13 @@ impl Context {
                     assign_rhs,
                 }
             }
-            Type::External(def) => self.setter_bounds(this_type, def.fallback(), value_ident),
+            Type::External(def) => match self.external_type(def) {
+                Some(external) => SetterBounds::Simple {
+                    argument_type: self.external_type_path(external),
+                    assign_rhs: value_ident,
+                },
+                None => self.setter_bounds(this_type, def.fallback(), value_ident),
+            },
         }
     }
 
@@ -687,9 +761,13 @@ impl Context {
                 argument_type: self.type_path(this_type, def),
                 assign_rhs: value_ident,
             },
-            Type::External(def) => {
-                self.collection_setter_bounds(this_type, def.fallback(), value_ident, key)
-            }
+            Type::External(def) => match self.external_type(def) {
+                Some(external) => CollectionSetterBounds::Simple {
+                    argument_type: self.external_type_path(external),
+                    assign_rhs: value_ident,
+                },
+                None => self.collection_setter_bounds(this_type, def.fallback(), value_ident, key),
+            },
         }
     }
 
@@ -699,7 +777,10 @@ impl Context {
             Type::Optional(_) => Some(quote!(is_none)),
             Type::List(_) | Type::Set(_) | Type::Map(_) => Some(quote!(is_empty)),
             Type::Reference(def) => self.is_empty_method_ref(def),
-            Type::External(def) => self.is_empty_method(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => None,
+                None => self.is_empty_method(def.fallback()),
+            },
         }
     }
 
@@ -726,7 +807,10 @@ impl Context {
             | Type::Set(_)
             | Type::Map(_) => false,
             Type::Reference(def) => self.is_binary_ref(def),
-            Type::External(def) => self.is_binary(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.is_binary(def.fallback()),
+            },
         }
     }
 
@@ -752,7 +836,10 @@ impl Context {
             Type::Primitive(primitive) => self.primitive_is_plain(primitive),
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.is_plain_ref(def),
-            Type::External(def) => self.is_plain(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(external) => external.plain,
+                None => self.is_plain(def.fallback()),
+            },
         }
     }
 
@@ -772,7 +859,10 @@ impl Context {
             Type::Primitive(_) => false,
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => true,
             Type::Reference(def) => self.is_iterable_ref(def),
-            Type::External(def) => self.is_iterable(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.is_iterable(def.fallback()),
+            },
         }
     }
 
@@ -790,7 +880,10 @@ impl Context {
             Type::Primitive(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => None,
             Type::Optional(def) => Some(def.item_type()),
             Type::Reference(def) => self.is_optional_ref(def),
-            Type::External(def) => self.is_optional(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => None,
+                None => self.is_optional(def.fallback()),
+            },
         }
     }
 
@@ -808,7 +901,10 @@ impl Context {
             Type::List(_) => true,
             Type::Primitive(_) | Type::Optional(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.is_list_ref(def),
-            Type::External(def) => self.is_list(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.is_list(def.fallback()),
+            },
         }
     }
 
@@ -826,7 +922,10 @@ impl Context {
             Type::Set(_) => true,
             Type::Primitive(_) | Type::Optional(_) | Type::List(_) | Type::Map(_) => false,
             Type::Reference(def) => self.is_set_ref(def),
-            Type::External(def) => self.is_set(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.is_set(def.fallback()),
+            },
         }
     }
 
@@ -846,7 +945,10 @@ impl Context {
             Type::Optional(def) => self.is_double(def.item_type()),
             Type::List(def) => self.is_double(def.item_type()),
             Type::Primitive(_) | Type::Set(_) | Type::Map(_) | Type::Reference(_) => false,
-            Type::External(def) => self.is_double(def.fallback()),
+            Type::External(def) => match self.external_type(def) {
+                Some(_) => false,
+                None => self.is_double(def.fallback()),
+            },
         }
     }
 
@@ -1030,7 +1132,41 @@ impl Context {
         package.split('.').map(|s| self.ident_name(s)).collect()
     }
 
+    /// Returns true if the type belongs to a package generated in another crate.
+    pub fn is_extern(&self, name: &TypeName) -> bool {
+        self.extern_package(name.package()).is_some()
+    }
+
+    fn extern_package(&self, package: &str) -> Option<(&str, &TokenStream)> {
+        self.extern_packages
+            .iter()
+            .filter(|(prefix, _)| {
+                matches!(
+                    package.strip_prefix(prefix.as_str()),
+                    Some(rest) if rest.is_empty() || rest.starts_with('.')
+                )
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(prefix, path)| (&**prefix, path))
+    }
+
+    fn extern_type_path(&self, name: &TypeName) -> Option<TokenStream> {
+        let (prefix, path) = self.extern_package(name.package())?;
+
+        let components = name.package()[prefix.len()..]
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .map(|s| self.ident_name(s).parse::<TokenStream>().unwrap());
+        let type_name = self.type_name(name.name());
+
+        Some(quote!(#path #(::#components)* :: #type_name))
+    }
+
     fn type_path(&self, this_type: &TypeName, other_type: &TypeName) -> TokenStream {
+        if let Some(path) = self.extern_type_path(other_type) {
+            return path;
+        }
+
         let this_module_path = self.module_path(this_type);
         let other_module_path = self.module_path(other_type);
 
@@ -1058,17 +1194,43 @@ impl Context {
         quote!(#(#components::)* #other_type_name)
     }
 
+    /// Formats a type the way it is written in a Conjure definition.
+    pub fn type_description(&self, ty: &Type) -> String {
+        match ty {
+            Type::Primitive(primitive) => primitive.as_str().to_lowercase(),
+            Type::Optional(optional) => {
+                format!("optional<{}>", self.type_description(optional.item_type()))
+            }
+            Type::List(list) => format!("list<{}>", self.type_description(list.item_type())),
+            Type::Set(set) => format!("set<{}>", self.type_description(set.item_type())),
+            Type::Map(map) => format!(
+                "map<{}, {}>",
+                self.type_description(map.key_type()),
+                self.type_description(map.value_type())
+            ),
+            Type::Reference(name) => format!("{}.{}", name.package(), name.name()),
+            Type::External(external) => {
+                let name = external.external_reference();
+                format!("{}.{}", name.package(), name.name())
+            }
+        }
+    }
+
     // https://github.com/palantir/conjure-java/blob/develop/conjure-java-core/src/main/java/com/palantir/conjure/java/types/SafetyEvaluator.java
     pub fn is_safe_arg(&self, arg: &ArgumentDefinition) -> bool {
+        self.arg_log_safety(arg) == Some(LogSafety::Safe)
+    }
+
+    pub fn arg_log_safety(&self, arg: &ArgumentDefinition) -> Option<LogSafety> {
         if let Some(log_safety) = arg.safety() {
-            return *log_safety == LogSafety::Safe;
+            return Some(log_safety.clone());
         }
 
         if self.is_legacy_safe_arg(arg) {
-            return true;
+            return Some(LogSafety::Safe);
         }
 
-        self.type_log_safety(arg.type_()) == Some(LogSafety::Safe)
+        self.type_log_safety(arg.type_())
     }
 
     fn is_legacy_safe_arg(&self, arg: &ArgumentDefinition) -> bool {
@@ -35,11 +35,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getFileSystems",
-                    "/catalog/fileSystems",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getFileSystems",
+                        "/catalog/fileSystems",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&["javax.annotation.Nonnull"]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_default_serializable_response(response_)
@@ -69,11 +73,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "createDataset",
-                    "/catalog/datasets",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "createDataset",
+                        "/catalog/datasets",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_serializable_response(response_).await
@@ -98,11 +106,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getDataset",
-                    "/catalog/datasets/{datasetRid}",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getDataset",
+                        "/catalog/datasets/{datasetRid}",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_default_serializable_response(response_)
@@ -126,11 +138,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getRawData",
-                    "/catalog/datasets/{datasetRid}/raw",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getRawData",
+                        "/catalog/datasets/{datasetRid}/raw",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::decode_binary_response(response_)
@@ -153,11 +169,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getAliasedRawData",
-                    "/catalog/datasets/{datasetRid}/raw-aliased",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getAliasedRawData",
+                        "/catalog/datasets/{datasetRid}/raw-aliased",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::decode_binary_response(response_)
@@ -180,11 +200,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "maybeGetRawData",
-                    "/catalog/datasets/{datasetRid}/raw-maybe",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "maybeGetRawData",
+                        "/catalog/datasets/{datasetRid}/raw-maybe",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::decode_optional_binary_response(response_)
@@ -207,11 +231,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getAliasedString",
-                    "/catalog/datasets/{datasetRid}/string-aliased",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getAliasedString",
+                        "/catalog/datasets/{datasetRid}/string-aliased",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_serializable_response(response_).await
@@ -238,11 +266,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "uploadRawData",
-                    "/catalog/datasets/upload-raw",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "uploadRawData",
+                        "/catalog/datasets/upload-raw",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(false)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_empty_response(response_).await
@@ -269,11 +301,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "uploadAliasedRawData",
-                    "/catalog/datasets/upload-raw-aliased",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "uploadAliasedRawData",
+                        "/catalog/datasets/upload-raw-aliased",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(false)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_empty_response(response_).await
@@ -296,11 +332,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getBranches",
-                    "/catalog/datasets/{datasetRid}/branches",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getBranches",
+                        "/catalog/datasets/{datasetRid}/branches",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_default_serializable_response(response_)
@@ -326,11 +366,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getBranchesDeprecated",
-                    "/catalog/datasets/{datasetRid}/branchesDeprecated",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getBranchesDeprecated",
+                        "/catalog/datasets/{datasetRid}/branchesDeprecated",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_default_serializable_response(response_)
@@ -357,11 +401,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "resolveBranch",
-                    "/catalog/datasets/{datasetRid}/branches/{branch:.+}/resolve",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "resolveBranch",
+                        "/catalog/datasets/{datasetRid}/branches/{branch:.+}/resolve",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_default_serializable_response(response_)
@@ -385,11 +433,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testParam",
-                    "/catalog/datasets/{datasetRid}/testParam",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testParam",
+                        "/catalog/datasets/{datasetRid}/testParam",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_default_serializable_response(response_)
@@ -423,11 +475,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testQueryParams",
-                    "/catalog/test-query-params",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testQueryParams",
+                        "/catalog/test-query-params",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_serializable_response(response_).await
@@ -460,11 +516,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testNoResponseQueryParams",
-                    "/catalog/test-no-response-query-params",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testNoResponseQueryParams",
+                        "/catalog/test-no-response-query-params",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_empty_response(response_).await
@@ -484,11 +544,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testBoolean",
-                    "/catalog/boolean",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testBoolean",
+                        "/catalog/boolean",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_serializable_response(response_).await
@@ -508,11 +572,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testDouble",
-                    "/catalog/double",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testDouble",
+                        "/catalog/double",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_serializable_response(response_).await
@@ -532,11 +600,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testInteger",
-                    "/catalog/integer",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testInteger",
+                        "/catalog/integer",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_serializable_response(response_).await
@@ -559,11 +631,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testPostOptional",
-                    "/catalog/optional",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testPostOptional",
+                        "/catalog/optional",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_default_serializable_response(response_)
@@ -588,11 +664,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testOptionalIntegerAndDouble",
-                    "/catalog/optional-integer-double",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testOptionalIntegerAndDouble",
+                        "/catalog/optional-integer-double",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_).await?;
         conjure_http::private::async_decode_empty_response(response_).await
@@ -635,11 +715,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getFileSystems",
-                    "/catalog/fileSystems",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getFileSystems",
+                        "/catalog/fileSystems",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&["javax.annotation.Nonnull"]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_default_serializable_response(response_)
@@ -666,11 +750,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "createDataset",
-                    "/catalog/datasets",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "createDataset",
+                        "/catalog/datasets",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_serializable_response(response_)
@@ -695,11 +783,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getDataset",
-                    "/catalog/datasets/{datasetRid}",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getDataset",
+                        "/catalog/datasets/{datasetRid}",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_default_serializable_response(response_)
@@ -722,11 +814,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getRawData",
-                    "/catalog/datasets/{datasetRid}/raw",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getRawData",
+                        "/catalog/datasets/{datasetRid}/raw",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_binary_response(response_)
@@ -749,11 +845,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getAliasedRawData",
-                    "/catalog/datasets/{datasetRid}/raw-aliased",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getAliasedRawData",
+                        "/catalog/datasets/{datasetRid}/raw-aliased",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_binary_response(response_)
@@ -776,11 +876,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "maybeGetRawData",
-                    "/catalog/datasets/{datasetRid}/raw-maybe",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "maybeGetRawData",
+                        "/catalog/datasets/{datasetRid}/raw-maybe",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_optional_binary_response(response_)
@@ -803,11 +907,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getAliasedString",
-                    "/catalog/datasets/{datasetRid}/string-aliased",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getAliasedString",
+                        "/catalog/datasets/{datasetRid}/string-aliased",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_serializable_response(response_)
@@ -832,11 +940,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "uploadRawData",
-                    "/catalog/datasets/upload-raw",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "uploadRawData",
+                        "/catalog/datasets/upload-raw",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(false)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_empty_response(response_)
@@ -861,11 +973,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "uploadAliasedRawData",
-                    "/catalog/datasets/upload-raw-aliased",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "uploadAliasedRawData",
+                        "/catalog/datasets/upload-raw-aliased",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(false)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_empty_response(response_)
@@ -888,11 +1004,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getBranches",
-                    "/catalog/datasets/{datasetRid}/branches",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getBranches",
+                        "/catalog/datasets/{datasetRid}/branches",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_default_serializable_response(response_)
@@ -917,11 +1037,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "getBranchesDeprecated",
-                    "/catalog/datasets/{datasetRid}/branchesDeprecated",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "getBranchesDeprecated",
+                        "/catalog/datasets/{datasetRid}/branchesDeprecated",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_default_serializable_response(response_)
@@ -947,11 +1071,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "resolveBranch",
-                    "/catalog/datasets/{datasetRid}/branches/{branch:.+}/resolve",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "resolveBranch",
+                        "/catalog/datasets/{datasetRid}/branches/{branch:.+}/resolve",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_default_serializable_response(response_)
@@ -974,11 +1102,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testParam",
-                    "/catalog/datasets/{datasetRid}/testParam",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testParam",
+                        "/catalog/datasets/{datasetRid}/testParam",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_default_serializable_response(response_)
@@ -1009,11 +1141,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testQueryParams",
-                    "/catalog/test-query-params",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testQueryParams",
+                        "/catalog/test-query-params",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_serializable_response(response_)
@@ -1044,11 +1180,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testNoResponseQueryParams",
-                    "/catalog/test-no-response-query-params",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testNoResponseQueryParams",
+                        "/catalog/test-no-response-query-params",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_empty_response(response_)
@@ -1068,11 +1208,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testBoolean",
-                    "/catalog/boolean",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testBoolean",
+                        "/catalog/boolean",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_serializable_response(response_)
@@ -1092,11 +1236,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testDouble",
-                    "/catalog/double",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testDouble",
+                        "/catalog/double",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_serializable_response(response_)
@@ -1116,11 +1264,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testInteger",
-                    "/catalog/integer",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testInteger",
+                        "/catalog/integer",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_serializable_response(response_)
@@ -1143,11 +1295,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testPostOptional",
-                    "/catalog/optional",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testPostOptional",
+                        "/catalog/optional",
+                    )
+                    .with_method(conjure_http::private::http::Method::POST)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_default_serializable_response(response_)
@@ -1171,11 +1327,15 @@ where
             .extensions_mut()
             .insert(
                 conjure_http::client::Endpoint::new(
-                    "TestService",
-                    conjure_http::private::Option::None,
-                    "testOptionalIntegerAndDouble",
-                    "/catalog/optional-integer-double",
-                ),
+                        "TestService",
+                        conjure_http::private::Option::None,
+                        "testOptionalIntegerAndDouble",
+                        "/catalog/optional-integer-double",
+                    )
+                    .with_method(conjure_http::private::http::Method::GET)
+                    .with_replayable_body(true)
+                    .with_tags(&[])
+                    .with_markers(&[]),
             );
         let response_ = self.0.send(request_)?;
         conjure_http::private::decode_empty_response(response_)
@@ -1542,6 +1702,22 @@ impl<T> conjure_http::server::EndpointMetadata for GetFileSystemsEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("map<string, com.palantir.product.datasets.BackingFileSystem>")
+    }
+    fn markers(&self) -> &[&str] {
+        &["javax.annotation.Nonnull"]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for GetFileSystemsEndpoint_<T>
 where
@@ -1562,7 +1738,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_file_systems(auth_)?;
-        Ok(conjure_http::private::encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 #[conjure_http::private::async_trait]
@@ -1588,7 +1769,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_file_systems(auth_).await?;
-        Ok(conjure_http::private::async_encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct CreateDatasetEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -1618,6 +1804,34 @@ impl<T> conjure_http::server::EndpointMetadata for CreateDatasetEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                "request",
+                conjure_http::server::ParameterType::Body,
+                "com.palantir.product.CreateDatasetRequest",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                    "testHeaderArg",
+                    conjure_http::server::ParameterType::Header("Test-Header"),
+                    "string",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("com.palantir.product.datasets.Dataset")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for CreateDatasetEndpoint_<T>
 where
@@ -1635,9 +1849,10 @@ where
         conjure_http::private::Error,
     > {
         let (parts_, body_) = request.into_parts();
-        let request = conjure_http::private::decode_serializable_request(
+        let request = conjure_http::private::decode_serializable_request2(
             &parts_,
             body_,
+            conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT,
         )?;
         let test_header_arg = conjure_http::private::parse_required_header(
             &parts_,
@@ -1646,7 +1861,7 @@ where
         )?;
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         let response_ = self.0.create_dataset(auth_, request, test_header_arg)?;
-        Ok(conjure_http::private::encode_serializable_response(&response_))
+        Ok(conjure_http::private::encode_serializable_response2(&parts_, &response_)?)
     }
 }
 #[conjure_http::private::async_trait]
@@ -1669,9 +1884,10 @@ where
         I: 'async_trait,
     {
         let (parts_, body_) = request.into_parts();
-        let request = conjure_http::private::async_decode_serializable_request(
+        let request = conjure_http::private::async_decode_serializable_request2(
                 &parts_,
                 body_,
+                conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT,
             )
             .await?;
         let test_header_arg = conjure_http::private::parse_required_header(
@@ -1681,7 +1897,12 @@ where
         )?;
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         let response_ = self.0.create_dataset(auth_, request, test_header_arg).await?;
-        Ok(conjure_http::private::async_encode_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct GetDatasetEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -1715,6 +1936,29 @@ impl<T> conjure_http::server::EndpointMetadata for GetDatasetEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("optional<com.palantir.product.datasets.Dataset>")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for GetDatasetEndpoint_<T>
 where
@@ -1739,7 +1983,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_dataset(auth_, dataset_rid)?;
-        Ok(conjure_http::private::encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 #[conjure_http::private::async_trait]
@@ -1769,7 +2018,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_dataset(auth_, dataset_rid).await?;
-        Ok(conjure_http::private::async_encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct GetRawDataEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -1806,6 +2060,29 @@ impl<T> conjure_http::server::EndpointMetadata for GetRawDataEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("binary")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for GetRawDataEndpoint_<T>
 where
@@ -1897,6 +2174,29 @@ impl<T> conjure_http::server::EndpointMetadata for GetAliasedRawDataEndpoint_<T>
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("com.palantir.product.NestedAliasedBinary")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for GetAliasedRawDataEndpoint_<T>
 where
@@ -1988,6 +2288,31 @@ impl<T> conjure_http::server::EndpointMetadata for MaybeGetRawDataEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(
+                    &["com.palantir.redaction.Safe", "javax.annotation.Nonnull"],
+                ),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("optional<binary>")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for MaybeGetRawDataEndpoint_<T>
 where
@@ -2079,6 +2404,29 @@ impl<T> conjure_http::server::EndpointMetadata for GetAliasedStringEndpoint_<T>
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("com.palantir.product.AliasedString")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for GetAliasedStringEndpoint_<T>
 where
@@ -2103,7 +2451,7 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_aliased_string(auth_, dataset_rid)?;
-        Ok(conjure_http::private::encode_serializable_response(&response_))
+        Ok(conjure_http::private::encode_serializable_response2(&parts_, &response_)?)
     }
 }
 #[conjure_http::private::async_trait]
@@ -2133,7 +2481,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_aliased_string(auth_, dataset_rid).await?;
-        Ok(conjure_http::private::async_encode_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct UploadRawDataEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -2166,6 +2519,29 @@ impl<T> conjure_http::server::EndpointMetadata for UploadRawDataEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "input",
+                    conjure_http::server::ParameterType::Body,
+                    "binary",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        None
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for UploadRawDataEndpoint_<T>
 where
@@ -2245,6 +2621,28 @@ impl<T> conjure_http::server::EndpointMetadata for UploadAliasedRawDataEndpoint_
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                "input",
+                conjure_http::server::ParameterType::Body,
+                "com.palantir.product.NestedAliasedBinary",
+            ),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        None
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for UploadAliasedRawDataEndpoint_<T>
 where
@@ -2329,6 +2727,29 @@ impl<T> conjure_http::server::EndpointMetadata for GetBranchesEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("set<string>")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for GetBranchesEndpoint_<T>
 where
@@ -2353,7 +2774,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_branches(auth_, dataset_rid)?;
-        Ok(conjure_http::private::encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 #[conjure_http::private::async_trait]
@@ -2383,7 +2809,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_branches(auth_, dataset_rid).await?;
-        Ok(conjure_http::private::async_encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct GetBranchesDeprecatedEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -2420,6 +2851,29 @@ impl<T> conjure_http::server::EndpointMetadata for GetBranchesDeprecatedEndpoint
     fn deprecated(&self) -> Option<&str> {
         Some("use getBranches instead")
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("set<string>")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for GetBranchesDeprecatedEndpoint_<T>
 where
@@ -2444,7 +2898,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_branches_deprecated(auth_, dataset_rid)?;
-        Ok(conjure_http::private::encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 #[conjure_http::private::async_trait]
@@ -2475,7 +2934,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.get_branches_deprecated(auth_, dataset_rid).await?;
-        Ok(conjure_http::private::async_encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct ResolveBranchEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -2519,6 +2983,34 @@ impl<T> conjure_http::server::EndpointMetadata for ResolveBranchEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+            conjure_http::server::ArgumentMetadata::new(
+                "branch",
+                conjure_http::server::ParameterType::Path,
+                "string",
+            ),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("optional<string>")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for ResolveBranchEndpoint_<T>
 where
@@ -2544,7 +3036,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.resolve_branch(auth_, dataset_rid, branch)?;
-        Ok(conjure_http::private::encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 #[conjure_http::private::async_trait]
@@ -2575,7 +3072,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.resolve_branch(auth_, dataset_rid, branch).await?;
-        Ok(conjure_http::private::async_encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct TestParamEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -2612,6 +3114,29 @@ impl<T> conjure_http::server::EndpointMetadata for TestParamEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                    "datasetRid",
+                    conjure_http::server::ParameterType::Path,
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("optional<string>")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for TestParamEndpoint_<T>
 where
@@ -2636,7 +3161,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.test_param(auth_, dataset_rid)?;
-        Ok(conjure_http::private::encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 #[conjure_http::private::async_trait]
@@ -2666,7 +3196,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.test_param(auth_, dataset_rid).await?;
-        Ok(conjure_http::private::async_encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct TestQueryParamsEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -2696,6 +3231,54 @@ impl<T> conjure_http::server::EndpointMetadata for TestQueryParamsEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                "query",
+                conjure_http::server::ParameterType::Body,
+                "string",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                    "something",
+                    conjure_http::server::ParameterType::Query("different"),
+                    "rid",
+                )
+                .with_markers(&["com.palantir.redaction.Safe"]),
+            conjure_http::server::ArgumentMetadata::new(
+                "optionalMiddle",
+                conjure_http::server::ParameterType::Query("optionalMiddle"),
+                "optional<rid>",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "implicit",
+                conjure_http::server::ParameterType::Query("implicit"),
+                "rid",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "setEnd",
+                conjure_http::server::ParameterType::Query("setEnd"),
+                "set<string>",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "optionalEnd",
+                conjure_http::server::ParameterType::Query("optionalEnd"),
+                "optional<rid>",
+            ),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("integer")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for TestQueryParamsEndpoint_<T>
 where
@@ -2714,7 +3297,11 @@ where
     > {
         let (parts_, body_) = request.into_parts();
         let query_params_ = conjure_http::private::parse_query_params(&parts_);
-        let query = conjure_http::private::decode_serializable_request(&parts_, body_)?;
+        let query = conjure_http::private::decode_serializable_request2(
+            &parts_,
+            body_,
+            conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT,
+        )?;
         let something = conjure_http::private::parse_query_param(
             &query_params_,
             "something",
@@ -2758,7 +3345,7 @@ where
                 set_end,
                 optional_end,
             )?;
-        Ok(conjure_http::private::encode_serializable_response(&response_))
+        Ok(conjure_http::private::encode_serializable_response2(&parts_, &response_)?)
     }
 }
 #[conjure_http::private::async_trait]
@@ -2782,9 +3369,10 @@ where
     {
         let (parts_, body_) = request.into_parts();
         let query_params_ = conjure_http::private::parse_query_params(&parts_);
-        let query = conjure_http::private::async_decode_serializable_request(
+        let query = conjure_http::private::async_decode_serializable_request2(
                 &parts_,
                 body_,
+                conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT,
             )
             .await?;
         let something = conjure_http::private::parse_query_param(
@@ -2831,7 +3419,12 @@ where
                 optional_end,
             )
             .await?;
-        Ok(conjure_http::private::async_encode_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct TestNoResponseQueryParamsEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -2862,6 +3455,53 @@ for TestNoResponseQueryParamsEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                "query",
+                conjure_http::server::ParameterType::Body,
+                "string",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "something",
+                conjure_http::server::ParameterType::Query("different"),
+                "rid",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "optionalMiddle",
+                conjure_http::server::ParameterType::Query("optionalMiddle"),
+                "optional<rid>",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "implicit",
+                conjure_http::server::ParameterType::Query("implicit"),
+                "rid",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "setEnd",
+                conjure_http::server::ParameterType::Query("setEnd"),
+                "set<string>",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "optionalEnd",
+                conjure_http::server::ParameterType::Query("optionalEnd"),
+                "optional<rid>",
+            ),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        None
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O>
 for TestNoResponseQueryParamsEndpoint_<T>
@@ -2881,7 +3521,11 @@ where
     > {
         let (parts_, body_) = request.into_parts();
         let query_params_ = conjure_http::private::parse_query_params(&parts_);
-        let query = conjure_http::private::decode_serializable_request(&parts_, body_)?;
+        let query = conjure_http::private::decode_serializable_request2(
+            &parts_,
+            body_,
+            conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT,
+        )?;
         let something = conjure_http::private::parse_query_param(
             &query_params_,
             "something",
@@ -2949,9 +3593,10 @@ where
     {
         let (parts_, body_) = request.into_parts();
         let query_params_ = conjure_http::private::parse_query_params(&parts_);
-        let query = conjure_http::private::async_decode_serializable_request(
+        let query = conjure_http::private::async_decode_serializable_request2(
                 &parts_,
                 body_,
+                conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT,
             )
             .await?;
         let something = conjure_http::private::parse_query_param(
@@ -3027,6 +3672,22 @@ impl<T> conjure_http::server::EndpointMetadata for TestBooleanEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("boolean")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for TestBooleanEndpoint_<T>
 where
@@ -3047,7 +3708,7 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.test_boolean(auth_)?;
-        Ok(conjure_http::private::encode_serializable_response(&response_))
+        Ok(conjure_http::private::encode_serializable_response2(&parts_, &response_)?)
     }
 }
 #[conjure_http::private::async_trait]
@@ -3073,7 +3734,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.test_boolean(auth_).await?;
-        Ok(conjure_http::private::async_encode_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct TestDoubleEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -3103,6 +3769,22 @@ impl<T> conjure_http::server::EndpointMetadata for TestDoubleEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("double")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for TestDoubleEndpoint_<T>
 where
@@ -3123,7 +3805,7 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.test_double(auth_)?;
-        Ok(conjure_http::private::encode_serializable_response(&response_))
+        Ok(conjure_http::private::encode_serializable_response2(&parts_, &response_)?)
     }
 }
 #[conjure_http::private::async_trait]
@@ -3149,7 +3831,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.test_double(auth_).await?;
-        Ok(conjure_http::private::async_encode_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct TestIntegerEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -3179,6 +3866,22 @@ impl<T> conjure_http::server::EndpointMetadata for TestIntegerEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("integer")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for TestIntegerEndpoint_<T>
 where
@@ -3199,7 +3902,7 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.test_integer(auth_)?;
-        Ok(conjure_http::private::encode_serializable_response(&response_))
+        Ok(conjure_http::private::encode_serializable_response2(&parts_, &response_)?)
     }
 }
 #[conjure_http::private::async_trait]
@@ -3225,7 +3928,12 @@ where
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         conjure_http::private::decode_empty_request(&parts_, body_)?;
         let response_ = self.0.test_integer(auth_).await?;
-        Ok(conjure_http::private::async_encode_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct TestPostOptionalEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -3255,6 +3963,28 @@ impl<T> conjure_http::server::EndpointMetadata for TestPostOptionalEndpoint_<T>
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                "maybeString",
+                conjure_http::server::ParameterType::Body,
+                "optional<string>",
+            ),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        Some("optional<string>")
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O> for TestPostOptionalEndpoint_<T>
 where
@@ -3272,13 +4002,19 @@ where
         conjure_http::private::Error,
     > {
         let (parts_, body_) = request.into_parts();
-        let maybe_string = conjure_http::private::decode_optional_serializable_request(
+        let maybe_string = conjure_http::private::decode_optional_serializable_request2(
             &parts_,
             body_,
+            conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT,
         )?;
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         let response_ = self.0.test_post_optional(auth_, maybe_string)?;
-        Ok(conjure_http::private::encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 #[conjure_http::private::async_trait]
@@ -3301,14 +4037,20 @@ where
         I: 'async_trait,
     {
         let (parts_, body_) = request.into_parts();
-        let maybe_string = conjure_http::private::async_decode_optional_serializable_request(
+        let maybe_string = conjure_http::private::async_decode_optional_serializable_request2(
                 &parts_,
                 body_,
+                conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT,
             )
             .await?;
         let auth_ = conjure_http::private::parse_header_auth(&parts_)?;
         let response_ = self.0.test_post_optional(auth_, maybe_string).await?;
-        Ok(conjure_http::private::async_encode_default_serializable_response(&response_))
+        Ok(
+            conjure_http::private::async_encode_default_serializable_response2(
+                &parts_,
+                &response_,
+            )?,
+        )
     }
 }
 struct TestOptionalIntegerAndDoubleEndpoint_<T>(conjure_http::private::Arc<T>);
@@ -3339,6 +4081,33 @@ for TestOptionalIntegerAndDoubleEndpoint_<T> {
     fn deprecated(&self) -> Option<&str> {
         None
     }
+    fn auth(&self) -> Option<conjure_http::server::AuthType> {
+        Some(conjure_http::server::AuthType::Header)
+    }
+    fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+        const ARGS: &[conjure_http::server::ArgumentMetadata] = &[
+            conjure_http::server::ArgumentMetadata::new(
+                "maybeInteger",
+                conjure_http::server::ParameterType::Query("maybeInteger"),
+                "optional<integer>",
+            ),
+            conjure_http::server::ArgumentMetadata::new(
+                "maybeDouble",
+                conjure_http::server::ParameterType::Query("maybeDouble"),
+                "optional<double>",
+            ),
+        ];
+        ARGS
+    }
+    fn returns(&self) -> Option<&str> {
+        None
+    }
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 impl<T, I, O> conjure_http::server::Endpoint<I, O>
 for TestOptionalIntegerAndDoubleEndpoint_<T>
@@ -1,2 +1,3 @@
 pub mod another;
 pub mod product;
+pub mod test;
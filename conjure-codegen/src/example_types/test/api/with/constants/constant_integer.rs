@@ -0,0 +1 @@
+pub const CONSTANT_INTEGER: i32 = 123;
@@ -0,0 +1,254 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use failure::{format_err, Error};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::{HashMap, HashSet};
+
+use crate::context::Context;
+use crate::types::{
+    ConjureDefinition, FieldDefinition, ParameterType, Type, TypeDefinition, TypeName,
+};
+use crate::ExternalType;
+
+/// Checks that user-provided Rust types for external references are valid types, and are declared
+/// to implement the traits required by the places they're used.
+///
+/// The problems found are returned so they can be reported along with the rest of the validation
+/// pass.
+pub fn validate(ctx: &Context, defs: &ConjureDefinition) -> Vec<Error> {
+    let mut validator = Validator {
+        ctx,
+        aliases: defs
+            .types()
+            .iter()
+            .filter_map(|def| match def {
+                TypeDefinition::Alias(def) => Some((def.type_name(), def.alias())),
+                _ => None,
+            })
+            .collect(),
+        visiting: HashSet::new(),
+        errors: vec![],
+    };
+
+    let mut external_types = ctx.external_types().iter().collect::<Vec<_>>();
+    external_types.sort_by(|a, b| a.0.cmp(b.0));
+    for (type_name, external) in external_types {
+        if syn::parse_str::<syn::Type>(&external.path).is_err() {
+            validator.errors.push(format_err!(
+                "external type {}: invalid Rust type `{}`",
+                name(type_name),
+                external.path,
+            ));
+        }
+    }
+
+    for def in defs.types() {
+        if ctx.is_extern(crate::type_definition_name(def)) {
+            continue;
+        }
+
+        // generated types implement Eq, Ord, and Hash, so their contents must as well
+        match def {
+            TypeDefinition::Alias(def) => {
+                let location = format!("alias {}", name(def.type_name()));
+                validator.validate_type(def.alias(), true, false, &location);
+            }
+            TypeDefinition::Object(def) => {
+                let location = format!("object {}", name(def.type_name()));
+                validator.validate_fields(&location, "field", def.fields());
+            }
+            TypeDefinition::Union(def) => {
+                let location = format!("union {}", name(def.type_name()));
+                validator.validate_fields(&location, "variant", def.union_());
+            }
+            TypeDefinition::Enum(_) | TypeDefinition::Constant(_) => {}
+        }
+    }
+
+    for def in defs.errors() {
+        if ctx.is_extern(def.error_name()) {
+            continue;
+        }
+
+        let location = format!("error {}", name(def.error_name()));
+        validator.validate_fields(&location, "field", def.safe_args());
+        validator.validate_fields(&location, "field", def.unsafe_args());
+    }
+
+    for def in defs.services() {
+        if ctx.is_extern(def.service_name()) {
+            continue;
+        }
+
+        for endpoint in def.endpoints() {
+            let location = format!(
+                "service {}, endpoint {}",
+                name(def.service_name()),
+                endpoint.endpoint_name(),
+            );
+
+            for arg in endpoint.args() {
+                let location = format!("{}, argument {}", location, arg.arg_name());
+                // path, query, and header parameters are encoded in the PLAIN format
+                let plain = !matches!(arg.param_type(), ParameterType::Body(_));
+                validator.validate_type(arg.type_(), false, plain, &location);
+            }
+
+            if let Some(returns) = endpoint.returns() {
+                let location = format!("{}, return type", location);
+                validator.validate_type(returns, false, false, &location);
+            }
+        }
+    }
+
+    validator.errors
+}
+
+/// Generates compile-time assertions that the user-provided Rust types for external references
+/// implement the traits they're declared to.
+///
+/// Without these, a type which doesn't match its declaration causes compilation errors deep in the
+/// generated code that uses it.
+pub fn assertions(external_types: &HashMap<TypeName, ExternalType>) -> TokenStream {
+    let mut external_types = external_types.values().collect::<Vec<_>>();
+    external_types.sort_by(|a, b| a.path.cmp(&b.path));
+    external_types.dedup_by(|a, b| a.path == b.path);
+
+    if external_types.is_empty() {
+        return quote!();
+    }
+
+    let assertions = external_types.iter().map(|external| {
+        // the path is checked to be a valid type by validate
+        let path = external.path.parse::<TokenStream>().unwrap();
+        let ord = if external.ord {
+            quote!(assert_ord::<#path>();)
+        } else {
+            quote!()
+        };
+        let plain = if external.plain {
+            quote!(assert_plain::<#path>();)
+        } else {
+            quote!()
+        };
+        let copy = if external.copy {
+            quote!(assert_copy::<#path>();)
+        } else {
+            quote!()
+        };
+
+        quote! {
+            assert_base::<#path>();
+            #ord
+            #plain
+            #copy
+        }
+    });
+
+    quote! {
+        const _: fn() = || {
+            fn assert_base<T>()
+            where
+                T: std::fmt::Debug
+                    + Clone
+                    + conjure_object::serde::Serialize
+                    + conjure_object::serde::de::DeserializeOwned,
+            {
+            }
+            fn assert_ord<T>()
+            where
+                T: PartialEq + Eq + PartialOrd + Ord + std::hash::Hash,
+            {
+            }
+            fn assert_plain<T>()
+            where
+                T: conjure_object::Plain + conjure_object::FromPlain,
+            {
+            }
+            fn assert_copy<T>()
+            where
+                T: Copy,
+            {
+            }
+
+            #(#assertions)*
+        };
+    }
+}
+
+fn name(name: &TypeName) -> String {
+    format!("{}.{}", name.package(), name.name())
+}
+
+struct Validator<'a> {
+    ctx: &'a Context,
+    aliases: HashMap<&'a TypeName, &'a Type>,
+    // the aliases currently being validated, to avoid recursing through alias cycles, which are
+    // reported by the rest of the validation pass
+    visiting: HashSet<&'a TypeName>,
+    errors: Vec<Error>,
+}
+
+impl<'a> Validator<'a> {
+    fn validate_fields(&mut self, location: &str, kind: &str, fields: &'a [FieldDefinition]) {
+        for field in fields {
+            let location = format!("{}, {} {}", location, kind, field.field_name());
+            self.validate_type(field.type_(), true, false, &location);
+        }
+    }
+
+    fn validate_type(&mut self, ty: &'a Type, ord: bool, plain: bool, location: &str) {
+        match ty {
+            Type::Primitive(_) => {}
+            Type::Optional(def) => self.validate_type(def.item_type(), ord, plain, location),
+            Type::List(def) => self.validate_type(def.item_type(), ord, plain, location),
+            Type::Set(def) => self.validate_type(def.item_type(), true, plain, location),
+            Type::Map(def) => {
+                self.validate_type(def.key_type(), true, plain, location);
+                self.validate_type(def.value_type(), ord, plain, location);
+            }
+            // aliases only implement the PLAIN format traits if the aliased type does
+            Type::Reference(name) => match self.aliases.get(name) {
+                Some(&alias) if plain && self.visiting.insert(name) => {
+                    self.validate_type(alias, true, plain, location);
+                    self.visiting.remove(name);
+                }
+                _ => {}
+            },
+            Type::External(def) => {
+                let external = match self.ctx.external_type(def) {
+                    Some(external) => external,
+                    None => return,
+                };
+                let name = name(def.external_reference());
+
+                if ord && !external.ord {
+                    self.errors.push(format_err!(
+                        "{}: external type {} must implement Eq, Ord, and Hash",
+                        location,
+                        name,
+                    ));
+                }
+                if plain && !external.plain {
+                    self.errors.push(format_err!(
+                        "{}: external type {} must implement Plain and FromPlain",
+                        location,
+                        name,
+                    ));
+                }
+            }
+        }
+    }
+}
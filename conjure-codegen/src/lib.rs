@@ -21,7 +21,7 @@
 //!
 //! ```no_run
 //! use std::env;
-//! use std::path::Path;
+//! use std::path::{Path, PathBuf};
 //!
 //! fn main() {
 //!     let input = "service-api.conjure.json";
@@ -44,6 +44,37 @@
 //! }
 //! ```
 //!
+//! Alternatively, the `include_ir!` macro from the `conjure-codegen-macros` crate expands the
+//! generated code inline without a build script:
+//!
+//! ```ignore
+//! mod service_api {
+//!     conjure_codegen_macros::include_ir!(
+//!         "service-api.conjure.json",
+//!         strip_prefix = "com.foobar.service",
+//!     );
+//! }
+//! ```
+//!
+//! [`Config::generate_module`] provides access to the generated code in memory for other uses,
+//! such as custom procedural macros. The `proc-macro` Cargo feature must be enabled in that case.
+//!
+//! ## Shared types
+//!
+//! Types which are shared between several Conjure APIs can be generated once and referenced from the code generated
+//! for each API. [`Config::extern_package`] maps a Conjure package to the Rust module its types were generated into,
+//! and [`Config::additional_ir_file`] provides the definitions of types which aren't already present in the primary IR
+//! file:
+//!
+//! ```no_run
+//! conjure_codegen::Config::new()
+//!     .strip_prefix("com.foobar.service".to_string())
+//!     .extern_package("com.foobar.common", "common_api")
+//!     .additional_ir_file("common-api.conjure.json")
+//!     .generate_files("service-api.conjure.json", "src/service_api")
+//!     .unwrap();
+//! ```
+//!
 //! # Types
 //!
 //! ## Builtin
@@ -80,6 +111,20 @@
 //!
 //! Conjure aliases, objects, and unions wrapping `double` types have trait implementations which use the same logic.
 //!
+//! ## External
+//!
+//! Conjure external type references are represented by their fallback type by default. [`Config::external_type`] maps
+//! an external type to a Rust type instead:
+//!
+//! ```no_run
+//! use conjure_codegen::{Config, ExternalType};
+//!
+//! Config::new()
+//!     .external_type("java.lang.Long", ExternalType::new("i64").copy(true))
+//!     .generate_files("service-api.conjure.json", "src/service_api")
+//!     .unwrap();
+//! ```
+//!
 //! ## Objects
 //!
 //! Conjure objects turn into Rust structs along with builders used to construct them:
@@ -177,6 +222,20 @@
 //! `Hash`, `Serialize`, and `Deserialize`. They also implement `Copy` if they wrap a copyable primitive type, `Default`
 //! if they wrap a type implementing `Default`, and `Display` if they wrap a type implementing `Display`.
 //!
+//! ## Constants
+//!
+//! Conjure constants turn into Rust constants in the module of their package:
+//!
+//! ```
+//! # use conjure_codegen::example_types::test::api::with::constants::{CONSTANT_INTEGER, CONSTANT_STRING};
+//! assert_eq!(CONSTANT_STRING, "hello");
+//! assert_eq!(CONSTANT_INTEGER, 123);
+//! ```
+//!
+//! String constants are `&str`s, and other constants use their normal Rust types. The `datetime`, `rid`, and
+//! `bearertoken` types can't be created in a `const` context, so constants of those types are instead lazily
+//! initialized statics which dereference to the value. `binary` and `any` constants are not supported.
+//!
 //! ## Errors
 //!
 //! Conjure errors turn into Rust structs storing the error's parameters as if it were a Conjure object. The struct
@@ -280,20 +339,27 @@
 //!
 //! * `server-request-context` - The generated server trait method will have an additional
 //!     `RequestContext` argument providing lower level access to request and response information.
+//! * `server-request-size-limit:<bytes>` - The generated server will reject serializable request
+//!   bodies larger than the specified number of bytes with a `RequestEntityTooLarge` error, rather
+//!   than the default limit of 50 MiB.
+//! * `server-streaming-response` - The generated blocking server will serialize the endpoint's
+//!   response directly into the response body rather than buffering it in memory. The response
+//!   writer of the generated service must implement `std::io::Write`.
 #![warn(clippy::all, missing_docs)]
 #![allow(clippy::needless_doctest_main)]
 #![recursion_limit = "256"]
 
 use crate::context::Context;
-use crate::types::{ConjureDefinition, ConstantDefinition, TypeDefinition, TypeName};
-use failure::{bail, Error, ResultExt};
+use crate::types::{ConjureDefinition, TypeDefinition, TypeName};
+use failure::{bail, Error, Fail, ResultExt};
 use proc_macro2::TokenStream;
 use quote::quote;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::ffi::OsStr;
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 mod aliases;
 mod cargo_toml;
@@ -302,6 +368,7 @@ mod constants;
 mod context;
 mod enums;
 mod errors;
+mod externals;
 mod http_paths;
 mod objects;
 mod servers;
@@ -309,6 +376,7 @@ mod servers;
 #[rustfmt::skip]
 mod types;
 mod unions;
+mod validation;
 
 /// Examples of generated Conjure code.
 ///
@@ -327,9 +395,15 @@ struct CrateInfo {
 pub struct Config {
     exhaustive: bool,
     staged_builders: bool,
+    prefer_smile: bool,
+    streaming_response_threshold: Option<usize>,
+    token_provider: bool,
     strip_prefix: Option<String>,
     version: Option<String>,
     build_crate: Option<CrateInfo>,
+    extern_packages: BTreeMap<String, String>,
+    external_types: BTreeMap<String, ExternalType>,
+    additional_ir_files: Vec<PathBuf>,
 }
 
 impl Default for Config {
@@ -344,9 +418,15 @@ impl Config {
         Config {
             exhaustive: false,
             staged_builders: false,
+            prefer_smile: false,
+            streaming_response_threshold: None,
+            token_provider: false,
             strip_prefix: None,
             version: None,
             build_crate: None,
+            extern_packages: BTreeMap::new(),
+            external_types: BTreeMap::new(),
+            additional_ir_files: vec![],
         }
     }
 
@@ -371,6 +451,51 @@ impl Config {
         self
     }
 
+    /// If enabled, generated clients will request Smile-encoded responses rather than JSON.
+    ///
+    /// Smile is a binary encoding which is more compact and faster to process than JSON. Generated clients decode
+    /// responses in either format regardless of this setting, and servers which do not support Smile will fall back
+    /// to JSON.
+    ///
+    /// Defaults to `false`.
+    pub fn prefer_smile(&mut self, prefer_smile: bool) -> &mut Config {
+        self.prefer_smile = prefer_smile;
+        self
+    }
+
+    /// Sets a response size above which generated server endpoints stream serializable responses directly into the
+    /// response body rather than buffering them in memory.
+    ///
+    /// This applies to all endpoints returning a non-binary type, and requires the response writer of the generated
+    /// service to implement `std::io::Write` for blocking services and `futures_io::AsyncWrite` for async services.
+    /// Async services serialize streamed responses on a separate thread. See `StreamingConjureResponseSerializer` in
+    /// `conjure-http` for details on how errors are handled while streaming.
+    ///
+    /// Defaults to `None`.
+    pub fn streaming_response_threshold<T>(
+        &mut self,
+        streaming_response_threshold: T,
+    ) -> &mut Config
+    where
+        T: Into<Option<usize>>,
+    {
+        self.streaming_response_threshold = streaming_response_threshold.into();
+        self
+    }
+
+    /// If enabled, generated clients will obtain bearer tokens from a `TokenProvider` rather than taking them as
+    /// an argument to each authenticated endpoint.
+    ///
+    /// Clients generated in this mode are created with an inherent `new` method taking the provider along with the
+    /// HTTP client, rather than through the `Service` or `AsyncService` traits. Async clients take an
+    /// `AsyncTokenProvider` instead.
+    ///
+    /// Defaults to `false`.
+    pub fn token_provider(&mut self, token_provider: bool) -> &mut Config {
+        self.token_provider = token_provider;
+        self
+    }
+
     /// No longer used.
     #[deprecated(note = "no longer used", since = "1.2.0")]
     pub fn run_rustfmt(&mut self, _run_rustfmt: bool) -> &mut Config {
@@ -419,6 +544,48 @@ impl Config {
         self
     }
 
+    /// Maps a Conjure package to a Rust module path in another crate.
+    ///
+    /// Types, errors, and services in the package and its subpackages will not be generated. References to them
+    /// will instead resolve to the specified path, followed by the modules of any subpackage. For example, with
+    /// `extern_package("com.foo.common", "common_api")` the type `com.foo.common.Bar` resolves to `common_api::Bar`
+    /// and `com.foo.common.baz.Qux` resolves to `common_api::baz::Qux`. This matches the layout of code generated for
+    /// the package with a [`Self::strip_prefix`] of the package's name.
+    ///
+    /// The path must be valid from every module of the generated code, so it should typically either be the name of
+    /// a crate or start with `crate::`. If a package is covered by multiple mappings, the most specific one is used.
+    pub fn extern_package(&mut self, package: &str, path: &str) -> &mut Config {
+        self.extern_packages
+            .insert(package.to_string(), path.to_string());
+        self
+    }
+
+    /// Maps a Conjure external type to a Rust type.
+    ///
+    /// The name is the fully qualified name of the external type, for example `java.lang.Long`. References to the
+    /// external type will use the provided Rust type rather than the fallback type specified in the IR. Code
+    /// generation will fail if the type is used somewhere that requires a trait implementation it was not declared to
+    /// have.
+    ///
+    /// Defaults to using the fallback type of every external type.
+    pub fn external_type(&mut self, name: &str, external_type: ExternalType) -> &mut Config {
+        self.external_types.insert(name.to_string(), external_type);
+        self
+    }
+
+    /// Adds a JSON-encoded Conjure IR file whose definitions are combined with those of the primary IR file.
+    ///
+    /// Definitions present in more than one IR file must be identical. Combined with [`Self::extern_package`], this
+    /// can be used to provide the IR of dependencies whose types have already been generated in another crate.
+    pub fn additional_ir_file<P>(&mut self, ir_file: P) -> &mut Config
+    where
+        P: AsRef<Path>,
+    {
+        self.additional_ir_files
+            .push(ir_file.as_ref().to_path_buf());
+        self
+    }
+
     /// Generates Rust source files from a JSON-encoded Conjure IR file.
     pub fn generate_files<P, Q>(&self, ir_file: P, out_dir: Q) -> Result<(), Error>
     where
@@ -429,13 +596,9 @@ impl Config {
     }
 
     fn generate_files_inner(&self, ir_file: &Path, out_dir: &Path) -> Result<(), Error> {
-        let defs = self.parse_ir(ir_file)?;
-
-        if defs.version() != 1 {
-            bail!("unsupported IR version {}", defs.version());
-        }
+        let defs = self.load_ir(ir_file)?;
+        let module = self.generate_module_inner(&defs)?;
 
-        let modules = self.create_modules(&defs);
         let (src_dir, lib_root) = if self.build_crate.is_some() {
             (out_dir.join("src"), true)
         } else {
@@ -447,52 +610,151 @@ impl Config {
             self.write_rustfmt_toml(out_dir)?;
         }
 
-        modules.render(&src_dir, lib_root)?;
+        module.render(&src_dir, lib_root)?;
 
         Ok(())
     }
 
+    /// Generates an in-memory Rust module from a JSON-encoded Conjure IR file.
+    ///
+    /// This is intended for contexts like procedural macros which embed the generated code
+    /// directly rather than writing it to disk. The `build_crate` setting is ignored.
+    pub fn generate_module<P>(&self, ir_file: P) -> Result<Module, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let defs = self.load_ir(ir_file.as_ref())?;
+        self.generate_module_inner(&defs)
+    }
+
+    fn generate_module_inner(&self, defs: &ConjureDefinition) -> Result<Module, Error> {
+        let mut extern_packages = BTreeMap::new();
+        for (package, path) in &self.extern_packages {
+            let path = syn::parse_str::<syn::Path>(path).with_context(|_| {
+                format!("invalid path `{}` for extern package {}", path, package)
+            })?;
+            extern_packages.insert(package.clone(), quote!(#path));
+        }
+
+        let mut external_types = HashMap::new();
+        for (name, external_type) in &self.external_types {
+            let name = match name.rsplit_once('.') {
+                Some((package, name)) => TypeName::new(name, package),
+                None => TypeName::new(name.as_str(), ""),
+            };
+            external_types.insert(name, external_type.clone());
+        }
+
+        Ok(self
+            .create_modules(defs, extern_packages, external_types)?
+            .into_module(String::new()))
+    }
+
+    fn load_ir(&self, ir_file: &Path) -> Result<ConjureDefinition, Error> {
+        let defs = self.parse_ir(ir_file)?;
+        if self.additional_ir_files.is_empty() {
+            return Ok(defs);
+        }
+
+        let mut errors = defs.errors().to_vec();
+        let mut types = defs.types().to_vec();
+        let mut services = defs.services().to_vec();
+        for ir_file in &self.additional_ir_files {
+            let additional = self.parse_ir(ir_file)?;
+            merge_definitions("error", &mut errors, additional.errors(), |d| {
+                d.error_name()
+            })?;
+            merge_definitions("type", &mut types, additional.types(), type_definition_name)?;
+            merge_definitions("service", &mut services, additional.services(), |d| {
+                d.service_name()
+            })?;
+        }
+
+        Ok(ConjureDefinition::builder()
+            .version(defs.version())
+            .errors(errors)
+            .types(types)
+            .services(services)
+            .extensions(defs.extensions().clone())
+            .build())
+    }
+
     fn parse_ir(&self, ir_file: &Path) -> Result<ConjureDefinition, Error> {
         let ir = fs::read_to_string(ir_file)
             .with_context(|_| format!("error reading file {}", ir_file.display()))?;
 
-        let defs = conjure_serde::json::client_from_str(&ir)
+        let defs: ConjureDefinition = conjure_serde::json::client_from_str(&ir)
             .with_context(|_| format!("error parsing Conjure IR file {}", ir_file.display()))?;
 
+        if defs.version() != 1 {
+            bail!(
+                "unsupported IR version {} in {}",
+                defs.version(),
+                ir_file.display()
+            );
+        }
+
         Ok(defs)
     }
 
-    fn create_modules(&self, defs: &ConjureDefinition) -> ModuleTrie {
+    fn create_modules(
+        &self,
+        defs: &ConjureDefinition,
+        extern_packages: BTreeMap<String, TokenStream>,
+        external_types: HashMap<TypeName, ExternalType>,
+    ) -> Result<ModuleTrie, Error> {
         let context = Context::new(
             defs,
             self.exhaustive,
             self.staged_builders,
+            self.prefer_smile,
+            self.streaming_response_threshold,
+            self.token_provider,
             self.strip_prefix.as_deref(),
+            extern_packages,
+            external_types,
             self.version
                 .as_deref()
                 .or_else(|| self.build_crate.as_ref().map(|v| &*v.version)),
         );
 
+        validation::validate(&context, defs)?;
+
         let mut root = ModuleTrie::new();
+        root.contents = externals::assertions(context.external_types());
 
         for def in defs.types() {
+            if context.is_extern(type_definition_name(def)) {
+                continue;
+            }
+
             let (type_name, contents) = match def {
                 TypeDefinition::Enum(def) => (def.type_name(), enums::generate(&context, def)),
                 TypeDefinition::Alias(def) => (def.type_name(), aliases::generate(&context, def)),
                 TypeDefinition::Union(def) => (def.type_name(), unions::generate(&context, def)),
                 TypeDefinition::Object(def) => (def.type_name(), objects::generate(&context, def)),
-                TypeDefinition::Constant(_) => continue,
+                TypeDefinition::Constant(def) => {
+                    (def.type_name(), constants::generate(&context, def))
+                }
             };
 
+            let exported_name = match def {
+                TypeDefinition::Constant(_) => context.constant_name(type_name.name()),
+                _ => context.type_name(type_name.name()),
+            };
             let type_ = Type {
                 module_name: context.module_name(type_name),
-                type_names: vec![context.type_name(type_name.name()).to_string()],
+                type_names: vec![exported_name.to_string()],
                 contents,
             };
             root.insert(&context.module_path(type_name), type_);
         }
 
         for def in defs.errors() {
+            if context.is_extern(def.error_name()) {
+                continue;
+            }
+
             let type_ = Type {
                 module_name: context.module_name(def.error_name()),
                 type_names: vec![context.type_name(def.error_name().name()).to_string()],
@@ -501,22 +763,11 @@ impl Config {
             root.insert(&context.module_path(def.error_name()), type_);
         }
 
-        let constants: Vec<&ConstantDefinition> = defs.types().iter().filter_map(|def| {
-            match def {
-                TypeDefinition::Constant(def) => Some(def),
-                _ => None,
+        for def in defs.services() {
+            if context.is_extern(def.service_name()) {
+                continue;
             }
-        }).collect();
-
-        let type_ = Type {
-            module_name: context.module_name(&TypeName::new("constants", "")),
-            type_names: vec![],
-            contents: constants::generate_constants(&context, &constants),
-        };
-        root.insert(&[], type_);
-
 
-        for def in defs.services() {
             let client = clients::generate(&context, def);
             let server = servers::generate(&context, def);
 
@@ -538,7 +789,7 @@ impl Config {
             root.insert(&context.module_path(def.service_name()), type_);
         }
 
-        root
+        Ok(root)
     }
 
     fn write_cargo_toml(
@@ -622,6 +873,131 @@ disable_all_formatting = true
     }
 }
 
+/// A Rust type used in place of a Conjure external type.
+///
+/// The type must implement `Debug`, `Clone`, and serde's `Serialize` and `Deserialize` traits, and is by default
+/// assumed to also implement `PartialEq`, `Eq`, `PartialOrd`, `Ord`, and `Hash` like generated types do.
+///
+/// The generated code asserts at compile time that the type implements these traits along with any others declared
+/// with the methods below.
+#[derive(Debug, Clone)]
+pub struct ExternalType {
+    path: String,
+    ord: bool,
+    plain: bool,
+    copy: bool,
+}
+
+impl ExternalType {
+    /// Creates a new `ExternalType` with the path of the Rust type, for example `i64` or `my_crate::MyType`.
+    ///
+    /// The path must be valid from every module of the generated code, so types other than builtins should typically
+    /// be referenced by a path starting with a crate name or `crate::`.
+    pub fn new(path: &str) -> ExternalType {
+        ExternalType {
+            path: path.to_string(),
+            ord: true,
+            plain: false,
+            copy: false,
+        }
+    }
+
+    /// Declares that the type implements `PartialEq`, `Eq`, `PartialOrd`, `Ord`, and `Hash`.
+    ///
+    /// These implementations are required for the type to be used in Conjure type definitions and errors, in sets,
+    /// and as the keys of maps.
+    ///
+    /// Defaults to `true`.
+    pub fn ord(mut self, ord: bool) -> ExternalType {
+        self.ord = ord;
+        self
+    }
+
+    /// Declares that the type implements `conjure_object::Plain` and `conjure_object::FromPlain`.
+    ///
+    /// These implementations are required for the type to be used in path, query, and header parameters.
+    ///
+    /// Defaults to `false`.
+    pub fn plain(mut self, plain: bool) -> ExternalType {
+        self.plain = plain;
+        self
+    }
+
+    /// Declares that the type implements `Copy`.
+    ///
+    /// Generated code will pass and return `Copy` types by value rather than by reference.
+    ///
+    /// Defaults to `false`.
+    pub fn copy(mut self, copy: bool) -> ExternalType {
+        self.copy = copy;
+        self
+    }
+}
+
+/// An error returned when a Conjure definition fails validation.
+///
+/// Validation reports every problem found in the definition rather than stopping at the first, and the individual
+/// problems can be retrieved by downcasting the error returned from code generation to this type.
+#[derive(Debug)]
+pub struct ValidationError {
+    errors: Vec<Error>,
+}
+
+impl ValidationError {
+    /// Returns the individual problems found in the definition.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "invalid Conjure definition")?;
+        for error in &self.errors {
+            write!(fmt, "\n  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Fail for ValidationError {}
+
+fn type_definition_name(def: &TypeDefinition) -> &TypeName {
+    match def {
+        TypeDefinition::Alias(def) => def.type_name(),
+        TypeDefinition::Enum(def) => def.type_name(),
+        TypeDefinition::Object(def) => def.type_name(),
+        TypeDefinition::Union(def) => def.type_name(),
+        TypeDefinition::Constant(def) => def.type_name(),
+    }
+}
+
+fn merge_definitions<T, F>(
+    kind: &str,
+    defs: &mut Vec<T>,
+    additional: &[T],
+    name: F,
+) -> Result<(), Error>
+where
+    T: Clone + PartialEq,
+    F: Fn(&T) -> &TypeName,
+{
+    for def in additional {
+        match defs.iter().find(|d| name(d) == name(def)) {
+            Some(existing) if existing == def => {}
+            Some(_) => bail!(
+                "conflicting definitions of {} {}.{}",
+                kind,
+                name(def).package(),
+                name(def).name()
+            ),
+            None => defs.push(def.clone()),
+        }
+    }
+
+    Ok(())
+}
+
 struct Type {
     module_name: String,
     type_names: Vec<String>,
@@ -631,6 +1007,7 @@ struct Type {
 struct ModuleTrie {
     submodules: BTreeMap<String, ModuleTrie>,
     types: Vec<Type>,
+    contents: TokenStream,
 }
 
 impl ModuleTrie {
@@ -638,6 +1015,7 @@ impl ModuleTrie {
         ModuleTrie {
             submodules: BTreeMap::new(),
             types: vec![],
+            contents: quote!(),
         }
     }
 
@@ -652,46 +1030,7 @@ impl ModuleTrie {
         }
     }
 
-    fn render(&self, dir: &Path, lib_root: bool) -> Result<(), Error> {
-        fs::create_dir_all(dir)
-            .with_context(|_| format!("error creating directory {}", dir.display()))?;
-
-        for type_ in &self.types {
-            self.write_module(
-                &dir.join(format!("{}.rs", type_.module_name)),
-                &type_.contents,
-            )?;
-        }
-
-        for (name, module) in &self.submodules {
-            module.render(&dir.join(name), false)?;
-        }
-
-        let root = self.create_root_module(lib_root);
-        let file_name = if lib_root { "lib.rs" } else { "mod.rs" };
-        self.write_module(&dir.join(file_name), &root)?;
-
-        Ok(())
-    }
-
-    fn write_module(&self, path: &Path, contents: &TokenStream) -> Result<(), Error> {
-        let file = syn::parse2(contents.clone())?;
-        let formatted = prettyplease::unparse(&file);
-
-        fs::write(path, formatted)
-            .with_context(|_| format!("error writing module {}", path.display()))?;
-        Ok(())
-    }
-
-    fn create_root_module(&self, lib_root: bool) -> TokenStream {
-        let attrs = if lib_root {
-            quote! {
-                #![allow(warnings)]
-            }
-        } else {
-            quote! {}
-        };
-
+    fn into_module(self, name: String) -> Module {
         let uses = self.types.iter().map(|m| {
             let module_name = m.module_name.parse::<TokenStream>().unwrap();
             let type_names = m
@@ -703,27 +1042,140 @@ impl ModuleTrie {
                 pub use self::#module_name::{#(#type_names),*};
             }
         });
+        let extra = self.contents;
+        let contents = quote! {
+            #(#uses)*
+            #extra
+        };
+
+        let type_mods = self.types.into_iter().map(|type_| Module {
+            name: type_.module_name,
+            contents: type_.contents,
+            submodules: vec![],
+        });
+        let sub_mods = self
+            .submodules
+            .into_iter()
+            .map(|(name, module)| module.into_module(name));
+
+        Module {
+            name,
+            contents,
+            submodules: type_mods.chain(sub_mods).collect(),
+        }
+    }
+}
 
-        let type_mods = self.types.iter().map(|m| {
-            let module_name = m.module_name.parse::<TokenStream>().unwrap();
+/// An in-memory Rust module generated from Conjure definitions.
+///
+/// The root module re-exports the types defined in its submodules. Each type is defined in a
+/// submodule of its own, and each Conjure package corresponds to a submodule containing the
+/// modules of its types.
+pub struct Module {
+    name: String,
+    contents: TokenStream,
+    submodules: Vec<Module>,
+}
+
+impl Module {
+    /// Returns the module's name.
+    ///
+    /// The root module's name is empty.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the module's items, not including the declarations of its submodules.
+    pub fn contents(&self) -> &TokenStream {
+        &self.contents
+    }
+
+    /// Returns the module's submodules.
+    pub fn submodules(&self) -> &[Module] {
+        &self.submodules
+    }
+
+    /// Returns the source files of the module and its submodules, keyed by their paths relative to
+    /// the module's directory.
+    ///
+    /// The module itself is stored in `mod.rs`. Submodules without submodules of their own are
+    /// stored in `<name>.rs`, and the rest in `<name>/mod.rs`.
+    pub fn files(&self) -> BTreeMap<PathBuf, TokenStream> {
+        let mut files = BTreeMap::new();
+        self.add_files(Path::new(""), &mut files);
+        files
+    }
+
+    fn add_files(&self, dir: &Path, files: &mut BTreeMap<PathBuf, TokenStream>) {
+        let contents = &self.contents;
+        let mods = self.submodules.iter().map(|m| {
+            let module_name = m.name.parse::<TokenStream>().unwrap();
             quote! {
                 pub mod #module_name;
             }
         });
+        files.insert(
+            dir.join("mod.rs"),
+            quote! {
+                #contents
+                #(#mods)*
+            },
+        );
+
+        for module in &self.submodules {
+            if module.submodules.is_empty() {
+                files.insert(
+                    dir.join(format!("{}.rs", module.name)),
+                    module.contents.clone(),
+                );
+            } else {
+                module.add_files(&dir.join(&module.name), files);
+            }
+        }
+    }
 
-        let sub_mods = self.submodules.keys().map(|v| {
-            let module_name = v.parse::<TokenStream>().unwrap();
+    /// Returns the module's items along with its submodules, which are declared inline.
+    pub fn inline_tokens(&self) -> TokenStream {
+        let contents = &self.contents;
+        let mods = self.submodules.iter().map(|m| {
+            let module_name = m.name.parse::<TokenStream>().unwrap();
+            let module = m.inline_tokens();
             quote! {
-                pub mod #module_name;
+                pub mod #module_name {
+                    #module
+                }
             }
         });
 
         quote! {
-            #attrs
-            #(#uses)*
+            #contents
+            #(#mods)*
+        }
+    }
 
-            #(#type_mods)*
-            #(#sub_mods)*
+    fn render(&self, dir: &Path, lib_root: bool) -> Result<(), Error> {
+        for (path, contents) in self.files() {
+            let (path, contents) = if lib_root && path == Path::new("mod.rs") {
+                let contents = quote! {
+                    #![allow(warnings)]
+                    #contents
+                };
+                (dir.join("lib.rs"), contents)
+            } else {
+                (dir.join(path), contents)
+            };
+
+            let parent = path.parent().unwrap();
+            fs::create_dir_all(parent)
+                .with_context(|_| format!("error creating directory {}", parent.display()))?;
+
+            let file = syn::parse2(contents)?;
+            let formatted = prettyplease::unparse(&file);
+
+            fs::write(&path, formatted)
+                .with_context(|_| format!("error writing module {}", path.display()))?;
         }
+
+        Ok(())
     }
 }
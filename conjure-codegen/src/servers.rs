@@ -1,12 +1,13 @@
 use crate::context::Context;
 use crate::http_paths::{self, PathSegment};
 use crate::types::{
-    ArgumentDefinition, AuthType, EndpointDefinition, HeaderParameterType, ParameterType,
-    QueryParameterType, ServiceDefinition, Type,
+    ArgumentDefinition, AuthType, EndpointDefinition, HeaderParameterType, LogSafety,
+    ParameterType, QueryParameterType, ServiceDefinition, Type,
 };
 use heck::ToUpperCamelCase;
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::{Ident, Literal, TokenStream};
 use quote::quote;
+use std::num::ParseIntError;
 
 #[derive(Copy, Clone)]
 enum Style {
@@ -279,6 +280,15 @@ fn generate_service_impl(ctx: &Context, def: &ServiceDefinition, style: Style) -
     let vec = ctx.vec_ident(def.service_name());
     let box_ = ctx.box_ident(def.service_name());
     let endpoint_name = endpoint_trait_name(style);
+    let o_bounds = match style {
+        Style::Async if service_has_streaming_response(ctx, def) => {
+            quote!(O: conjure_http::private::AsyncWrite + #send,)
+        }
+        Style::Sync if service_has_streaming_response(ctx, def) => {
+            quote!(O: conjure_http::private::Write,)
+        }
+        _ => quote!(),
+    };
 
     let endpoint_instances = def.endpoints().iter().map(|e| create_endpoint(ctx, def, e));
 
@@ -287,6 +297,7 @@ fn generate_service_impl(ctx: &Context, def: &ServiceDefinition, style: Style) -
         where
             T: #trait_name #params + 'static + #sync + #send,
             I: #input_trait<Item = #result<conjure_http::private::Bytes, conjure_http::private::Error>> #i_traits,
+            #o_bounds
         {
             fn endpoints(&self) -> #vec<#box_<dyn conjure_http::server::#endpoint_name<I, O> + Sync + Send>>
             {
@@ -382,6 +393,34 @@ fn generate_endpoint_metadata(
         None => quote!(#none),
     };
 
+    let auth = match endpoint.auth() {
+        Some(AuthType::Header(_)) => quote!(#some(conjure_http::server::AuthType::Header)),
+        Some(AuthType::Cookie(cookie)) => {
+            let cookie_name = cookie.cookie_name();
+            quote!(#some(conjure_http::server::AuthType::Cookie(#cookie_name)))
+        }
+        None => quote!(#none),
+    };
+
+    let args = endpoint
+        .args()
+        .iter()
+        .map(|arg| generate_argument_metadata(ctx, arg));
+
+    let returns = match endpoint.returns() {
+        Some(returns) => {
+            let returns = ctx.type_description(returns);
+            quote!(#some(#returns))
+        }
+        None => quote!(#none),
+    };
+
+    let markers = endpoint
+        .markers()
+        .iter()
+        .map(|marker| ctx.type_description(marker));
+    let tags = endpoint.tags();
+
     quote! {
         impl<T> conjure_http::server::EndpointMetadata for #endpoint_name<T> {
             fn method(&self) -> conjure_http::private::Method {
@@ -407,10 +446,87 @@ fn generate_endpoint_metadata(
             fn deprecated(&self) -> #option<&str> {
                 #deprecated
             }
+
+            fn auth(&self) -> #option<conjure_http::server::AuthType> {
+                #auth
+            }
+
+            fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+                const ARGS: &[conjure_http::server::ArgumentMetadata] = &[#(#args,)*];
+                ARGS
+            }
+
+            fn returns(&self) -> #option<&str> {
+                #returns
+            }
+
+            fn markers(&self) -> &[&str] {
+                &[#(#markers,)*]
+            }
+
+            fn tags(&self) -> &[&str] {
+                &[#(#tags,)*]
+            }
         }
     }
 }
 
+fn generate_argument_metadata(ctx: &Context, arg: &ArgumentDefinition) -> TokenStream {
+    let name = &**arg.arg_name();
+
+    let param_type = match arg.param_type() {
+        ParameterType::Body(_) => quote!(Body),
+        ParameterType::Header(header) => {
+            let param_id = &**header.param_id();
+            quote!(Header(#param_id))
+        }
+        ParameterType::Path(_) => quote!(Path),
+        ParameterType::Query(query) => {
+            let param_id = &**query.param_id();
+            quote!(Query(#param_id))
+        }
+    };
+
+    let type_ = ctx.type_description(arg.type_());
+
+    let safety = ctx.arg_log_safety(arg).map(|safety| {
+        let safety = match safety {
+            LogSafety::Safe => quote!(Safe),
+            LogSafety::Unsafe => quote!(Unsafe),
+            LogSafety::DoNotLog => quote!(DoNotLog),
+        };
+        quote!(.with_safety(conjure_http::server::LogSafety::#safety))
+    });
+
+    let markers = if arg.markers().is_empty() {
+        quote!()
+    } else {
+        let markers = arg
+            .markers()
+            .iter()
+            .map(|marker| ctx.type_description(marker));
+        quote!(.with_markers(&[#(#markers,)*]))
+    };
+
+    let tags = if arg.tags().is_empty() {
+        quote!()
+    } else {
+        let tags = arg.tags();
+        quote!(.with_tags(&[#(#tags,)*]))
+    };
+
+    quote! {
+        conjure_http::server::ArgumentMetadata::new(
+            #name,
+            conjure_http::server::ParameterType::#param_type,
+            #type_,
+        )
+        #safety
+        #markers
+        #tags
+    }
+}
+
 fn endpoint_name(ctx: &Context, endpoint: &EndpointDefinition) -> TokenStream {
     let name = ctx.type_name(endpoint.endpoint_name());
     format!("{}Endpoint_", name).parse().unwrap()
@@ -443,6 +559,16 @@ fn generate_endpoint_impl(
         Style::Sync => quote!(),
     };
 
+    let o_bounds = match style {
+        Style::Async if streaming_response_limit(ctx, endpoint).is_some() => {
+            quote!(O: conjure_http::private::AsyncWrite + #send,)
+        }
+        Style::Sync if streaming_response_limit(ctx, endpoint).is_some() => {
+            quote!(O: conjure_http::private::Write,)
+        }
+        _ => quote!(),
+    };
+
     let asyncness = match style {
         Style::Async => quote!(async),
         Style::Sync => quote!(),
@@ -500,7 +626,7 @@ fn generate_endpoint_impl(
             ParameterType::Path(_) => parse_path_arg(ctx, arg, &parts),
             ParameterType::Query(param) => parse_query_arg(ctx, def, arg, param, &query_params),
             ParameterType::Header(param) => parse_header_arg(ctx, def, arg, param, &parts),
-            ParameterType::Body(_) => parse_body_arg(ctx, arg, &parts, &body, style),
+            ParameterType::Body(_) => parse_body_arg(ctx, endpoint, arg, &parts, &body, style),
         };
 
         let put_safe = if ctx.is_safe_arg(arg) {
@@ -526,8 +652,8 @@ fn generate_endpoint_impl(
     let request_context = quote!(request_context_);
     let make_request_context = if has_request_context(endpoint) {
         quote! {
-            let #request_context = conjure_http::server::RequestContext::new(
-                #parts,
+            let #request_context = conjure_http::server::RequestContext::new2(
+                &#parts,
                 #response_extensions,
             );
         }
@@ -543,7 +669,7 @@ fn generate_endpoint_impl(
 
     let handle = handle(ctx, endpoint, &auth, &request_context, style);
 
-    let make_response = make_response(ctx, endpoint, &response, style);
+    let make_response = make_response(ctx, endpoint, &parts, &response, style);
     let ok = ctx.ok_ident(def.service_name());
 
     quote! {
@@ -552,6 +678,7 @@ fn generate_endpoint_impl(
         where
             T: #trait_name #trait_params + 'static + #sync + #send,
             I: #input_trait<Item = #result<conjure_http::private::Bytes, conjure_http::private::Error>> #i_bounds,
+            #o_bounds
         {
             #asyncness fn handle(
                 &self,
@@ -601,6 +728,48 @@ fn has_request_context(endpoint: &EndpointDefinition) -> bool {
         .any(|t| t == "server-request-context")
 }
 
+fn request_size_limit(endpoint: &EndpointDefinition) -> Option<usize> {
+    // malformed tags are reported by the validation pass
+    parse_request_size_limit(endpoint).unwrap()
+}
+
+pub fn parse_request_size_limit(
+    endpoint: &EndpointDefinition,
+) -> Result<Option<usize>, ParseIntError> {
+    for tag in endpoint.tags() {
+        if let Some(limit) = tag.strip_prefix("server-request-size-limit:") {
+            return limit.parse().map(Some);
+        }
+    }
+
+    Ok(None)
+}
+
+fn service_has_streaming_response(ctx: &Context, def: &ServiceDefinition) -> bool {
+    def.endpoints()
+        .iter()
+        .any(|e| streaming_response_limit(ctx, e).is_some())
+}
+
+// Returns the number of bytes an endpoint's response can be buffered up to before it is streamed
+// instead.
+fn streaming_response_limit(ctx: &Context, endpoint: &EndpointDefinition) -> Option<usize> {
+    match return_type(ctx, endpoint) {
+        ReturnType::Json(_) => {}
+        ReturnType::None | ReturnType::Binary | ReturnType::OptionalBinary => return None,
+    }
+
+    if endpoint
+        .tags()
+        .iter()
+        .any(|t| t == "server-streaming-response")
+    {
+        Some(0)
+    } else {
+        ctx.streaming_response_threshold()
+    }
+}
+
 fn parse_path_arg(ctx: &Context, arg: &ArgumentDefinition, parts: &TokenStream) -> TokenStream {
     let name = ctx.field_name(arg.arg_name());
     let param_name = &***arg.arg_name();
@@ -672,26 +841,36 @@ fn parse_header_arg(
 
 fn parse_body_arg(
     ctx: &Context,
+    endpoint: &EndpointDefinition,
     arg: &ArgumentDefinition,
     parts: &TokenStream,
     body: &TokenStream,
     style: Style,
 ) -> TokenStream {
     let name = ctx.field_name(arg.arg_name());
+    let limit = match request_size_limit(endpoint) {
+        Some(limit) => {
+            let limit = Literal::usize_unsuffixed(limit);
+            quote!(#limit)
+        }
+        None => quote!(conjure_http::private::SERIALIZABLE_REQUEST_SIZE_LIMIT),
+    };
 
     let call = if ctx.is_optional(arg.type_()).is_some() {
         match style {
             Style::Async => {
-                quote!(async_decode_optional_serializable_request(&#parts, #body).await)
+                quote!(async_decode_optional_serializable_request2(&#parts, #body, #limit).await)
             }
-            Style::Sync => quote!(decode_optional_serializable_request(&#parts, #body)),
+            Style::Sync => quote!(decode_optional_serializable_request2(&#parts, #body, #limit)),
         }
     } else if ctx.is_binary(arg.type_()) {
         quote!(decode_binary_request(&#parts, #body))
     } else {
         match style {
-            Style::Async => quote!(async_decode_serializable_request(&#parts, #body).await),
-            Style::Sync => quote!(decode_serializable_request(&#parts, #body)),
+            Style::Async => {
+                quote!(async_decode_serializable_request2(&#parts, #body, #limit).await)
+            }
+            Style::Sync => quote!(decode_serializable_request2(&#parts, #body, #limit)),
         }
     };
 
@@ -751,6 +930,7 @@ fn handle(
 fn make_response(
     ctx: &Context,
     endpoint: &EndpointDefinition,
+    parts: &TokenStream,
     response: &TokenStream,
     style: Style,
 ) -> TokenStream {
@@ -768,15 +948,37 @@ fn make_response(
                 Style::Async => quote!(async_encode_binary_response(#response)),
                 Style::Sync => quote!(encode_binary_response(#response)),
             },
-            _ if ctx.is_iterable(ty) => match style {
-                Style::Async => {
-                    quote!(async_encode_default_serializable_response(&#response))
+            _ if ctx.is_iterable(ty) => match (style, streaming_response_limit(ctx, endpoint)) {
+                (Style::Sync, Some(limit)) => {
+                    let limit = Literal::usize_unsuffixed(limit);
+                    quote!(encode_streaming_default_serializable_response(&#parts, #response, #limit)?)
+                }
+                (Style::Async, Some(limit)) => {
+                    let limit = Literal::usize_unsuffixed(limit);
+                    quote!(async_encode_streaming_default_serializable_response(&#parts, #response, #limit)?)
+                }
+                (Style::Async, None) => {
+                    quote!(async_encode_default_serializable_response2(&#parts, &#response)?)
+                }
+                (Style::Sync, None) => {
+                    quote!(encode_default_serializable_response2(&#parts, &#response)?)
                 }
-                Style::Sync => quote!(encode_default_serializable_response(&#response)),
             },
-            _ => match style {
-                Style::Async => quote!(async_encode_serializable_response(&#response)),
-                Style::Sync => quote!(encode_serializable_response(&#response)),
+            _ => match (style, streaming_response_limit(ctx, endpoint)) {
+                (Style::Sync, Some(limit)) => {
+                    let limit = Literal::usize_unsuffixed(limit);
+                    quote!(encode_streaming_serializable_response(&#parts, #response, #limit)?)
+                }
+                (Style::Async, Some(limit)) => {
+                    let limit = Literal::usize_unsuffixed(limit);
+                    quote!(async_encode_streaming_serializable_response(&#parts, #response, #limit)?)
+                }
+                (Style::Async, None) => {
+                    quote!(async_encode_serializable_response2(&#parts, &#response)?)
+                }
+                (Style::Sync, None) => {
+                    quote!(encode_serializable_response2(&#parts, &#response)?)
+                }
             },
         },
         None => match style {
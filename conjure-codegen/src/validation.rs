@@ -0,0 +1,463 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use conjure_object::{BearerToken, DateTime, ResourceIdentifier, SafeLong, Uuid};
+use failure::{format_err, Error};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::context::Context;
+use crate::externals;
+use crate::http_paths::{self, PathSegment};
+use crate::servers;
+use crate::types::{
+    ConjureDefinition, ConstantDefinition, EndpointDefinition, FieldDefinition, ParameterType,
+    PrimitiveType, ServiceDefinition, Type, TypeDefinition, TypeName,
+};
+use crate::ValidationError;
+
+/// Checks a Conjure definition for problems which would otherwise cause code generation to panic
+/// or produce code which doesn't compile.
+///
+/// Every problem found is reported rather than just the first.
+pub fn validate(ctx: &Context, defs: &ConjureDefinition) -> Result<(), Error> {
+    let mut validator = Validator {
+        ctx,
+        types: defs
+            .types()
+            .iter()
+            .map(|def| (crate::type_definition_name(def), def))
+            .collect(),
+        modules: HashMap::new(),
+        errors: vec![],
+    };
+
+    for def in defs.types() {
+        validator.validate_type_definition(def);
+    }
+
+    for def in defs.errors() {
+        let location = format!("error {}", name(def.error_name()));
+        validator.register(
+            def.error_name(),
+            &location,
+            vec![ctx.type_name(def.error_name().name())],
+        );
+        let args = def.safe_args().iter().chain(def.unsafe_args());
+        validator.validate_fields(&location, args);
+    }
+
+    for def in defs.services() {
+        validator.validate_service(def);
+    }
+
+    validator.errors.extend(externals::validate(ctx, defs));
+
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError {
+            errors: validator.errors,
+        }
+        .into())
+    }
+}
+
+fn name(name: &TypeName) -> String {
+    format!("{}.{}", name.package(), name.name())
+}
+
+#[derive(Default)]
+struct ModuleNames {
+    modules: HashMap<String, String>,
+    types: HashMap<String, String>,
+}
+
+struct Validator<'a> {
+    ctx: &'a Context,
+    types: HashMap<&'a TypeName, &'a TypeDefinition>,
+    modules: HashMap<Vec<String>, ModuleNames>,
+    errors: Vec<Error>,
+}
+
+impl Validator<'_> {
+    fn error<T>(&mut self, location: &str, message: T)
+    where
+        T: Display,
+    {
+        self.errors.push(format_err!("{}: {}", location, message));
+    }
+
+    fn validate_type_definition(&mut self, def: &TypeDefinition) {
+        let ctx = self.ctx;
+        let type_name = crate::type_definition_name(def);
+        let type_names = vec![self.ctx.type_name(type_name.name())];
+
+        match def {
+            TypeDefinition::Alias(def) => {
+                let location = format!("alias {}", name(type_name));
+                self.register(type_name, &location, type_names);
+                self.validate_type(&location, def.alias());
+                if self.is_alias_cycle(type_name, def.alias(), &mut HashSet::new()) {
+                    self.error(&location, "alias cycle");
+                }
+            }
+            TypeDefinition::Enum(def) => {
+                let location = format!("enum {}", name(type_name));
+                self.register(type_name, &location, type_names);
+                self.check_unique(
+                    &location,
+                    "value",
+                    def.values()
+                        .iter()
+                        .map(|v| (v.value(), ctx.type_name(v.value()))),
+                );
+            }
+            TypeDefinition::Object(def) => {
+                let location = format!("object {}", name(type_name));
+                self.register(type_name, &location, type_names);
+                self.validate_fields(&location, def.fields());
+            }
+            TypeDefinition::Union(def) => {
+                let location = format!("union {}", name(type_name));
+                self.register(type_name, &location, type_names);
+                self.check_unique(
+                    &location,
+                    "variant",
+                    def.union_()
+                        .iter()
+                        .map(|f| (f.field_name(), ctx.type_name(f.field_name()))),
+                );
+                for field in def.union_() {
+                    let location = format!("{}, variant {}", location, field.field_name());
+                    self.validate_type(&location, field.type_());
+                }
+            }
+            TypeDefinition::Constant(def) => {
+                let location = format!("constant {}", name(type_name));
+                self.register(
+                    type_name,
+                    &location,
+                    vec![ctx.constant_name(type_name.name())],
+                );
+                self.validate_constant(&location, def);
+            }
+        }
+    }
+
+    fn validate_fields<'b, I>(&mut self, location: &str, fields: I)
+    where
+        I: IntoIterator<Item = &'b FieldDefinition> + Clone,
+    {
+        let ctx = self.ctx;
+        self.check_unique(
+            location,
+            "field",
+            fields
+                .clone()
+                .into_iter()
+                .map(|f| (f.field_name(), ctx.field_name(f.field_name()))),
+        );
+        for field in fields {
+            let location = format!("{}, field {}", location, field.field_name());
+            self.validate_type(&location, field.type_());
+        }
+    }
+
+    fn validate_constant(&mut self, location: &str, def: &ConstantDefinition) {
+        let value = def.value();
+        let type_description = self
+            .ctx
+            .type_description(&Type::Primitive(def.const_type().clone()));
+
+        let valid = match def.const_type() {
+            PrimitiveType::String => true,
+            PrimitiveType::Datetime => DateTime::parse_from_rfc3339(value).is_ok(),
+            PrimitiveType::Integer => value.parse::<i32>().is_ok(),
+            PrimitiveType::Double => value.parse::<f64>().is_ok(),
+            PrimitiveType::Safelong => matches!(value.parse::<i64>().map(SafeLong::new), Ok(Ok(_))),
+            PrimitiveType::Boolean => value.parse::<bool>().is_ok(),
+            PrimitiveType::Uuid => Uuid::from_str(value).is_ok(),
+            PrimitiveType::Rid => ResourceIdentifier::from_str(value).is_ok(),
+            PrimitiveType::Bearertoken => BearerToken::new(value.as_str()).is_ok(),
+            PrimitiveType::Binary | PrimitiveType::Any => {
+                self.error(
+                    location,
+                    format_args!("`{}` constants are not supported", type_description),
+                );
+                return;
+            }
+        };
+
+        if !valid {
+            self.error(
+                location,
+                format_args!("invalid `{}` value `{}`", type_description, value),
+            );
+        }
+    }
+
+    fn validate_service(&mut self, def: &ServiceDefinition) {
+        let ctx = self.ctx;
+        let service_name = def.service_name();
+        let location = format!("service {}", name(service_name));
+        let type_names = [
+            format!("{}Client", service_name.name()),
+            format!("{}AsyncClient", service_name.name()),
+            self.ctx.type_name(service_name.name()).to_string(),
+            format!("Async{}", service_name.name()),
+            format!("{}Endpoints", service_name.name()),
+        ];
+        self.register(service_name, &location, type_names);
+
+        self.check_unique(
+            &location,
+            "endpoint",
+            def.endpoints()
+                .iter()
+                .map(|e| (e.endpoint_name(), ctx.field_name(e.endpoint_name()))),
+        );
+
+        for endpoint in def.endpoints() {
+            let location = format!("{}, endpoint {}", location, endpoint.endpoint_name());
+            self.validate_endpoint(&location, endpoint);
+        }
+    }
+
+    fn validate_endpoint(&mut self, location: &str, endpoint: &EndpointDefinition) {
+        let ctx = self.ctx;
+        self.check_unique(
+            location,
+            "argument",
+            endpoint
+                .args()
+                .iter()
+                .map(|a| (a.arg_name(), ctx.field_name(a.arg_name()))),
+        );
+
+        for arg in endpoint.args() {
+            let location = format!("{}, argument {}", location, arg.arg_name());
+            self.validate_type(&location, arg.type_());
+            for marker in arg.markers() {
+                self.validate_type(&location, marker);
+            }
+        }
+
+        if let Some(returns) = endpoint.returns() {
+            let location = format!("{}, return type", location);
+            self.validate_type(&location, returns);
+        }
+
+        for marker in endpoint.markers() {
+            self.validate_type(location, marker);
+        }
+
+        if let Err(e) = servers::parse_request_size_limit(endpoint) {
+            self.error(
+                location,
+                format_args!("invalid `server-request-size-limit` tag: {}", e),
+            );
+        }
+
+        let path_args = endpoint
+            .args()
+            .iter()
+            .filter(|a| matches!(a.param_type(), ParameterType::Path(_)))
+            .map(|a| &***a.arg_name())
+            .collect::<BTreeSet<_>>();
+        let mut path_params = BTreeSet::new();
+        for segment in http_paths::parse(endpoint.http_path()) {
+            let param = match segment {
+                PathSegment::Parameter { name, .. } => name,
+                PathSegment::Literal(_) => continue,
+            };
+
+            if !path_params.insert(param) {
+                self.error(
+                    location,
+                    format_args!("path parameter `{}` appears multiple times", param),
+                );
+            } else if !path_args.contains(param) {
+                self.error(
+                    location,
+                    format_args!("path parameter `{}` has no corresponding argument", param),
+                );
+            }
+        }
+        for arg in path_args.difference(&path_params) {
+            self.error(
+                location,
+                format_args!(
+                    "path argument `{}` does not appear in the path `{}`",
+                    arg,
+                    endpoint.http_path(),
+                ),
+            );
+        }
+    }
+
+    fn validate_type(&mut self, location: &str, ty: &Type) {
+        match ty {
+            Type::Primitive(_) => {}
+            Type::Optional(def) => self.validate_type(location, def.item_type()),
+            Type::List(def) => self.validate_type(location, def.item_type()),
+            Type::Set(def) => self.validate_type(location, def.item_type()),
+            Type::Map(def) => {
+                self.validate_type(location, def.key_type());
+                self.validate_type(location, def.value_type());
+                if let Some(description) = self.invalid_key(def.key_type(), &mut HashSet::new()) {
+                    self.error(
+                        location,
+                        format_args!("{} cannot be used as a map key", description),
+                    );
+                }
+            }
+            Type::Reference(def) => {
+                if !self.types.contains_key(def) {
+                    self.error(location, format_args!("unknown type {}", name(def)));
+                }
+            }
+            Type::External(def) => self.validate_type(location, def.fallback()),
+        }
+    }
+
+    // Returns true if the aliased type of the alias `name` directly contains the alias itself.
+    // Lists, sets, and maps are heap allocated, so only references, optionals, and external
+    // fallbacks are followed.
+    fn is_alias_cycle<'b>(
+        &'b self,
+        name: &TypeName,
+        ty: &'b Type,
+        visited: &mut HashSet<&'b TypeName>,
+    ) -> bool {
+        match ty {
+            Type::Optional(def) => self.is_alias_cycle(name, def.item_type(), visited),
+            Type::External(def) => self.is_alias_cycle(name, def.fallback(), visited),
+            Type::Reference(reference) if reference == name => true,
+            Type::Reference(reference) => match self.types.get(reference) {
+                Some(TypeDefinition::Alias(def)) if visited.insert(reference) => {
+                    self.is_alias_cycle(name, def.alias(), visited)
+                }
+                _ => false,
+            },
+            Type::Primitive(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => false,
+        }
+    }
+
+    // Returns a description of the type if it can't be used as a map key.
+    //
+    // Alias cycles are reported separately, so aliases already visited are skipped.
+    fn invalid_key<'b>(
+        &'b self,
+        ty: &'b Type,
+        visited: &mut HashSet<&'b TypeName>,
+    ) -> Option<String> {
+        match ty {
+            Type::Primitive(PrimitiveType::Any | PrimitiveType::Binary)
+            | Type::Optional(_)
+            | Type::List(_)
+            | Type::Set(_)
+            | Type::Map(_) => Some(format!("`{}`", self.ctx.type_description(ty))),
+            Type::Primitive(_) => None,
+            Type::Reference(name) => match self.types.get(name) {
+                Some(TypeDefinition::Alias(def)) if visited.insert(name) => {
+                    self.invalid_key(def.alias(), visited)
+                }
+                Some(TypeDefinition::Alias(_)) => None,
+                Some(TypeDefinition::Object(_)) => Some(format!("object {}", self::name(name))),
+                Some(TypeDefinition::Union(_)) => Some(format!("union {}", self::name(name))),
+                Some(TypeDefinition::Enum(_) | TypeDefinition::Constant(_)) | None => None,
+            },
+            Type::External(def) => self.invalid_key(def.fallback(), visited),
+        }
+    }
+
+    // Checks that distinct names in the IR don't map to the same Rust identifier.
+    fn check_unique<I, N, T>(&mut self, location: &str, kind: &str, names: I)
+    where
+        I: IntoIterator<Item = (N, T)>,
+        N: Display,
+        T: ToString,
+    {
+        let mut seen = HashMap::new();
+        let mut conflicts = vec![];
+        for (name, ident) in names {
+            let name = name.to_string();
+            let ident = ident.to_string();
+            match seen.get(&ident) {
+                Some(other) => conflicts.push(format!(
+                    "{}s `{}` and `{}` both generate the name `{}`",
+                    kind, other, name, ident,
+                )),
+                None => {
+                    seen.insert(ident, name);
+                }
+            }
+        }
+
+        for conflict in conflicts {
+            self.error(location, conflict);
+        }
+    }
+
+    // Registers the module and exported type names generated for a definition, reporting any
+    // conflicts with the names of other definitions in the same module.
+    fn register<I, T>(&mut self, type_name: &TypeName, location: &str, type_names: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: ToString,
+    {
+        if self.ctx.is_extern(type_name) {
+            return;
+        }
+
+        let module_path = self.ctx.module_path(type_name);
+        let mut conflicts = vec![];
+
+        // the modules of the package itself, which are shared by all of its definitions
+        for i in 0..module_path.len() {
+            let owner = format!("the module {}", module_path[..=i].join("::"));
+            let names = self.modules.entry(module_path[..i].to_vec()).or_default();
+            if let Some(conflict) = claim(&mut names.modules, &module_path[i], &owner) {
+                conflicts.push(conflict);
+            }
+        }
+
+        let names = self.modules.entry(module_path).or_default();
+        let module_name = self.ctx.module_name(type_name);
+        if let Some(conflict) = claim(&mut names.modules, &module_name, location) {
+            conflicts.push(conflict);
+        }
+        for type_name in type_names {
+            if let Some(conflict) = claim(&mut names.types, &type_name.to_string(), location) {
+                conflicts.push(conflict);
+            }
+        }
+
+        for conflict in conflicts {
+            self.error(location, conflict);
+        }
+    }
+}
+
+fn claim(names: &mut HashMap<String, String>, name: &str, owner: &str) -> Option<String> {
+    match names.get(name) {
+        Some(existing) if existing == owner => None,
+        Some(existing) => Some(format!("the name `{}` conflicts with {}", name, existing)),
+        None => {
+            names.insert(name.to_string(), owner.to_string());
+            None
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The blocking client.
+use crate::builder::Builder;
+use crate::client::{self, CHUNK_SIZE};
+use crate::state::{self, ClientState, Failure};
+use bytes::{Bytes, BytesMut};
+use conjure_error::Error;
+use conjure_http::client::{is_idempotent, Endpoint, RequestBody};
+use futures_util::StreamExt;
+use http::{request, Request, Response};
+use hyper::body::Sender;
+use hyper::Body;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use tokio::runtime::{self, Runtime};
+
+/// A blocking HTTP client for Conjure services.
+///
+/// Requests are executed on an internal Tokio runtime. It is cheap to clone, with clones sharing
+/// the same runtime and connection pool.
+#[derive(Clone)]
+pub struct Client {
+    state: Arc<ClientState>,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    pub(crate) fn new(state: Arc<ClientState>) -> Result<Self, Error> {
+        let runtime = runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("conjure-http-client")
+            .build()
+            .map_err(Error::internal_safe)?;
+
+        Ok(Client {
+            state,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Returns a new builder.
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    fn send_attempt(
+        &self,
+        parts: &request::Parts,
+        body: &mut RequestBody<'_, BodyWriter>,
+        uri: usize,
+        idempotent: bool,
+    ) -> Result<hyper::Response<Body>, Failure> {
+        let (body, writer) = match body {
+            RequestBody::Empty => (Body::empty(), None),
+            RequestBody::Fixed(body) => (Body::from(body.clone()), None),
+            RequestBody::Streaming(writer) => {
+                let (sender, body) = Body::channel();
+                (body, Some((writer, sender)))
+            }
+        };
+
+        let request = self
+            .state
+            .build_request(uri, parts, body)
+            .map_err(Failure::fatal)?;
+        let state = self.state.clone();
+        let response = self
+            .runtime
+            .spawn(async move { state.send(request, idempotent).await });
+
+        // The body is written from the calling thread while the request is in flight.
+        if let Some((writer, sender)) = writer {
+            let mut sender = BodyWriter::new(sender, self.runtime.clone());
+            let result = writer
+                .write_body(&mut sender)
+                .and_then(|()| sender.finish().map_err(Error::internal_safe));
+            if let Err(e) = result {
+                drop(sender);
+                response.abort();
+                return Err(Failure::fatal(e));
+            }
+        }
+
+        self.runtime
+            .block_on(response)
+            .map_err(|e| Failure::fatal(Error::internal_safe(e)))?
+    }
+}
+
+impl conjure_http::client::Client for Client {
+    type BodyWriter = BodyWriter;
+    type ResponseBody = ResponseBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, mut body) = req.into_parts();
+        let endpoint = parts.extensions.get::<Endpoint>();
+        let idempotent = is_idempotent(&parts);
+
+        let mut retrier = self.state.retrier();
+        loop {
+            let failure = match self.send_attempt(&parts, &mut body, retrier.uri(), idempotent) {
+                Ok(response) => {
+                    return Ok(response.map(|body| ResponseBody {
+                        body: client::ResponseBody::new(body),
+                        runtime: self.runtime.clone(),
+                    }))
+                }
+                Err(failure) => failure,
+            };
+
+            let delay = match retrier.next_attempt(&failure) {
+                Some(delay) => delay,
+                None => return Err(state::with_endpoint(failure.error, endpoint)),
+            };
+
+            if let RequestBody::Streaming(writer) = &mut body {
+                if !writer.reset() {
+                    return Err(state::with_endpoint(failure.error, endpoint));
+                }
+            }
+
+            thread::sleep(delay);
+        }
+    }
+}
+
+/// The request body writer used by [`Client`].
+///
+/// If the writer is dropped before the body is completely written, the request body is aborted.
+pub struct BodyWriter {
+    sender: Option<Sender>,
+    buf: BytesMut,
+    runtime: Arc<Runtime>,
+}
+
+impl BodyWriter {
+    fn new(sender: Sender, runtime: Arc<Runtime>) -> Self {
+        BodyWriter {
+            sender: Some(sender),
+            buf: BytesMut::new(),
+            runtime,
+        }
+    }
+
+    fn send(&mut self, chunk: Bytes) -> io::Result<()> {
+        let sender = self.sender.as_mut().ok_or_else(client::closed)?;
+        self.runtime
+            .block_on(sender.send_data(chunk))
+            .map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.flush()?;
+        // Dropping the sender without aborting it cleanly ends the body.
+        self.sender = None;
+        Ok(())
+    }
+}
+
+impl Drop for BodyWriter {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            sender.abort();
+        }
+    }
+}
+
+impl Write for BodyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let chunk = self.buf.split().freeze();
+        self.send(chunk)
+    }
+}
+
+/// The response body returned by [`Client`].
+pub struct ResponseBody {
+    body: client::ResponseBody,
+    // Keeps the runtime driving the connection alive for as long as the body is.
+    runtime: Arc<Runtime>,
+}
+
+impl Iterator for ResponseBody {
+    type Item = Result<Bytes, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.body.next())
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::blocking;
+use crate::client::Client;
+use crate::state::ClientState;
+use conjure_error::Error;
+use http::Uri;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A builder for [`Client`]s and [`blocking::Client`]s.
+#[derive(Clone)]
+pub struct Builder {
+    pub(crate) uris: Vec<Uri>,
+    pub(crate) connect_timeout: Duration,
+    pub(crate) request_timeout: Duration,
+    pub(crate) max_num_retries: u32,
+    pub(crate) backoff_slot_size: Duration,
+    pub(crate) max_retry_after: Duration,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+impl Builder {
+    /// Creates a new builder with default settings.
+    pub fn new() -> Self {
+        Builder {
+            uris: vec![],
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(5 * 60),
+            max_num_retries: 4,
+            backoff_slot_size: Duration::from_millis(250),
+            max_retry_after: Duration::from_secs(30),
+        }
+    }
+
+    /// Adds a base URI of the service.
+    ///
+    /// The URI must use the `http` or `https` scheme, and may include a path prefix which will be
+    /// prepended to the paths of all requests. At least one URI is required.
+    pub fn uri(mut self, uri: Uri) -> Self {
+        self.uris.push(uri);
+        self
+    }
+
+    /// Sets the timeout for establishing a connection to the service.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets the timeout for a single attempt of a request, from when it is sent until the
+    /// response's headers are received.
+    ///
+    /// Defaults to 5 minutes.
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Sets the maximum number of times a request will be retried before giving up.
+    ///
+    /// Defaults to 4.
+    pub fn max_num_retries(mut self, max_num_retries: u32) -> Self {
+        self.max_num_retries = max_num_retries;
+        self
+    }
+
+    /// Sets the base duration used to compute the exponential backoff between retries.
+    ///
+    /// Before the `n`th retry, the client will sleep for a random duration between 0 and
+    /// `backoff_slot_size * 2^(n - 1)`. Defaults to 250 milliseconds.
+    pub fn backoff_slot_size(mut self, backoff_slot_size: Duration) -> Self {
+        self.backoff_slot_size = backoff_slot_size;
+        self
+    }
+
+    /// Sets the longest delay requested by a throttle response's `Retry-After` header which the
+    /// client will wait for before retrying.
+    ///
+    /// Throttle responses requesting a longer delay are returned as errors instead of being
+    /// retried. Defaults to 30 seconds.
+    pub fn max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Creates a new async client.
+    ///
+    /// The client must be used from within the context of a Tokio runtime with IO and time
+    /// enabled.
+    pub fn build(&self) -> Result<Client, Error> {
+        let state = ClientState::new(self)?;
+        Ok(Client::new(Arc::new(state)))
+    }
+
+    /// Creates a new blocking client.
+    ///
+    /// The client runs requests on an internal Tokio runtime, and must not be used from within
+    /// the context of another Tokio runtime.
+    pub fn build_blocking(&self) -> Result<blocking::Client, Error> {
+        let state = ClientState::new(self)?;
+        blocking::Client::new(Arc::new(state))
+    }
+}
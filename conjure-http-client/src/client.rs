@@ -0,0 +1,228 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::builder::Builder;
+use crate::state::{self, ClientState, Failure};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use conjure_error::Error;
+use conjure_http::client::{is_idempotent, AsyncClient, AsyncRequestBody, Endpoint};
+use futures_core::Stream;
+use futures_io::AsyncWrite;
+use futures_util::future;
+use futures_util::io::AsyncWriteExt;
+use futures_util::ready;
+use http::{request, Request, Response};
+use http_body::Body as _;
+use hyper::body::Sender;
+use hyper::Body;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+// The amount of data buffered by a body writer before it is sent to the connection.
+pub(crate) const CHUNK_SIZE: usize = 4 * 1024;
+
+/// An async HTTP client for Conjure services.
+///
+/// It is cheap to clone, with clones sharing the same connection pool.
+#[derive(Clone)]
+pub struct Client {
+    state: Arc<ClientState>,
+}
+
+impl Client {
+    pub(crate) fn new(state: Arc<ClientState>) -> Self {
+        Client { state }
+    }
+
+    /// Returns a new builder.
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    async fn send_attempt(
+        &self,
+        parts: &request::Parts,
+        body: &mut AsyncRequestBody<'_, BodyWriter>,
+        uri: usize,
+        idempotent: bool,
+    ) -> Result<hyper::Response<Body>, Failure> {
+        let writer = match body {
+            AsyncRequestBody::Empty => {
+                let request = self
+                    .state
+                    .build_request(uri, parts, Body::empty())
+                    .map_err(Failure::fatal)?;
+                return self.state.send(request, idempotent).await;
+            }
+            AsyncRequestBody::Fixed(body) => {
+                let request = self
+                    .state
+                    .build_request(uri, parts, Body::from(body.clone()))
+                    .map_err(Failure::fatal)?;
+                return self.state.send(request, idempotent).await;
+            }
+            AsyncRequestBody::Streaming(writer) => writer,
+        };
+
+        let (sender, body) = Body::channel();
+        let request = self
+            .state
+            .build_request(uri, parts, body)
+            .map_err(Failure::fatal)?;
+
+        let write = async {
+            let mut sender = BodyWriter::new(sender);
+            writer.as_mut().write_body(Pin::new(&mut sender)).await?;
+            sender.close().await.map_err(Error::internal_safe)
+        };
+        let (write, response) = future::join(write, self.state.send(request, idempotent)).await;
+        write.map_err(Failure::fatal)?;
+
+        response
+    }
+}
+
+#[async_trait]
+impl AsyncClient for Client {
+    type BodyWriter = BodyWriter;
+    type ResponseBody = ResponseBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, mut body) = req.into_parts();
+        let endpoint = parts.extensions.get::<Endpoint>();
+        let idempotent = is_idempotent(&parts);
+
+        let mut retrier = self.state.retrier();
+        loop {
+            let failure = match self
+                .send_attempt(&parts, &mut body, retrier.uri(), idempotent)
+                .await
+            {
+                Ok(response) => return Ok(response.map(ResponseBody::new)),
+                Err(failure) => failure,
+            };
+
+            let delay = match retrier.next_attempt(&failure) {
+                Some(delay) => delay,
+                None => return Err(state::with_endpoint(failure.error, endpoint)),
+            };
+
+            if let AsyncRequestBody::Streaming(writer) = &mut body {
+                if !writer.as_mut().reset().await {
+                    return Err(state::with_endpoint(failure.error, endpoint));
+                }
+            }
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// The request body writer used by [`Client`].
+///
+/// If the writer is dropped before it is closed, the request body is aborted.
+pub struct BodyWriter {
+    sender: Option<Sender>,
+    buf: BytesMut,
+}
+
+impl BodyWriter {
+    fn new(sender: Sender) -> Self {
+        BodyWriter {
+            sender: Some(sender),
+            buf: BytesMut::new(),
+        }
+    }
+
+    fn poll_flush_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.buf.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+
+        let sender = match &mut self.sender {
+            Some(sender) => sender,
+            None => return Poll::Ready(Err(closed())),
+        };
+        ready!(sender.poll_ready(cx)).map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))?;
+        sender
+            .try_send_data(self.buf.split().freeze())
+            .map_err(|_| closed())?;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for BodyWriter {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            sender.abort();
+        }
+    }
+}
+
+impl AsyncWrite for BodyWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.buf.len() >= CHUNK_SIZE {
+            ready!(self.poll_flush_buf(cx))?;
+        }
+
+        self.buf.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush_buf(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_flush_buf(cx))?;
+        // Dropping the sender without aborting it cleanly ends the body.
+        self.sender = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+pub(crate) fn closed() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "request body closed")
+}
+
+/// The response body returned by [`Client`].
+pub struct ResponseBody {
+    body: Body,
+}
+
+impl ResponseBody {
+    pub(crate) fn new(body: Body) -> Self {
+        ResponseBody { body }
+    }
+}
+
+impl Stream for ResponseBody {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.body)
+            .poll_data(cx)
+            .map(|data| data.map(|data| data.map_err(Error::internal_safe)))
+    }
+}
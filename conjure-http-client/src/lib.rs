@@ -0,0 +1,63 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [hyper](https://docs.rs/hyper)-based HTTP client for generated Conjure services.
+//!
+//! [`Client`] implements conjure-http's [`AsyncClient`](conjure_http::client::AsyncClient) trait,
+//! and [`blocking::Client`] implements its blocking [`Client`](conjure_http::client::Client)
+//! trait, so either can be passed to the constructor of a generated service client.
+//!
+//! # Behavior
+//!
+//! * Connections are pooled and reused across requests. Both HTTP and HTTPS URIs are supported,
+//!   with server certificates verified against the Mozilla root certificates.
+//! * A client can be configured with multiple URIs of replicas of a service. Requests are sent to
+//!   a single URI until it fails, at which point the client fails over to the next one.
+//! * Requests are retried with exponential backoff and jitter when the server responds with a
+//!   `429 Too Many Requests` or `503 Service Unavailable`. If a throttle response includes a
+//!   `Retry-After` header, that duration is used instead of the backoff, unless it is longer than
+//!   [`Builder::max_retry_after`]. Requests which fail due to an IO error are retried if the
+//!   connection could not be established or the request's method is idempotent.
+//! * Streaming request bodies are reset with [`WriteBody::reset`] before a retry. If the body
+//!   cannot be reset, the request is not retried.
+//! * Other non-2xx responses are converted into errors with
+//!   [`async_decode_error_response`](conjure_http::client::async_decode_error_response). The
+//!   service and name of the request's [`Endpoint`](conjure_http::client::Endpoint) are added to
+//!   all errors as the `service` and `endpoint` safe parameters.
+//!
+//! [`WriteBody::reset`]: conjure_http::client::WriteBody::reset
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use conjure_http::client::AsyncService;
+//! use conjure_http_client::Builder;
+//!
+//! let client = Builder::new()
+//!     .uri("https://foo-1.internal/api".parse()?)
+//!     .uri("https://foo-2.internal/api".parse()?)
+//!     .build()?;
+//! let client = FooServiceAsyncClient::new(client);
+//!
+//! let thing = client.get_thing("bar").await?;
+//! ```
+#![warn(missing_docs, clippy::all)]
+
+pub use crate::builder::Builder;
+pub use crate::client::{BodyWriter, Client, ResponseBody};
+
+pub mod blocking;
+mod builder;
+mod client;
+mod state;
@@ -0,0 +1,244 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::builder::Builder;
+use crate::client::ResponseBody;
+use conjure_error::{Error, ErrorKind};
+use conjure_http::client::{async_decode_error_response, Endpoint};
+use http::{request, Uri};
+use hyper::client::HttpConnector;
+use hyper::Body;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// The state shared by the blocking and async clients.
+pub(crate) struct ClientState {
+    client: hyper::Client<HttpsConnector<HttpConnector>>,
+    uris: Vec<Uri>,
+    current_uri: AtomicUsize,
+    request_timeout: Duration,
+    max_num_retries: u32,
+    backoff_slot_size: Duration,
+    max_retry_after: Duration,
+}
+
+impl ClientState {
+    pub(crate) fn new(builder: &Builder) -> Result<Self, Error> {
+        if builder.uris.is_empty() {
+            return Err(Error::internal_safe("at least one URI must be configured"));
+        }
+
+        for uri in &builder.uris {
+            let valid_scheme = matches!(uri.scheme_str(), Some("http") | Some("https"));
+            if !valid_scheme || uri.authority().is_none() || uri.query().is_some() {
+                return Err(Error::internal_safe("invalid service URI")
+                    .with_safe_param("uri", uri.to_string()));
+            }
+        }
+
+        let mut connector = HttpConnector::new();
+        connector.enforce_http(false);
+        connector.set_connect_timeout(Some(builder.connect_timeout));
+        connector.set_nodelay(true);
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .enable_http2()
+            .wrap_connector(connector);
+
+        Ok(ClientState {
+            client: hyper::Client::builder().build(connector),
+            uris: builder.uris.clone(),
+            current_uri: AtomicUsize::new(0),
+            request_timeout: builder.request_timeout,
+            max_num_retries: builder.max_num_retries,
+            backoff_slot_size: builder.backoff_slot_size,
+            max_retry_after: builder.max_retry_after,
+        })
+    }
+
+    /// Returns a new retry tracker for a request.
+    pub(crate) fn retrier(&self) -> Retrier<'_> {
+        Retrier {
+            state: self,
+            uri: self.current_uri.load(Ordering::Relaxed) % self.uris.len(),
+            attempt: 0,
+        }
+    }
+
+    /// Builds the hyper request for a single attempt of a Conjure request.
+    pub(crate) fn build_request(
+        &self,
+        uri: usize,
+        parts: &request::Parts,
+        body: Body,
+    ) -> Result<hyper::Request<Body>, Error> {
+        let base = &self.uris[uri];
+        let path_and_query = parts.uri.path_and_query().map_or("/", |p| p.as_str());
+        let uri = format!(
+            "{}://{}{}{}",
+            base.scheme_str().expect("validated at construction"),
+            base.authority().expect("validated at construction"),
+            base.path().trim_end_matches('/'),
+            path_and_query,
+        )
+        .parse::<Uri>()
+        .map_err(Error::internal_safe)?;
+
+        let mut request = hyper::Request::new(body);
+        *request.method_mut() = parts.method.clone();
+        *request.uri_mut() = uri;
+        *request.headers_mut() = parts.headers.clone();
+
+        Ok(request)
+    }
+
+    /// Sends a single attempt of a request.
+    ///
+    /// Non-2xx responses are decoded into errors.
+    pub(crate) async fn send(
+        &self,
+        request: hyper::Request<Body>,
+        idempotent: bool,
+    ) -> Result<hyper::Response<Body>, Failure> {
+        let response = tokio::time::timeout(self.request_timeout, self.client.request(request));
+        let response = match response.await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                // If the connection was never established, the server could not have seen the
+                // request.
+                let retry = if idempotent || e.is_connect() {
+                    Retry::NextUri
+                } else {
+                    Retry::No
+                };
+                return Err(Failure {
+                    error: Error::internal_safe(e),
+                    retry,
+                });
+            }
+            Err(e) => {
+                let retry = if idempotent {
+                    Retry::NextUri
+                } else {
+                    Retry::No
+                };
+                return Err(Failure {
+                    error: Error::internal_safe(e),
+                    retry,
+                });
+            }
+        };
+
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        let error = async_decode_error_response(response.map(ResponseBody::new)).await;
+        let retry = match error.kind() {
+            ErrorKind::Throttle(e) => Retry::SameUri(e.duration()),
+            ErrorKind::Unavailable(_) => Retry::NextUri,
+            _ => Retry::No,
+        };
+
+        Err(Failure { error, retry })
+    }
+}
+
+/// Adds the request's endpoint information to an error.
+pub(crate) fn with_endpoint(error: Error, endpoint: Option<&Endpoint>) -> Error {
+    match endpoint {
+        Some(endpoint) => error
+            .with_safe_param("service", endpoint.service())
+            .with_safe_param("endpoint", endpoint.name()),
+        None => error,
+    }
+}
+
+enum Retry {
+    No,
+    SameUri(Option<Duration>),
+    NextUri,
+}
+
+/// A failed attempt of a request.
+pub(crate) struct Failure {
+    pub(crate) error: Error,
+    retry: Retry,
+}
+
+impl Failure {
+    /// Creates a failure which will not be retried.
+    pub(crate) fn fatal(error: Error) -> Self {
+        Failure {
+            error,
+            retry: Retry::No,
+        }
+    }
+}
+
+/// Tracks the URI and attempt number of a request across retries.
+pub(crate) struct Retrier<'a> {
+    state: &'a ClientState,
+    uri: usize,
+    attempt: u32,
+}
+
+impl Retrier<'_> {
+    /// Returns the index of the URI the next attempt should be sent to.
+    pub(crate) fn uri(&self) -> usize {
+        self.uri
+    }
+
+    /// Returns the duration to wait before retrying after a failed attempt, or `None` if the
+    /// request should not be retried.
+    pub(crate) fn next_attempt(&mut self, failure: &Failure) -> Option<Duration> {
+        match failure.retry {
+            Retry::No => return None,
+            Retry::SameUri(Some(delay)) if delay > self.state.max_retry_after => return None,
+            _ => {}
+        }
+
+        if self.attempt >= self.state.max_num_retries {
+            return None;
+        }
+        self.attempt += 1;
+
+        match failure.retry {
+            Retry::No => unreachable!(),
+            Retry::SameUri(Some(delay)) => Some(delay),
+            Retry::SameUri(None) => Some(self.backoff()),
+            Retry::NextUri => {
+                let next = (self.uri + 1) % self.state.uris.len();
+                // Move every request off of the failed URI, unless another request has already
+                // done so.
+                let _ = self.state.current_uri.compare_exchange(
+                    self.uri,
+                    next,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+                self.uri = next;
+                Some(self.backoff())
+            }
+        }
+    }
+
+    fn backoff(&self) -> Duration {
+        let scale = 1u32.checked_shl(self.attempt - 1).unwrap_or(u32::MAX);
+        let max = self.state.backoff_slot_size.saturating_mul(scale);
+        max.mul_f64(rand::random::<f64>())
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::server::RequestBody;
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use conjure_error::Error;
+use conjure_http::server::tower::ResponseWriter;
+use conjure_http::server::{
+    ArgumentMetadata, AsyncEndpoint, AsyncResponseBody, AsyncService, AsyncWriteBody, AuthType,
+    Endpoint, EndpointMetadata, PathSegment, ResponseBody, Service,
+};
+use futures_util::{AsyncWriteExt, StreamExt};
+use http::{response, Extensions, Method, Request, Response};
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::{self, JoinHandle};
+
+// The amount of data buffered by a blocking response writer before it is handed to the
+// connection.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+type BoxEndpoint = Arc<dyn Endpoint<BlockingRequestBody, BlockingResponseWriter> + Sync + Send>;
+
+/// The request body type passed to blocking endpoints by [`Server`](crate::Server).
+pub struct BlockingRequestBody {
+    body: RequestBody,
+    runtime: Handle,
+}
+
+impl Iterator for BlockingRequestBody {
+    type Item = Result<Bytes, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.body.next())
+    }
+}
+
+/// The writer passed to streaming response bodies of blocking endpoints by
+/// [`Server`](crate::Server).
+pub struct BlockingResponseWriter {
+    sender: mpsc::Sender<Result<Bytes, Error>>,
+    buf: BytesMut,
+}
+
+impl Write for BlockingResponseWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let chunk = self.buf.split().freeze();
+        self.sender
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "response body closed"))
+    }
+}
+
+/// Adapts a blocking service into an async service which runs its endpoints on Tokio's blocking
+/// thread pool.
+pub(crate) struct BlockingService<'a, T>(pub(crate) &'a T)
+where
+    T: ?Sized;
+
+impl<T> AsyncService<RequestBody, ResponseWriter> for BlockingService<'_, T>
+where
+    T: ?Sized + Service<BlockingRequestBody, BlockingResponseWriter>,
+{
+    fn endpoints(&self) -> Vec<Box<dyn AsyncEndpoint<RequestBody, ResponseWriter> + Sync + Send>> {
+        self.0
+            .endpoints()
+            .into_iter()
+            .map(|endpoint| {
+                Box::new(BlockingEndpoint {
+                    endpoint: Arc::from(endpoint),
+                }) as _
+            })
+            .collect()
+    }
+}
+
+struct BlockingEndpoint {
+    endpoint: BoxEndpoint,
+}
+
+impl EndpointMetadata for BlockingEndpoint {
+    fn method(&self) -> Method {
+        self.endpoint.method()
+    }
+
+    fn path(&self) -> &[PathSegment] {
+        self.endpoint.path()
+    }
+
+    fn template(&self) -> &str {
+        self.endpoint.template()
+    }
+
+    fn service_name(&self) -> &str {
+        self.endpoint.service_name()
+    }
+
+    fn name(&self) -> &str {
+        self.endpoint.name()
+    }
+
+    fn deprecated(&self) -> Option<&str> {
+        self.endpoint.deprecated()
+    }
+
+    fn auth(&self) -> Option<AuthType> {
+        self.endpoint.auth()
+    }
+
+    fn args(&self) -> &[ArgumentMetadata] {
+        self.endpoint.args()
+    }
+
+    fn returns(&self) -> Option<&str> {
+        self.endpoint.returns()
+    }
+
+    fn markers(&self) -> &[&str] {
+        self.endpoint.markers()
+    }
+
+    fn tags(&self) -> &[&str] {
+        self.endpoint.tags()
+    }
+}
+
+struct Head {
+    parts: response::Parts,
+    extensions: Extensions,
+    body: HeadBody,
+}
+
+enum HeadBody {
+    Empty,
+    Fixed(Bytes),
+    Streaming,
+}
+
+#[async_trait]
+impl AsyncEndpoint<RequestBody, ResponseWriter> for BlockingEndpoint {
+    async fn handle(
+        &self,
+        req: Request<RequestBody>,
+        response_extensions: &mut Extensions,
+    ) -> Result<Response<AsyncResponseBody<ResponseWriter>>, Error> {
+        let endpoint = self.endpoint.clone();
+        let runtime = Handle::current();
+        let (head_sender, head_receiver) = oneshot::channel();
+        let (body_sender, body_receiver) = mpsc::channel(1);
+
+        // The endpoint's streaming body isn't Send, so it has to be written from the same thread
+        // that handled the request.
+        let join = task::spawn_blocking(move || {
+            let req = req.map(|body| BlockingRequestBody { body, runtime });
+            let mut extensions = Extensions::new();
+            let response = match endpoint.handle(req, &mut extensions) {
+                Ok(response) => response,
+                Err(e) => {
+                    let _ = head_sender.send(Err(e));
+                    return;
+                }
+            };
+
+            let (parts, body) = response.into_parts();
+            let (body, writer) = match body {
+                ResponseBody::Empty => (HeadBody::Empty, None),
+                ResponseBody::Fixed(bytes) => (HeadBody::Fixed(bytes), None),
+                ResponseBody::Streaming(writer) => (HeadBody::Streaming, Some(writer)),
+            };
+            let head = Head {
+                parts,
+                extensions,
+                body,
+            };
+            if head_sender.send(Ok(head)).is_err() {
+                return;
+            }
+
+            if let Some(writer) = writer {
+                let mut w = BlockingResponseWriter {
+                    sender: body_sender,
+                    buf: BytesMut::new(),
+                };
+                let result = writer
+                    .write_body(&mut w)
+                    .and_then(|()| w.flush().map_err(Error::internal_safe));
+                if let Err(e) = result {
+                    let _ = w.sender.blocking_send(Err(e));
+                }
+            }
+        });
+
+        let head = match head_receiver.await {
+            Ok(head) => head?,
+            Err(_) => return Err(join_error(join).await),
+        };
+
+        response_extensions.extend(head.extensions);
+        let body = match head.body {
+            HeadBody::Empty => AsyncResponseBody::Empty,
+            HeadBody::Fixed(bytes) => AsyncResponseBody::Fixed(bytes),
+            HeadBody::Streaming => AsyncResponseBody::Streaming(Box::new(StreamingBody {
+                receiver: body_receiver,
+                join,
+            })),
+        };
+
+        Ok(Response::from_parts(head.parts, body))
+    }
+}
+
+async fn join_error(join: JoinHandle<()>) -> Error {
+    match join.await {
+        Ok(()) => Error::internal_safe("blocking endpoint exited without a response"),
+        Err(e) => Error::internal_safe(e),
+    }
+}
+
+struct StreamingBody {
+    receiver: mpsc::Receiver<Result<Bytes, Error>>,
+    join: JoinHandle<()>,
+}
+
+#[async_trait]
+impl AsyncWriteBody<ResponseWriter> for StreamingBody {
+    async fn write_body(mut self: Box<Self>, mut w: Pin<&mut ResponseWriter>) -> Result<(), Error> {
+        while let Some(chunk) = self.receiver.recv().await {
+            w.write_all(&chunk?).await.map_err(Error::internal_safe)?;
+        }
+
+        // The channel also closes if the thread panicked partway through the body.
+        (&mut self.join).await.map_err(Error::internal_safe)?;
+
+        w.flush().await.map_err(Error::internal_safe)
+    }
+}
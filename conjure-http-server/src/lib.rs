@@ -0,0 +1,50 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [hyper](https://docs.rs/hyper)-based HTTP server for generated Conjure services.
+//!
+//! The server routes requests to the endpoints of a set of blocking and async Conjure services,
+//! streams request and response bodies, and serializes errors returned by endpoints in the
+//! standard Conjure format. It is built on conjure-http's
+//! [`ConjureService`](conjure_http::server::tower::ConjureService).
+//!
+//! Async services are run directly on the Tokio runtime. Blocking services are run on Tokio's
+//! blocking thread pool, with their request and response bodies streamed to and from the
+//! connection.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use conjure_http_server::Server;
+//!
+//! let server = Server::builder()
+//!     .service(&MyServiceEndpoints::new(MyServiceHandler))
+//!     .blocking_service(&OtherServiceEndpoints::new(OtherServiceHandler))
+//!     .bind("0.0.0.0:8443".parse()?)?;
+//!
+//! server
+//!     .serve_with_graceful_shutdown(async {
+//!         tokio::signal::ctrl_c().await.unwrap();
+//!     })
+//!     .await?;
+//! ```
+#![warn(missing_docs, clippy::all)]
+
+pub use crate::blocking::{BlockingRequestBody, BlockingResponseWriter};
+pub use crate::server::{RequestBody, Server, ServerBuilder};
+#[doc(inline)]
+pub use conjure_http::server::tower::ResponseWriter;
+
+mod blocking;
+mod server;
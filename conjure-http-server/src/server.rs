@@ -0,0 +1,173 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::blocking::{BlockingRequestBody, BlockingResponseWriter, BlockingService};
+use conjure_error::Error;
+use conjure_http::server::tower::{ConjureService, ConjureServiceBuilder, ResponseWriter};
+use conjure_http::server::{AsyncService, ErrorHook, Service};
+use hyper::service::make_service_fn;
+use hyper::Body;
+use std::convert::Infallible;
+use std::future::{self, Future};
+use std::net::{SocketAddr, TcpListener};
+use std::time::Duration;
+
+/// The request body type passed to async endpoints by [`Server`].
+pub type RequestBody = conjure_http::server::tower::RequestBody<Body>;
+
+/// A builder for [`Server`]s.
+pub struct ServerBuilder {
+    service: ConjureServiceBuilder<Body>,
+    keep_alive: bool,
+    header_read_timeout: Duration,
+}
+
+impl ServerBuilder {
+    /// Adds all endpoints of an async service.
+    pub fn service<T>(mut self, service: &T) -> Self
+    where
+        T: ?Sized + AsyncService<RequestBody, ResponseWriter>,
+    {
+        self.service = self.service.service(service);
+        self
+    }
+
+    /// Adds all endpoints of a blocking service.
+    ///
+    /// The endpoints are run on Tokio's blocking thread pool.
+    pub fn blocking_service<T>(mut self, service: &T) -> Self
+    where
+        T: ?Sized + Service<BlockingRequestBody, BlockingResponseWriter>,
+    {
+        self.service = self.service.service(&BlockingService(service));
+        self
+    }
+
+    /// Sets a hook which will be passed every error encoded into a response.
+    ///
+    /// This is typically used to log errors. Defaults to a no-op.
+    pub fn error_hook<H>(mut self, error_hook: H) -> Self
+    where
+        H: ErrorHook + 'static + Sync + Send,
+    {
+        self.service = self.service.error_hook(error_hook);
+        self
+    }
+
+    /// Sets whether HTTP/1 connections are kept alive between requests.
+    ///
+    /// Defaults to `true`.
+    pub fn keep_alive(mut self, keep_alive: bool) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    /// Sets the maximum amount of time the server will wait for the headers of a request on an
+    /// HTTP/1 connection.
+    ///
+    /// This also bounds the time an idle keep-alive connection is held open waiting for its next
+    /// request. Defaults to 30 seconds.
+    pub fn header_read_timeout(mut self, header_read_timeout: Duration) -> Self {
+        self.header_read_timeout = header_read_timeout;
+        self
+    }
+
+    /// Binds the server to a socket.
+    ///
+    /// Returns an error if the routes of the registered endpoints conflict or the socket cannot be
+    /// bound.
+    pub fn bind(self, addr: SocketAddr) -> Result<Server, Error> {
+        let listener = TcpListener::bind(addr)
+            .map_err(|e| Error::internal_safe(e).with_safe_param("addr", addr.to_string()))?;
+        self.from_listener(listener)
+    }
+
+    /// Creates a server from an already bound socket.
+    ///
+    /// Returns an error if the routes of the registered endpoints conflict.
+    pub fn from_listener(self, listener: TcpListener) -> Result<Server, Error> {
+        listener
+            .set_nonblocking(true)
+            .map_err(Error::internal_safe)?;
+        let local_addr = listener.local_addr().map_err(Error::internal_safe)?;
+
+        Ok(Server {
+            service: self.service.build()?,
+            listener,
+            local_addr,
+            keep_alive: self.keep_alive,
+            header_read_timeout: self.header_read_timeout,
+        })
+    }
+}
+
+/// An HTTP server serving a set of Conjure services.
+///
+/// The server supports HTTP/1 and cleartext HTTP/2 connections. TLS is not supported directly,
+/// and should be terminated in front of the server.
+pub struct Server {
+    service: ConjureService<Body>,
+    listener: TcpListener,
+    local_addr: SocketAddr,
+    keep_alive: bool,
+    header_read_timeout: Duration,
+}
+
+impl Server {
+    /// Returns a new builder.
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder {
+            service: ConjureService::builder(),
+            keep_alive: true,
+            header_read_timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Returns the local address the server is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Serves requests until an error occurs.
+    ///
+    /// This must be called from within the context of a Tokio runtime.
+    pub async fn serve(self) -> Result<(), Error> {
+        self.serve_with_graceful_shutdown(future::pending()).await
+    }
+
+    /// Serves requests until the `signal` future completes.
+    ///
+    /// Once the signal completes, the server stops accepting new connections and waits for all
+    /// in-flight requests to complete before returning.
+    ///
+    /// This must be called from within the context of a Tokio runtime.
+    pub async fn serve_with_graceful_shutdown<F>(self, signal: F) -> Result<(), Error>
+    where
+        F: Future<Output = ()>,
+    {
+        let service = self.service;
+        let make_service = make_service_fn(move |_| {
+            let service = service.clone();
+            async move { Ok::<_, Infallible>(service) }
+        });
+
+        hyper::Server::from_tcp(self.listener)
+            .map_err(Error::internal_safe)?
+            .http1_keepalive(self.keep_alive)
+            .http1_header_read_timeout(self.header_read_timeout)
+            .serve(make_service)
+            .with_graceful_shutdown(signal)
+            .await
+            .map_err(Error::internal_safe)
+    }
+}
@@ -14,20 +14,34 @@
 
 //! The Conjure HTTP client API.
 
-use crate::private::{self, APPLICATION_JSON};
+use crate::private::{self, Encoding, APPLICATION_JSON};
 use async_trait::async_trait;
 use bytes::Bytes;
-use conjure_error::Error;
+use conjure_error::{Error, SerializableError};
 use conjure_serde::json;
 use futures_core::Stream;
-use http::header::CONTENT_TYPE;
-use http::{HeaderValue, Request, Response};
+use http::header::RETRY_AFTER;
+use http::{request, response, HeaderValue, Method, Request, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::convert::TryFrom;
 use std::fmt::Display;
 use std::io::Write;
 use std::pin::Pin;
+use std::time::Duration;
+
+pub use self::auth::{AsyncTokenProvider, TokenProvider};
+pub use self::deadline::{DeadlineAsyncClient, DeadlineClient};
+pub use self::logging::LoggingClient;
+pub use self::retry::{RetryPolicy, RetryingAsyncClient, RetryingClient, RetryingClientBuilder};
+pub use self::trace::TracingClient;
+
+mod auth;
+mod deadline;
+mod logging;
+mod retry;
+mod sleep;
+mod trace;
 
 #[allow(missing_docs)]
 #[deprecated(note = "renamed to RequestBody", since = "3.5.0")]
@@ -58,6 +72,11 @@ pub struct Endpoint {
     version: Option<&'static str>,
     name: &'static str,
     path: &'static str,
+    method: Option<Method>,
+    idempotent: Option<bool>,
+    replayable_body: bool,
+    tags: &'static [&'static str],
+    markers: &'static [&'static str],
 }
 
 impl Endpoint {
@@ -74,9 +93,53 @@ impl Endpoint {
             version,
             name,
             path,
+            method: None,
+            idempotent: None,
+            replayable_body: true,
+            tags: &[],
+            markers: &[],
         }
     }
 
+    /// Sets the HTTP method of the endpoint.
+    #[inline]
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Sets whether the endpoint is idempotent.
+    ///
+    /// If not set, idempotency is determined from the endpoint's method.
+    #[inline]
+    pub fn with_idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = Some(idempotent);
+        self
+    }
+
+    /// Sets whether the request body can be sent multiple times without being reset.
+    ///
+    /// Defaults to `true`.
+    #[inline]
+    pub fn with_replayable_body(mut self, replayable_body: bool) -> Self {
+        self.replayable_body = replayable_body;
+        self
+    }
+
+    /// Sets the endpoint's tags.
+    #[inline]
+    pub fn with_tags(mut self, tags: &'static [&'static str]) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets the names of the endpoint's marker types.
+    #[inline]
+    pub fn with_markers(mut self, markers: &'static [&'static str]) -> Self {
+        self.markers = markers;
+        self
+    }
+
     /// Returns the name of the service the endpoint is part of.
     #[inline]
     pub fn service(&self) -> &'static str {
@@ -100,6 +163,76 @@ impl Endpoint {
     pub fn path(&self) -> &'static str {
         self.path
     }
+
+    /// Returns the HTTP method of the endpoint, if known.
+    #[inline]
+    pub fn method(&self) -> Option<&Method> {
+        self.method.as_ref()
+    }
+
+    /// Returns whether the endpoint is idempotent, and is therefore safe to retry.
+    ///
+    /// Unless explicitly set, endpoints with the `GET`, `HEAD`, `OPTIONS`, `TRACE`, `PUT`, and
+    /// `DELETE` methods are considered idempotent. Endpoints with an unknown method are not.
+    #[inline]
+    pub fn idempotent(&self) -> bool {
+        match (self.idempotent, &self.method) {
+            (Some(idempotent), _) => idempotent,
+            (None, Some(method)) => Endpoint::idempotent_for(method),
+            (None, None) => false,
+        }
+    }
+
+    /// Returns whether endpoints with the given method are idempotent unless explicitly set
+    /// otherwise.
+    #[inline]
+    pub fn idempotent_for(method: &Method) -> bool {
+        matches!(
+            *method,
+            Method::GET
+                | Method::HEAD
+                | Method::OPTIONS
+                | Method::TRACE
+                | Method::PUT
+                | Method::DELETE
+        )
+    }
+
+    /// Returns whether the request body can be sent multiple times without being reset.
+    ///
+    /// This is `true` for requests with an empty or buffered body, and `false` for requests with
+    /// a streaming body, which must be [reset](WriteBody::reset) before it can be sent again.
+    #[inline]
+    pub fn replayable_body(&self) -> bool {
+        self.replayable_body
+    }
+
+    /// Returns the endpoint's tags.
+    #[inline]
+    pub fn tags(&self) -> &'static [&'static str] {
+        self.tags
+    }
+
+    /// Returns the names of the endpoint's marker types.
+    #[inline]
+    pub fn markers(&self) -> &'static [&'static str] {
+        self.markers
+    }
+}
+
+/// Returns whether a request is idempotent, and is therefore safe to retry.
+///
+/// The request's [`Endpoint`] extension determines idempotency if it was explicitly set, and
+/// otherwise the request's method does, as described in [`Endpoint::idempotent`].
+pub fn is_idempotent(parts: &request::Parts) -> bool {
+    match parts
+        .extensions
+        .get::<Endpoint>()
+        .and_then(|e| e.idempotent)
+    {
+        Some(idempotent) => idempotent,
+        None => Endpoint::idempotent_for(&parts.method),
+    }
 }
 
 /// The body of a blocking Conjure request.
@@ -134,8 +267,8 @@ pub trait Client {
     /// The request's URI will be in absolute-form and it will always contain an `Endpoint` object in its extensions.
     ///
     /// A response must only be returned if it has a 2xx status code. The client is responsible for handling all other
-    /// status codes (for example, converting a 5xx response into a service error). The client is also responsible for
-    /// decoding the response body if necessary.
+    /// status codes (for example, converting a 5xx response into a service error with [`decode_error_response`]). The
+    /// client is also responsible for decoding the response body if necessary.
     fn send(
         &self,
         req: Request<RequestBody<'_, Self::BodyWriter>>,
@@ -158,14 +291,111 @@ pub trait AsyncClient {
     /// parameters, unencoded query parameters, header parameters, and request body.
     ///
     /// A response must only be returned if it has a 2xx status code. The client is responsible for handling all other
-    /// status codes (for example, converting a 5xx response into a service error). The client is also responsible for
-    /// decoding the response body if necessary.
+    /// status codes (for example, converting a 5xx response into a service error with
+    /// [`async_decode_error_response`]). The client is also responsible for decoding the response body if necessary.
     async fn send(
         &self,
         req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
     ) -> Result<Response<Self::ResponseBody>, Error>;
 }
 
+/// Decodes a non-2xx response into an error.
+///
+/// This is intended to be used by [`Client`] implementations to handle responses that must not be
+/// returned to the caller.
+///
+/// * A `429 Too Many Requests` response is converted into a throttle error. If the response
+///   contains a `Retry-After` header with an integral number of seconds, it will be used as the
+///   error's throttle duration.
+/// * A `503 Service Unavailable` response is converted into an unavailable error.
+/// * Any other response with a JSON-encoded `SerializableError` body is converted into a
+///   propagated service error.
+///
+/// The body is not read for throttle and unavailable responses. Otherwise, up to 1 MiB of the
+/// response body is read. If the body fails to read, it is too large, or it cannot be decoded as a
+/// `SerializableError`, an internal error is returned instead. In all cases, the response's status
+/// code is included in the error as the `status` safe parameter. The body of an undecodable
+/// response is included as the `body` unsafe parameter, and the cause of a read failure as the
+/// `cause` unsafe parameter.
+pub fn decode_error_response<R>(response: Response<R>) -> Error
+where
+    R: Iterator<Item = Result<Bytes, Error>>,
+{
+    let (parts, body) = response.into_parts();
+    if let Some(error) = decode_error_status(&parts) {
+        return error;
+    }
+
+    let body = private::read_body(body, Some(ERROR_RESPONSE_SIZE_LIMIT));
+    decode_error_body(&parts, body)
+}
+
+/// Decodes a non-2xx response into an error.
+///
+/// This is intended to be used by [`AsyncClient`] implementations, and is otherwise identical to
+/// [`decode_error_response`].
+pub async fn async_decode_error_response<R>(response: Response<R>) -> Error
+where
+    R: Stream<Item = Result<Bytes, Error>>,
+{
+    let (parts, body) = response.into_parts();
+    if let Some(error) = decode_error_status(&parts) {
+        return error;
+    }
+
+    let body = private::async_read_body(body, Some(ERROR_RESPONSE_SIZE_LIMIT)).await;
+    decode_error_body(&parts, body)
+}
+
+const ERROR_RESPONSE_SIZE_LIMIT: usize = 1024 * 1024;
+
+fn decode_error_status(parts: &response::Parts) -> Option<Error> {
+    let error = match parts.status {
+        StatusCode::TOO_MANY_REQUESTS => {
+            let retry_after = parts
+                .headers
+                .get(RETRY_AFTER)
+                .and_then(|h| h.to_str().ok())
+                .and_then(|s| s.parse().ok());
+            match retry_after {
+                Some(secs) => Error::throttle_for_safe(
+                    "remote service requested throttling",
+                    Duration::from_secs(secs),
+                ),
+                None => Error::throttle_safe("remote service requested throttling"),
+            }
+        }
+        StatusCode::SERVICE_UNAVAILABLE => Error::unavailable_safe("remote service unavailable"),
+        _ => return None,
+    };
+
+    Some(error.with_safe_param("status", parts.status.as_u16()))
+}
+
+fn decode_error_body(parts: &response::Parts, body: Result<Bytes, Error>) -> Error {
+    let status = parts.status.as_u16();
+
+    let body = match body {
+        Ok(body) => body,
+        Err(e) => {
+            return Error::internal_safe("unable to read remote error response")
+                .with_safe_param("status", status)
+                .with_unsafe_param("cause", e.cause().to_string())
+        }
+    };
+
+    if let Some(encoding) = Encoding::from_content_type(&parts.headers) {
+        if let Ok(error) = encoding.client_from_slice::<SerializableError>(&body) {
+            return Error::propagated_service_safe("remote service returned an error", error)
+                .with_safe_param("status", status);
+        }
+    }
+
+    Error::internal_safe("unable to decode remote error response")
+        .with_safe_param("status", status)
+        .with_unsafe_param("body", String::from_utf8_lossy(&body))
+}
+
 /// A trait implemented by streaming bodies.
 pub trait WriteBody<W> {
     /// Writes the body out, in its entirety.
@@ -332,8 +562,17 @@ pub trait AsyncDeserializeResponse<T, R> {
 }
 
 /// A response deserializer which acts like a Conjure-generated client would.
+///
+/// Both JSON and Smile response bodies are supported.
 pub enum ConjureResponseDeserializer {}
 
+impl ConjureResponseDeserializer {
+    fn check_content_type<R>(response: &Response<R>) -> Result<Encoding, Error> {
+        Encoding::from_content_type(response.headers())
+            .ok_or_else(|| Error::internal_safe("invalid response Content-Type"))
+    }
+}
+
 impl<T, R> DeserializeResponse<T, R> for ConjureResponseDeserializer
 where
     T: DeserializeOwned,
@@ -344,11 +583,9 @@ where
     }
 
     fn deserialize(response: Response<R>) -> Result<T, Error> {
-        if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-            return Err(Error::internal_safe("invalid response Content-Type"));
-        }
+        let encoding = Self::check_content_type(&response)?;
         let buf = private::read_body(response.into_body(), None)?;
-        json::client_from_slice(&buf).map_err(Error::internal)
+        encoding.client_from_slice(&buf)
     }
 }
 
@@ -363,11 +600,9 @@ where
     }
 
     async fn deserialize(response: Response<R>) -> Result<T, Error> {
-        if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-            return Err(Error::internal_safe("invalid response Content-Type"));
-        }
+        let encoding = Self::check_content_type(&response)?;
         let buf = private::async_read_body(response.into_body(), None).await?;
-        json::client_from_slice(&buf).map_err(Error::internal)
+        encoding.client_from_slice(&buf)
     }
 }
 
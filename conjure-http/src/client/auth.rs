@@ -0,0 +1,72 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use async_trait::async_trait;
+use conjure_error::Error;
+use conjure_object::BearerToken;
+
+/// A source of the bearer tokens used to authenticate requests.
+///
+/// Blocking clients generated in token provider mode hold a provider rather than taking an
+/// explicit token argument on each authenticated endpoint. The provider is called once for each
+/// request, so implementations which need to refresh their token should cache it rather than
+/// fetching a new one every time.
+///
+/// The trait is implemented for `BearerToken` itself, which always provides the same token, and for
+/// closures returning a `Result<BearerToken, Error>`.
+pub trait TokenProvider: Sync + Send {
+    /// Returns the token to use for a request.
+    ///
+    /// If an error is returned, the request is not sent and the error is returned to the caller.
+    fn token(&self) -> Result<BearerToken, Error>;
+}
+
+impl TokenProvider for BearerToken {
+    fn token(&self) -> Result<BearerToken, Error> {
+        Ok(self.clone())
+    }
+}
+
+impl<F> TokenProvider for F
+where
+    F: Fn() -> Result<BearerToken, Error> + Sync + Send,
+{
+    fn token(&self) -> Result<BearerToken, Error> {
+        self()
+    }
+}
+
+/// An async source of the bearer tokens used to authenticate requests.
+///
+/// This is the async equivalent of [`TokenProvider`], held by async clients generated in token
+/// provider mode. It allows implementations to fetch or refresh their token without blocking the
+/// executor.
+///
+/// The trait is implemented for all `TokenProvider`s.
+#[async_trait]
+pub trait AsyncTokenProvider: Sync + Send {
+    /// Returns the token to use for a request.
+    ///
+    /// If an error is returned, the request is not sent and the error is returned to the caller.
+    async fn token(&self) -> Result<BearerToken, Error>;
+}
+
+#[async_trait]
+impl<T> AsyncTokenProvider for T
+where
+    T: ?Sized + TokenProvider,
+{
+    async fn token(&self) -> Result<BearerToken, Error> {
+        TokenProvider::token(self)
+    }
+}
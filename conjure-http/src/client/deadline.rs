@@ -0,0 +1,162 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::client::sleep::{self, Sleep};
+use crate::client::{AsyncClient, AsyncRequestBody, Client, RequestBody};
+use crate::deadline::{Deadline, DEADLINE_HEADER};
+use async_trait::async_trait;
+use conjure_error::Error;
+use http::header::HeaderName;
+use http::{request, Request, Response};
+use std::future::Future;
+use std::time::Duration;
+
+/// A client wrapper which applies a [`Deadline`] to every request.
+///
+/// Before a request is sent, the deadline is inserted into its extensions and propagated to the
+/// server in the [`DEADLINE_HEADER`] header. If the request's extensions already contain an
+/// earlier deadline, that one is used instead. Requests are not sent at all if their deadline has
+/// already passed, and instead fail with a [`Timeout`](conjure_error::Timeout) error.
+///
+/// Blocking requests can't be interrupted once they've been sent, so it's up to the server to
+/// enforce the deadline from that point.
+#[derive(Clone)]
+pub struct DeadlineClient<C> {
+    client: C,
+    deadline: Deadline,
+}
+
+impl<C> DeadlineClient<C> {
+    /// Creates a new `DeadlineClient` which applies `deadline` to requests made by `client`.
+    pub fn new(client: C, deadline: Deadline) -> Self {
+        DeadlineClient { client, deadline }
+    }
+
+    /// Returns a shared reference to the inner client.
+    pub fn get_ref(&self) -> &C {
+        &self.client
+    }
+
+    /// Returns the client's deadline.
+    pub fn deadline(&self) -> Deadline {
+        self.deadline
+    }
+}
+
+impl<C> Client for DeadlineClient<C>
+where
+    C: Client,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (mut parts, body) = req.into_parts();
+        apply_deadline(self.deadline, &mut parts)?;
+        self.client.send(Request::from_parts(parts, body))
+    }
+}
+
+/// An [`AsyncClient`] which applies a [`Deadline`] to every request.
+///
+/// In addition to the behavior of [`DeadlineClient`], requests which are still in flight when
+/// their deadline passes are cancelled and fail with a [`Timeout`](conjure_error::Timeout) error.
+pub struct DeadlineAsyncClient<C> {
+    client: C,
+    deadline: Deadline,
+    sleep: Sleep,
+}
+
+impl<C> Clone for DeadlineAsyncClient<C>
+where
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        DeadlineAsyncClient {
+            client: self.client.clone(),
+            deadline: self.deadline,
+            sleep: self.sleep.clone(),
+        }
+    }
+}
+
+impl<C> DeadlineAsyncClient<C> {
+    /// Creates a new `DeadlineAsyncClient` which applies `deadline` to requests made by `client`.
+    ///
+    /// The `sleep` function is used to wait for the deadline to pass. It is typically the sleep
+    /// function of the async runtime the client is used with, such as `tokio::time::sleep`.
+    pub fn new<F, S>(client: C, deadline: Deadline, sleep: F) -> Self
+    where
+        F: Fn(Duration) -> S + 'static + Sync + Send,
+        S: Future<Output = ()> + 'static + Send,
+    {
+        DeadlineAsyncClient {
+            client,
+            deadline,
+            sleep: sleep::boxed(sleep),
+        }
+    }
+
+    /// Returns a shared reference to the inner client.
+    pub fn get_ref(&self) -> &C {
+        &self.client
+    }
+
+    /// Returns the client's deadline.
+    pub fn deadline(&self) -> Deadline {
+        self.deadline
+    }
+}
+
+#[async_trait]
+impl<C> AsyncClient for DeadlineAsyncClient<C>
+where
+    C: AsyncClient + Sync + Send,
+    C::BodyWriter: Send,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (mut parts, body) = req.into_parts();
+        let deadline = apply_deadline(self.deadline, &mut parts)?;
+        deadline
+            .enforce(
+                (self.sleep)(deadline.remaining()),
+                self.client.send(Request::from_parts(parts, body)),
+            )
+            .await
+    }
+}
+
+fn apply_deadline(deadline: Deadline, parts: &mut request::Parts) -> Result<Deadline, Error> {
+    let deadline = match parts.extensions.get::<Deadline>() {
+        Some(existing) => deadline.min(*existing),
+        None => deadline,
+    };
+    deadline.check()?;
+
+    parts.extensions.insert(deadline);
+    parts.headers.insert(
+        HeaderName::from_static(DEADLINE_HEADER),
+        deadline.to_header_value(),
+    );
+
+    Ok(deadline)
+}
@@ -0,0 +1,138 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::client::{AsyncClient, AsyncRequestBody, Client, Endpoint, RequestBody};
+use crate::request_log::{RequestLog, RequestLogSink};
+use crate::SafeParams;
+use async_trait::async_trait;
+use conjure_error::Error;
+use http::{Method, Request, Response, StatusCode};
+use std::time::Instant;
+
+/// A client wrapper which emits a [`RequestLog`] for every request.
+///
+/// The endpoint is identified by the request's [`Endpoint`] extension, and the values of safe
+/// parameters are taken from its [`SafeParams`] extension, which is populated by generated
+/// Conjure clients.
+///
+/// The logged duration covers the time taken by the inner client to return a response, so it does
+/// not include the time taken to read the response body. If the request fails with an error
+/// decoded from an error response, the logged status is taken from the error's `status` safe
+/// parameter.
+#[derive(Clone)]
+pub struct LoggingClient<C, S> {
+    client: C,
+    sink: S,
+}
+
+impl<C, S> LoggingClient<C, S> {
+    /// Creates a new `LoggingClient` which passes logs of requests made by `client` to `sink`.
+    pub fn new(client: C, sink: S) -> Self {
+        LoggingClient { client, sink }
+    }
+
+    /// Returns a shared reference to the inner client.
+    pub fn get_ref(&self) -> &C {
+        &self.client
+    }
+}
+
+impl<C, S> LoggingClient<C, S>
+where
+    S: RequestLogSink,
+{
+    fn log<B>(&self, request: RequestInfo, start: Instant, result: &Result<Response<B>, Error>) {
+        let (service, name, path) = match &request.endpoint {
+            Some(endpoint) => (endpoint.service(), endpoint.name(), endpoint.path()),
+            None => ("", "", ""),
+        };
+
+        let log = RequestLog::new(service, name, request.method, path, start.elapsed());
+        let log = match result {
+            Ok(response) => log.with_status(response.status()),
+            Err(error) => match error_status(error) {
+                Some(status) => log.with_status(status).with_error(error),
+                None => log.with_error(error),
+            },
+        };
+
+        self.sink
+            .log(&log.with_safe_params(request.safe_params.as_ref()));
+    }
+}
+
+struct RequestInfo {
+    method: Method,
+    endpoint: Option<Endpoint>,
+    safe_params: Option<SafeParams>,
+}
+
+impl RequestInfo {
+    fn new<B>(request: &Request<B>) -> Self {
+        RequestInfo {
+            method: request.method().clone(),
+            endpoint: request.extensions().get::<Endpoint>().cloned(),
+            safe_params: request.extensions().get::<SafeParams>().cloned(),
+        }
+    }
+}
+
+fn error_status(error: &Error) -> Option<StatusCode> {
+    error
+        .safe_params()
+        .iter()
+        .find(|(key, _)| *key == "status")
+        .and_then(|(_, value)| value.clone().deserialize_into::<u16>().ok())
+        .and_then(|status| StatusCode::from_u16(status).ok())
+}
+
+impl<C, S> Client for LoggingClient<C, S>
+where
+    C: Client,
+    S: RequestLogSink,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let request = RequestInfo::new(&req);
+        let start = Instant::now();
+        let result = self.client.send(req);
+        self.log(request, start, &result);
+        result
+    }
+}
+
+#[async_trait]
+impl<C, S> AsyncClient for LoggingClient<C, S>
+where
+    C: AsyncClient + Sync + Send,
+    S: RequestLogSink + Sync + Send,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let request = RequestInfo::new(&req);
+        let start = Instant::now();
+        let result = self.client.send(req).await;
+        self.log(request, start, &result);
+        result
+    }
+}
@@ -0,0 +1,435 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::client::sleep::{self, Sleep};
+use crate::client::{self, AsyncClient, AsyncRequestBody, Client, Endpoint, RequestBody};
+use crate::{Deadline, SafeParams, TraceContext};
+use async_trait::async_trait;
+use conjure_error::{Error, ErrorKind};
+use http::{request, Extensions, Request, Response};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// The retry behavior applied to requests by [`RetryingClient`] and [`RetryingAsyncClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_num_retries: u32,
+    backoff_slot_size: Duration,
+    max_delay: Duration,
+    idempotent: Option<bool>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Returns a new policy with default settings.
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_num_retries: 4,
+            backoff_slot_size: Duration::from_millis(250),
+            max_delay: Duration::from_secs(30),
+            idempotent: None,
+        }
+    }
+
+    /// Returns a policy which never retries requests.
+    pub fn never() -> Self {
+        RetryPolicy::new().max_num_retries(0)
+    }
+
+    /// Sets the maximum number of times a request will be retried.
+    ///
+    /// Defaults to 4.
+    pub fn max_num_retries(mut self, max_num_retries: u32) -> Self {
+        self.max_num_retries = max_num_retries;
+        self
+    }
+
+    /// Sets the base delay used for exponential backoff between retries.
+    ///
+    /// Before the `n`th retry, the client sleeps for a random duration between 0 and
+    /// `backoff_slot_size * 2^n`, unless the server requested a specific delay in a throttle
+    /// response. Defaults to 250 milliseconds.
+    pub fn backoff_slot_size(mut self, backoff_slot_size: Duration) -> Self {
+        self.backoff_slot_size = backoff_slot_size;
+        self
+    }
+
+    /// Sets the maximum delay between attempts.
+    ///
+    /// Backoff delays are capped at this value. If the server requests a longer delay in a throttle
+    /// response, the request fails with the throttle error instead of being retried. Defaults to 30
+    /// seconds.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Overrides whether requests are considered idempotent, and are therefore safe to retry.
+    ///
+    /// By default, idempotency is determined by [`Endpoint::idempotent`], using the request's
+    /// method.
+    pub fn idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = Some(idempotent);
+        self
+    }
+
+    fn is_idempotent(&self, parts: &request::Parts) -> bool {
+        match self.idempotent {
+            Some(idempotent) => idempotent,
+            None => client::is_idempotent(parts),
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let scale = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        let max = self
+            .backoff_slot_size
+            .checked_mul(scale)
+            .unwrap_or(Duration::MAX)
+            .min(self.max_delay);
+        max.mul_f64(rand::random::<f64>())
+    }
+}
+
+/// A builder for [`RetryingClient`]s and [`RetryingAsyncClient`]s.
+#[derive(Clone, Default)]
+pub struct RetryingClientBuilder {
+    policy: RetryPolicy,
+    endpoint_policies: HashMap<(&'static str, &'static str), RetryPolicy>,
+}
+
+impl RetryingClientBuilder {
+    /// Returns a new builder with default settings.
+    pub fn new() -> Self {
+        RetryingClientBuilder::default()
+    }
+
+    /// Sets the policy used for requests without an endpoint-specific policy.
+    ///
+    /// Defaults to [`RetryPolicy::new`].
+    pub fn policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the policy used for requests to a specific endpoint.
+    ///
+    /// The endpoint is identified by the service and name of the [`Endpoint`] in the request's
+    /// extensions.
+    pub fn endpoint_policy(
+        mut self,
+        service: &'static str,
+        name: &'static str,
+        policy: RetryPolicy,
+    ) -> Self {
+        self.endpoint_policies.insert((service, name), policy);
+        self
+    }
+
+    /// Creates a new blocking client wrapping `client`.
+    pub fn build<C>(&self, client: C) -> RetryingClient<C>
+    where
+        C: Client,
+    {
+        RetryingClient {
+            client,
+            policies: Arc::new(self.policies()),
+        }
+    }
+
+    /// Creates a new async client wrapping `client`.
+    ///
+    /// The `sleep` function is used to wait between retries. It is typically the sleep function
+    /// of the async runtime the client is used with, such as `tokio::time::sleep`.
+    pub fn build_async<C, F, S>(&self, client: C, sleep: F) -> RetryingAsyncClient<C>
+    where
+        C: AsyncClient,
+        F: Fn(Duration) -> S + 'static + Sync + Send,
+        S: Future<Output = ()> + 'static + Send,
+    {
+        RetryingAsyncClient {
+            client,
+            policies: Arc::new(self.policies()),
+            sleep: sleep::boxed(sleep),
+        }
+    }
+
+    fn policies(&self) -> Policies {
+        Policies {
+            policy: self.policy.clone(),
+            endpoint_policies: self.endpoint_policies.clone(),
+        }
+    }
+}
+
+struct Policies {
+    policy: RetryPolicy,
+    endpoint_policies: HashMap<(&'static str, &'static str), RetryPolicy>,
+}
+
+impl Policies {
+    fn get(&self, extensions: &Extensions) -> &RetryPolicy {
+        extensions
+            .get::<Endpoint>()
+            .and_then(|e| self.endpoint_policies.get(&(e.service(), e.name())))
+            .unwrap_or(&self.policy)
+    }
+}
+
+/// Returns the delay before the next attempt if `error` is retryable, and `None` otherwise.
+///
+/// Requests are not retried if the delay is longer than the policy's maximum or would pass the
+/// request's [`Deadline`].
+fn retry_delay(
+    policy: &RetryPolicy,
+    parts: &request::Parts,
+    attempt: u32,
+    error: &Error,
+) -> Option<Duration> {
+    if attempt >= policy.max_num_retries || !policy.is_idempotent(parts) {
+        return None;
+    }
+
+    let delay = match error.kind() {
+        ErrorKind::Throttle(e) => e.duration().unwrap_or_else(|| policy.backoff(attempt)),
+        ErrorKind::Unavailable(_) => policy.backoff(attempt),
+        _ => return None,
+    };
+
+    if delay > policy.max_delay {
+        return None;
+    }
+
+    if let Some(deadline) = parts.extensions.get::<Deadline>() {
+        if delay >= deadline.remaining() {
+            return None;
+        }
+    }
+
+    Some(delay)
+}
+
+/// Copies the parts of a request for an attempt after the first.
+///
+/// Arbitrary extensions can't be cloned, so only the extensions defined by this crate are
+/// preserved.
+fn clone_parts(parts: &request::Parts) -> request::Parts {
+    let (mut new_parts, ()) = Request::builder()
+        .method(parts.method.clone())
+        .uri(parts.uri.clone())
+        .version(parts.version)
+        .body(())
+        .unwrap()
+        .into_parts();
+    new_parts.headers = parts.headers.clone();
+    copy_extension::<Endpoint>(parts, &mut new_parts);
+    copy_extension::<SafeParams>(parts, &mut new_parts);
+    copy_extension::<Deadline>(parts, &mut new_parts);
+    copy_extension::<TraceContext>(parts, &mut new_parts);
+    new_parts
+}
+
+fn copy_extension<T>(from: &request::Parts, to: &mut request::Parts)
+where
+    T: Clone + Send + Sync + 'static,
+{
+    if let Some(value) = from.extensions.get::<T>() {
+        to.extensions.insert(value.clone());
+    }
+}
+
+/// A blocking [`Client`] which retries requests that fail with throttle or unavailable errors.
+///
+/// Only idempotent requests are retried, and requests with a streaming body are only retried if
+/// the body can be [reset](crate::client::WriteBody::reset). The delay between attempts is
+/// determined by jittered exponential backoff, or by the duration requested by the server in a
+/// throttle error. Requests are not retried if that duration exceeds the policy's
+/// [maximum delay](RetryPolicy::max_delay) or would pass the request's [`Deadline`].
+///
+/// The first attempt of a request is passed all of the request's extensions, but later attempts
+/// are only passed its [`Endpoint`], [`SafeParams`], [`Deadline`], and [`TraceContext`].
+#[derive(Clone)]
+pub struct RetryingClient<C> {
+    client: C,
+    policies: Arc<Policies>,
+}
+
+impl<C> RetryingClient<C>
+where
+    C: Client,
+{
+    /// Creates a new client wrapping `client` with the default retry policy.
+    pub fn new(client: C) -> Self {
+        RetryingClientBuilder::new().build(client)
+    }
+
+    /// Returns a shared reference to the wrapped client.
+    pub fn get_ref(&self) -> &C {
+        &self.client
+    }
+}
+
+impl<C> Client for RetryingClient<C>
+where
+    C: Client,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, mut body) = req.into_parts();
+        let policy = self.policies.get(&parts.extensions);
+        let template = clone_parts(&parts);
+        let mut first_parts = Some(parts);
+
+        let mut attempt = 0;
+        loop {
+            let attempt_parts = first_parts.take().unwrap_or_else(|| clone_parts(&template));
+            let attempt_body = match &mut body {
+                RequestBody::Empty => RequestBody::Empty,
+                RequestBody::Fixed(bytes) => RequestBody::Fixed(bytes.clone()),
+                RequestBody::Streaming(writer) => RequestBody::Streaming(&mut **writer),
+            };
+
+            let error = match self
+                .client
+                .send(Request::from_parts(attempt_parts, attempt_body))
+            {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+
+            let delay = match retry_delay(policy, &template, attempt, &error) {
+                Some(delay) => delay,
+                None => return Err(error),
+            };
+
+            if let RequestBody::Streaming(writer) = &mut body {
+                if !writer.reset() {
+                    return Err(error);
+                }
+            }
+
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+}
+
+/// An [`AsyncClient`] which retries requests that fail with throttle or unavailable errors.
+///
+/// Its behavior is otherwise identical to [`RetryingClient`].
+pub struct RetryingAsyncClient<C> {
+    client: C,
+    policies: Arc<Policies>,
+    sleep: Sleep,
+}
+
+impl<C> Clone for RetryingAsyncClient<C>
+where
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        RetryingAsyncClient {
+            client: self.client.clone(),
+            policies: self.policies.clone(),
+            sleep: self.sleep.clone(),
+        }
+    }
+}
+
+impl<C> RetryingAsyncClient<C>
+where
+    C: AsyncClient,
+{
+    /// Creates a new client wrapping `client` with the default retry policy.
+    ///
+    /// The `sleep` function is used to wait between retries. It is typically the sleep function
+    /// of the async runtime the client is used with, such as `tokio::time::sleep`.
+    pub fn new<F, S>(client: C, sleep: F) -> Self
+    where
+        F: Fn(Duration) -> S + 'static + Sync + Send,
+        S: Future<Output = ()> + 'static + Send,
+    {
+        RetryingClientBuilder::new().build_async(client, sleep)
+    }
+
+    /// Returns a shared reference to the wrapped client.
+    pub fn get_ref(&self) -> &C {
+        &self.client
+    }
+}
+
+#[async_trait]
+impl<C> AsyncClient for RetryingAsyncClient<C>
+where
+    C: AsyncClient + Sync + Send,
+    C::BodyWriter: Send,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, mut body) = req.into_parts();
+        let policy = self.policies.get(&parts.extensions);
+        let template = clone_parts(&parts);
+        let mut first_parts = Some(parts);
+
+        let mut attempt = 0;
+        loop {
+            let attempt_parts = first_parts.take().unwrap_or_else(|| clone_parts(&template));
+            let attempt_body = match &mut body {
+                AsyncRequestBody::Empty => AsyncRequestBody::Empty,
+                AsyncRequestBody::Fixed(bytes) => AsyncRequestBody::Fixed(bytes.clone()),
+                AsyncRequestBody::Streaming(writer) => AsyncRequestBody::Streaming(writer.as_mut()),
+            };
+
+            let error = match self
+                .client
+                .send(Request::from_parts(attempt_parts, attempt_body))
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+
+            let delay = match retry_delay(policy, &template, attempt, &error) {
+                Some(delay) => delay,
+                None => return Err(error),
+            };
+
+            if let AsyncRequestBody::Streaming(writer) = &mut body {
+                if !writer.as_mut().reset().await {
+                    return Err(error);
+                }
+            }
+
+            (self.sleep)(delay).await;
+            attempt += 1;
+        }
+    }
+}
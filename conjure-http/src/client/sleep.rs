@@ -0,0 +1,29 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use futures_util::future::BoxFuture;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A type-erased async sleep function, used by async clients which need to wait for a duration.
+pub(crate) type Sleep = Arc<dyn Fn(Duration) -> BoxFuture<'static, ()> + Sync + Send>;
+
+/// Wraps a runtime's sleep function, such as `tokio::time::sleep`, into a [`Sleep`].
+pub(crate) fn boxed<F, S>(sleep: F) -> Sleep
+where
+    F: Fn(Duration) -> S + 'static + Sync + Send,
+    S: Future<Output = ()> + 'static + Send,
+{
+    Arc::new(move |duration| Box::pin(sleep(duration)))
+}
@@ -0,0 +1,107 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::client::{AsyncClient, AsyncRequestBody, Client, RequestBody};
+use crate::trace::{TraceContext, TraceContextSource, TracePropagator};
+use async_trait::async_trait;
+use conjure_error::Error;
+use http::{Request, Response};
+
+/// A client wrapper which propagates a [`TraceContext`] with every request.
+///
+/// If the request's extensions already contain a `TraceContext`, it is used. Otherwise, the
+/// context is taken from a [`TraceContextSource`] and inserted into the request's extensions. The
+/// context is then injected into the request's headers with a [`TracePropagator`]. Requests are
+/// sent unchanged if there is no context.
+#[derive(Clone)]
+pub struct TracingClient<C, P, S> {
+    client: C,
+    propagator: P,
+    source: S,
+}
+
+impl<C, P, S> TracingClient<C, P, S> {
+    /// Creates a new `TracingClient` which injects contexts from `source` into requests made by
+    /// `client` using `propagator`.
+    pub fn new(client: C, propagator: P, source: S) -> Self {
+        TracingClient {
+            client,
+            propagator,
+            source,
+        }
+    }
+
+    /// Returns a shared reference to the inner client.
+    pub fn get_ref(&self) -> &C {
+        &self.client
+    }
+}
+
+impl<C, P, S> TracingClient<C, P, S>
+where
+    P: TracePropagator,
+    S: TraceContextSource,
+{
+    fn inject<B>(&self, req: &mut Request<B>) {
+        let context = match req.extensions().get::<TraceContext>() {
+            Some(context) => context.clone(),
+            None => match self.source.current() {
+                Some(context) => {
+                    req.extensions_mut().insert(context.clone());
+                    context
+                }
+                None => return,
+            },
+        };
+
+        self.propagator.inject(&context, req.headers_mut());
+    }
+}
+
+impl<C, P, S> Client for TracingClient<C, P, S>
+where
+    C: Client,
+    P: TracePropagator,
+    S: TraceContextSource,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    fn send(
+        &self,
+        mut req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        self.inject(&mut req);
+        self.client.send(req)
+    }
+}
+
+#[async_trait]
+impl<C, P, S> AsyncClient for TracingClient<C, P, S>
+where
+    C: AsyncClient + Sync + Send,
+    C::BodyWriter: Send,
+    P: TracePropagator + Sync + Send,
+    S: TraceContextSource + Sync + Send,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    async fn send(
+        &self,
+        mut req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        self.inject(&mut req);
+        self.client.send(req).await
+    }
+}
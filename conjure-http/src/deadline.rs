@@ -0,0 +1,125 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Request deadlines.
+use conjure_error::{Error, Timeout};
+use futures_util::future::{self, Either};
+use http::{HeaderMap, HeaderValue};
+use std::convert::TryFrom;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// The header used to propagate a request's deadline.
+///
+/// Its value is the number of milliseconds remaining until the deadline when the request was sent.
+/// A relative value is used rather than a timestamp so that deadlines are not affected by clock
+/// skew between the client and server.
+pub const DEADLINE_HEADER: &str = "deadline-remaining-millis";
+
+/// The point in time by which a request must complete.
+///
+/// Deadlines are carried in the extensions of requests. [`DeadlineClient`] and
+/// [`DeadlineAsyncClient`] add them to outgoing requests and propagate them to the server in the
+/// [`DEADLINE_HEADER`] header, and [`DeadlineLayer`] parses them from incoming requests, after
+/// which they are available from [`RequestContext::deadline`].
+///
+/// Requests which are still in progress after their deadline fail with a [`Timeout`] error.
+///
+/// [`DeadlineClient`]: crate::client::DeadlineClient
+/// [`DeadlineAsyncClient`]: crate::client::DeadlineAsyncClient
+/// [`DeadlineLayer`]: crate::server::DeadlineLayer
+/// [`RequestContext::deadline`]: crate::server::RequestContext::deadline
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// Creates a deadline at the specified instant.
+    #[inline]
+    pub fn new(instant: Instant) -> Self {
+        Deadline(instant)
+    }
+
+    /// Creates a deadline the specified duration from now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the deadline can't be represented as an [`Instant`].
+    #[inline]
+    pub fn after(timeout: Duration) -> Self {
+        Deadline(Instant::now() + timeout)
+    }
+
+    /// Returns the instant of the deadline.
+    #[inline]
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Returns the time remaining until the deadline, or zero if it has passed.
+    #[inline]
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    /// Determines if the deadline has passed.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.0 <= Instant::now()
+    }
+
+    /// Returns a [`Timeout`] error if the deadline has passed.
+    pub fn check(&self) -> Result<(), Error> {
+        if self.is_expired() {
+            return Err(timeout_error());
+        }
+
+        Ok(())
+    }
+
+    /// Runs a future to completion, failing with a [`Timeout`] error if the deadline passes first.
+    ///
+    /// The `sleep` future should complete when the deadline passes. It is typically created with
+    /// the sleep function of the async runtime in use, such as
+    /// `tokio::time::sleep(deadline.remaining())`. The future is not polled at all if the deadline
+    /// has already passed.
+    pub async fn enforce<F, S, T>(&self, sleep: S, future: F) -> Result<T, Error>
+    where
+        F: Future<Output = Result<T, Error>>,
+        S: Future<Output = ()>,
+    {
+        self.check()?;
+
+        pin_utils::pin_mut!(future);
+        pin_utils::pin_mut!(sleep);
+        match future::select(future, sleep).await {
+            Either::Left((result, _)) => result,
+            Either::Right(((), _)) => Err(timeout_error()),
+        }
+    }
+
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let millis = headers.get(DEADLINE_HEADER)?.to_str().ok()?.parse().ok()?;
+        Instant::now()
+            .checked_add(Duration::from_millis(millis))
+            .map(Deadline)
+    }
+
+    pub(crate) fn to_header_value(self) -> HeaderValue {
+        let millis = u64::try_from(self.remaining().as_millis()).unwrap_or(u64::MAX);
+        HeaderValue::from(millis)
+    }
+}
+
+fn timeout_error() -> Error {
+    Error::service_safe("request deadline expired", Timeout::new())
+}
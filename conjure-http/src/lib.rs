@@ -21,6 +21,16 @@
 //!
 //! If the `macros` Cargo feature is enabled, the `conjure_client` macro can be used to create
 //! client implementations for non-Conjure APIs.
+//!
+//! # Tower
+//!
+//! If the `tower` Cargo feature is enabled, the `server::tower` module can be used to serve
+//! Conjure services as a [tower](https://docs.rs/tower) `Service`.
+//!
+//! # Testing
+//!
+//! If the `testing` Cargo feature is enabled, the `testing` module provides in-memory clients
+//! which dispatch requests directly to Conjure services without an HTTP server.
 #![warn(missing_docs, clippy::all)]
 // https://github.com/rust-lang/rust-clippy/issues/7752
 #![allow(
@@ -32,15 +42,26 @@
 #[doc(inline)]
 pub use conjure_macros::{conjure_client, conjure_endpoints, endpoint};
 
+#[doc(inline)]
+pub use crate::deadline::Deadline;
 #[doc(inline)]
 pub use crate::path_params::PathParams;
 #[doc(inline)]
+pub use crate::request_log::{RequestLog, RequestLogSink};
+#[doc(inline)]
 pub use crate::safe_params::SafeParams;
+#[doc(inline)]
+pub use crate::trace::TraceContext;
 
 pub mod client;
+pub mod deadline;
 pub mod path_params;
+pub mod request_log;
 pub mod safe_params;
 pub mod server;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod trace;
 
 #[doc(hidden)]
 pub mod private;
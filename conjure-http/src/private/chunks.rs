@@ -0,0 +1,175 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A single-slot channel passing chunks of a body from a blocking writer to an async reader.
+use conjure_error::Error;
+use futures_util::future;
+use futures_util::task::AtomicWaker;
+use std::io::{self, Write};
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll};
+
+pub(crate) fn channel(chunk_size: usize) -> (ChunkWriter, ChunkReceiver) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            chunk: None,
+            sender: SenderState::Open,
+            receiver_closed: false,
+        }),
+        taken: Condvar::new(),
+        waker: AtomicWaker::new(),
+    });
+
+    let writer = ChunkWriter {
+        shared: shared.clone(),
+        buf: Vec::with_capacity(chunk_size),
+        chunk_size,
+    };
+    let receiver = ChunkReceiver { shared };
+
+    (writer, receiver)
+}
+
+struct Shared {
+    state: Mutex<State>,
+    taken: Condvar,
+    waker: AtomicWaker,
+}
+
+struct State {
+    chunk: Option<Vec<u8>>,
+    sender: SenderState,
+    receiver_closed: bool,
+}
+
+enum SenderState {
+    Open,
+    Finished(Result<(), Error>),
+    Done,
+}
+
+/// A writer which buffers up to `chunk_size` bytes before passing them to the receiver.
+///
+/// Writes block while the receiver still has a previous chunk pending, so at most two chunks are
+/// held in memory at a time.
+pub(crate) struct ChunkWriter {
+    shared: Arc<Shared>,
+    buf: Vec<u8>,
+    chunk_size: usize,
+}
+
+impl ChunkWriter {
+    /// Sends any remaining data followed by the result of producing the body.
+    pub(crate) fn finish(mut self, result: Result<(), Error>) {
+        let result = result.and_then(|()| self.send().map_err(Error::internal_safe));
+
+        let mut state = self.shared.state.lock().unwrap();
+        state.sender = SenderState::Finished(result);
+        drop(state);
+        self.shared.waker.wake();
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let mut state = self.shared.state.lock().unwrap();
+        while state.chunk.is_some() && !state.receiver_closed {
+            state = self.shared.taken.wait(state).unwrap();
+        }
+        if state.receiver_closed {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "body receiver closed",
+            ));
+        }
+        state.chunk = Some(mem::replace(
+            &mut self.buf,
+            Vec::with_capacity(self.chunk_size),
+        ));
+        drop(state);
+        self.shared.waker.wake();
+
+        Ok(())
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = usize::min(buf.len(), self.chunk_size - self.buf.len());
+        self.buf.extend_from_slice(&buf[..len]);
+        if self.buf.len() == self.chunk_size {
+            self.send()?;
+        }
+
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for ChunkWriter {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        if let SenderState::Open = state.sender {
+            // the writer was dropped without being finished, most likely due to a panic
+            state.sender = SenderState::Finished(Err(Error::internal_safe(
+                "body writer exited before completing the body",
+            )));
+        }
+        drop(state);
+        self.shared.waker.wake();
+    }
+}
+
+pub(crate) struct ChunkReceiver {
+    shared: Arc<Shared>,
+}
+
+impl ChunkReceiver {
+    /// Returns the next chunk of the body, or `None` once the body has been completed.
+    pub(crate) async fn recv(&mut self) -> Option<Result<Vec<u8>, Error>> {
+        future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Vec<u8>, Error>>> {
+        self.shared.waker.register(cx.waker());
+
+        let mut state = self.shared.state.lock().unwrap();
+        if let Some(chunk) = state.chunk.take() {
+            self.shared.taken.notify_one();
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+
+        match mem::replace(&mut state.sender, SenderState::Done) {
+            SenderState::Open => {
+                state.sender = SenderState::Open;
+                Poll::Pending
+            }
+            SenderState::Finished(Ok(())) | SenderState::Done => Poll::Ready(None),
+            SenderState::Finished(Err(e)) => Poll::Ready(Some(Err(e))),
+        }
+    }
+}
+
+impl Drop for ChunkReceiver {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().receiver_closed = true;
+        self.shared.taken.notify_one();
+    }
+}
@@ -13,7 +13,9 @@
 // limitations under the License.
 use crate::client::{AsyncRequestBody, AsyncWriteBody, RequestBody, WriteBody};
 pub use crate::private::client::uri_builder::UriBuilder;
-use crate::private::{async_read_body, read_body, APPLICATION_JSON, APPLICATION_OCTET_STREAM};
+use crate::private::{
+    async_read_body, media_type, read_body, Encoding, APPLICATION_JSON, APPLICATION_OCTET_STREAM,
+};
 use bytes::Bytes;
 use conjure_error::Error;
 use conjure_object::{BearerToken, Plain, ToPlain};
@@ -104,6 +106,14 @@ pub fn encode_serializable_response_headers<B>(request: &mut Request<B>) {
     request.headers_mut().insert(ACCEPT, APPLICATION_JSON);
 }
 
+pub fn encode_smile_serializable_response_headers<B>(request: &mut Request<B>) {
+    // servers which don't support Smile will fall back to JSON
+    request.headers_mut().insert(
+        ACCEPT,
+        HeaderValue::from_static("application/x-jackson-smile, application/json;q=0.9"),
+    );
+}
+
 pub fn encode_binary_response_headers<B>(request: &mut Request<B>) {
     request
         .headers_mut()
@@ -165,7 +175,7 @@ where
     }
 
     // Servers can send a JSON response to an endpoint we expect to be void. Rather than just ignoring the response
-    // body, we're going to "deserialize" it to IgnoredAny to validate that it is in fact a valid JSON body and to
+    // body, we're going to "deserialize" it to IgnoredAny to validate that it is in fact a valid body and to
     // consume the response body data so the socket can be reused for another request.
     decode_serializable_response::<IgnoredAny, _>(response)?;
 
@@ -216,14 +226,10 @@ where
     T: DeserializeOwned,
     I: Iterator<Item = Result<Bytes, Error>>,
 {
-    if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-        return Err(Error::internal_safe("invalid response Content-Type"));
-    }
-
+    let encoding = response_encoding(&response)?;
     let body = read_body(response.into_body(), None)?;
-    let body = json::client_from_slice(&body).map_err(Error::internal)?;
 
-    Ok(body)
+    encoding.client_from_slice(&body)
 }
 
 pub async fn async_decode_serializable_response<T, I>(response: Response<I>) -> Result<T, Error>
@@ -231,14 +237,15 @@ where
     T: DeserializeOwned,
     I: Stream<Item = Result<Bytes, Error>>,
 {
-    if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-        return Err(Error::internal("invalid response Content-Type"));
-    }
-
+    let encoding = response_encoding(&response)?;
     let body = async_read_body(response.into_body(), None).await?;
-    let body = json::client_from_slice(&body).map_err(Error::internal)?;
 
-    Ok(body)
+    encoding.client_from_slice(&body)
+}
+
+fn response_encoding<I>(response: &Response<I>) -> Result<Encoding, Error> {
+    Encoding::from_content_type(response.headers())
+        .ok_or_else(|| Error::internal_safe("invalid response Content-Type"))
 }
 
 pub fn decode_optional_binary_response<I>(response: Response<I>) -> Result<Option<I>, Error> {
@@ -250,7 +257,7 @@ pub fn decode_optional_binary_response<I>(response: Response<I>) -> Result<Optio
 }
 
 pub fn decode_binary_response<I>(response: Response<I>) -> Result<I, Error> {
-    if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_OCTET_STREAM) {
+    if !media_type::is_content_type(response.headers(), "application", "octet-stream") {
         return Err(Error::internal_safe("invalid response Content-Type"));
     }
 
@@ -0,0 +1,169 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::private::media_type::{self, MediaType};
+use crate::private::{APPLICATION_JSON, APPLICATION_SMILE};
+use conjure_error::{Error, InvalidArgument};
+use conjure_serde::{json, smile};
+use http::header::{ACCEPT, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Write;
+
+/// The formats supported for serializable Conjure bodies.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Json,
+    Smile,
+}
+
+impl Encoding {
+    const ALL: [Encoding; 2] = [Encoding::Json, Encoding::Smile];
+
+    /// Determines the encoding of a body from its `Content-Type` header.
+    ///
+    /// Returns `None` if the header is missing, malformed, or names an unsupported media type.
+    pub(crate) fn from_content_type(headers: &HeaderMap) -> Option<Encoding> {
+        let media_type = MediaType::from_header(headers.get(CONTENT_TYPE)?)?;
+        Encoding::ALL
+            .iter()
+            .copied()
+            .find(|e| e.supports(&media_type))
+    }
+
+    /// Selects the encoding of a response body from the request's `Accept` header.
+    ///
+    /// The encoding with the highest quality value wins, with ties broken in favor of the one listed
+    /// first. JSON is used if the header is missing, and `None` is returned if it excludes both
+    /// supported types.
+    pub(crate) fn from_accept(headers: &HeaderMap) -> Option<Encoding> {
+        let ranges = media_type::parse_accept(headers.get_all(ACCEPT)).collect::<Vec<_>>();
+        if ranges.is_empty() {
+            return Some(Encoding::Json);
+        }
+
+        let mut best = None;
+        for encoding in Encoding::ALL {
+            let (quality, index) = match encoding.accept_quality(&ranges) {
+                Some(v) => v,
+                None => continue,
+            };
+            if quality <= 0. {
+                continue;
+            }
+            match best {
+                Some((_, best_quality, best_index))
+                    if best_quality > quality
+                        || (best_quality == quality && best_index <= index) => {}
+                _ => best = Some((encoding, quality, index)),
+            }
+        }
+
+        best.map(|(encoding, _, _)| encoding)
+    }
+
+    fn essence(self) -> (&'static str, &'static str) {
+        match self {
+            Encoding::Json => ("application", "json"),
+            Encoding::Smile => ("application", "x-jackson-smile"),
+        }
+    }
+
+    fn supports(self, media_type: &MediaType<'_>) -> bool {
+        let (type_, subtype) = self.essence();
+        if !media_type.is(type_, subtype) {
+            return false;
+        }
+
+        // JSON must be UTF-8 encoded (RFC 8259)
+        match (self, media_type.param("charset")) {
+            (Encoding::Json, Some(charset)) => charset.eq_ignore_ascii_case("utf-8"),
+            _ => true,
+        }
+    }
+
+    // Returns the quality and position of the most specific media range matching this encoding.
+    fn accept_quality(self, ranges: &[MediaType<'_>]) -> Option<(f32, usize)> {
+        let (type_, subtype) = self.essence();
+        let mut best: Option<(u8, f32, usize)> = None;
+        for (index, range) in ranges.iter().enumerate() {
+            let specificity = match range.range_specificity(type_, subtype) {
+                Some(specificity) => specificity,
+                None => continue,
+            };
+            let quality = match range.quality() {
+                Some(quality) => quality,
+                None => continue,
+            };
+            match best {
+                Some((best_specificity, _, _)) if best_specificity >= specificity => {}
+                _ => best = Some((specificity, quality, index)),
+            }
+        }
+
+        best.map(|(_, quality, index)| (quality, index))
+    }
+
+    pub(crate) fn content_type(self) -> HeaderValue {
+        match self {
+            Encoding::Json => APPLICATION_JSON,
+            Encoding::Smile => APPLICATION_SMILE,
+        }
+    }
+
+    pub(crate) fn to_vec<T>(self, value: &T) -> Result<Vec<u8>, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self {
+            Encoding::Json => json::to_vec(value).map_err(Error::internal),
+            Encoding::Smile => smile::to_vec(value).map_err(Error::internal),
+        }
+    }
+
+    pub(crate) fn to_writer<W, T>(self, writer: W, value: &T) -> Result<(), Error>
+    where
+        W: Write,
+        T: ?Sized + Serialize,
+    {
+        match self {
+            Encoding::Json => json::to_writer(writer, value).map_err(Error::internal),
+            Encoding::Smile => smile::to_writer(writer, value).map_err(Error::internal),
+        }
+    }
+
+    pub(crate) fn server_from_slice<T>(self, buf: &[u8]) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        match self {
+            Encoding::Json => {
+                json::server_from_slice(buf).map_err(|e| Error::service(e, InvalidArgument::new()))
+            }
+            Encoding::Smile => {
+                smile::server_from_slice(buf).map_err(|e| Error::service(e, InvalidArgument::new()))
+            }
+        }
+    }
+
+    pub(crate) fn client_from_slice<T>(self, buf: &[u8]) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        match self {
+            Encoding::Json => json::client_from_slice(buf).map_err(Error::internal),
+            Encoding::Smile => smile::client_from_slice(buf).map_err(Error::internal),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, HeaderValue};
+use std::borrow::Cow;
+
+/// A parsed media type or `Accept` media range, as defined in RFC 9110.
+#[derive(Debug, PartialEq)]
+pub(crate) struct MediaType<'a> {
+    type_: &'a str,
+    subtype: &'a str,
+    params: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> MediaType<'a> {
+    /// Parses a single media type like `application/json; charset=utf-8`.
+    pub(crate) fn parse(s: &'a str) -> Option<MediaType<'a>> {
+        let mut parts = split_unquoted(s, ';');
+
+        let essence = parts.next()?.trim();
+        let (type_, subtype) = essence.split_once('/')?;
+        if !is_token(type_) || !is_token(subtype) {
+            return None;
+        }
+
+        let params = parts
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| {
+                let (name, value) = p.split_once('=')?;
+                let name = name.trim();
+                if !is_token(name) {
+                    return None;
+                }
+                Some((name, unquote(value.trim())?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(MediaType {
+            type_,
+            subtype,
+            params,
+        })
+    }
+
+    /// Parses a media type from a header value.
+    pub(crate) fn from_header(value: &'a HeaderValue) -> Option<MediaType<'a>> {
+        value.to_str().ok().and_then(MediaType::parse)
+    }
+
+    /// Returns the value of a parameter, looked up case-insensitively.
+    pub(crate) fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| &**v)
+    }
+
+    /// Determines if this is exactly the specified type, ignoring parameters.
+    pub(crate) fn is(&self, type_: &str, subtype: &str) -> bool {
+        self.type_.eq_ignore_ascii_case(type_) && self.subtype.eq_ignore_ascii_case(subtype)
+    }
+
+    /// If this is a media range matching the specified type, returns the specificity of the match.
+    ///
+    /// Exact matches are more specific than `type/*`, which is more specific than `*/*`.
+    pub(crate) fn range_specificity(&self, type_: &str, subtype: &str) -> Option<u8> {
+        if self.type_ == "*" && self.subtype == "*" {
+            Some(0)
+        } else if self.type_.eq_ignore_ascii_case(type_) && self.subtype == "*" {
+            Some(1)
+        } else if self.is(type_, subtype) {
+            Some(2)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the media range's quality value, defaulting to 1.
+    ///
+    /// Returns `None` if the `q` parameter is malformed.
+    pub(crate) fn quality(&self) -> Option<f32> {
+        match self.param("q") {
+            Some(q) => q.parse().ok().filter(|q| (0. ..=1.).contains(q)),
+            None => Some(1.),
+        }
+    }
+}
+
+/// Parses the media ranges of an `Accept` header.
+///
+/// Malformed ranges are skipped.
+pub(crate) fn parse_accept<'a, I>(values: I) -> impl Iterator<Item = MediaType<'a>>
+where
+    I: IntoIterator<Item = &'a HeaderValue>,
+{
+    values
+        .into_iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| split_unquoted(v, ','))
+        .filter(|v| !v.trim().is_empty())
+        .filter_map(MediaType::parse)
+}
+
+/// Determines if the `Content-Type` header is present and names the specified type.
+pub(crate) fn is_content_type(headers: &HeaderMap, type_: &str, subtype: &str) -> bool {
+    match headers.get(CONTENT_TYPE).and_then(MediaType::from_header) {
+        Some(media_type) => media_type.is(type_, subtype),
+        None => false,
+    }
+}
+
+fn split_unquoted(s: &str, delim: char) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    let mut escaped = false;
+    s.split(move |c| {
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if !quoted && c == delim {
+            return true;
+        }
+        false
+    })
+}
+
+fn unquote(s: &str) -> Option<Cow<'_, str>> {
+    let s = match s.strip_prefix('"') {
+        Some(s) => s.strip_suffix('"')?,
+        None if is_token(s) => return Some(Cow::Borrowed(s)),
+        None => return None,
+    };
+
+    if !s.contains('\\') {
+        return Some(Cow::Borrowed(s));
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next()?),
+            c => out.push(c),
+        }
+    }
+    Some(Cow::Owned(out))
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
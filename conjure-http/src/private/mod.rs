@@ -19,11 +19,13 @@ pub use bytes::Bytes;
 pub use conjure_error::Error;
 pub use conjure_serde::json;
 pub use futures_core::Stream;
+pub use futures_io::AsyncWrite;
 pub use http::{self, header, Extensions, Method, Request, Response};
 pub use pin_utils::pin_mut;
 pub use std::borrow::Cow;
 pub use std::boxed::Box;
 pub use std::future::Future;
+pub use std::io::Write;
 pub use std::iter::Iterator;
 pub use std::option::Option;
 pub use std::pin::Pin;
@@ -32,14 +34,21 @@ pub use std::sync::Arc;
 pub use std::vec::Vec;
 
 use bytes::BytesMut;
-use conjure_error::InvalidArgument;
+use conjure_error::RequestEntityTooLarge;
 use futures_util::TryStreamExt;
 use http::HeaderValue;
 
+pub(crate) use self::encoding::Encoding;
+
+mod chunks;
 mod client;
+mod encoding;
+mod media_type;
 mod server;
 
 pub(crate) const APPLICATION_JSON: HeaderValue = HeaderValue::from_static("application/json");
+pub(crate) const APPLICATION_SMILE: HeaderValue =
+    HeaderValue::from_static("application/x-jackson-smile");
 const APPLICATION_OCTET_STREAM: HeaderValue = HeaderValue::from_static("application/octet-stream");
 
 // slightly nontrivial to avoid a copy for single-chunk bodies
@@ -110,10 +119,10 @@ fn check_limit(buf: &[u8], limit: Option<usize>) -> Result<(), Error> {
     };
 
     if buf.len() > limit {
-        return Err(Error::service_safe(
-            "body too large",
-            InvalidArgument::new(),
-        ));
+        return Err(
+            Error::service_safe("body too large", RequestEntityTooLarge::new())
+                .with_safe_param("limit", limit),
+        );
     }
 
     Ok(())
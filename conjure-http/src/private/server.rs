@@ -1,23 +1,31 @@
-use crate::private::{async_read_body, read_body, APPLICATION_JSON, APPLICATION_OCTET_STREAM};
+use crate::private::{
+    async_read_body, chunks, media_type, read_body, Encoding, APPLICATION_OCTET_STREAM,
+};
 use crate::server::{
-    AsyncResponseBody, AsyncWriteBody, DecodeHeader, DecodeParam, ResponseBody, WriteBody,
+    AsyncResponseBody, AsyncWriteBody, DecodeHeader, DecodeParam, NotAcceptable, ResponseBody,
+    UnsupportedMediaType, WriteBody,
 };
 use crate::PathParams;
+use async_trait::async_trait;
 use bytes::Bytes;
 use conjure_error::{Error, InvalidArgument, PermissionDenied};
 use conjure_object::{BearerToken, FromPlain};
-use conjure_serde::json;
 use futures_core::Stream;
+use futures_io::AsyncWrite;
+use futures_util::AsyncWriteExt;
 use http::header::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE};
 use http::request;
-use http::{Response, StatusCode};
+use http::{HeaderMap, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::borrow::Cow;
 use std::collections::{BTreeSet, HashMap};
 use std::error;
+use std::io::{self, BufWriter, Write};
+use std::pin::Pin;
+use std::thread;
 
-pub const SERIALIZABLE_REQUEST_SIZE_LIMIT: usize = 50 * 1024 * 1024;
+pub use crate::server::DEFAULT_REQUEST_SIZE_LIMIT as SERIALIZABLE_REQUEST_SIZE_LIMIT;
 
 pub fn parse_path_param<T>(parts: &request::Parts, param: &str) -> Result<T, Error>
 where
@@ -282,17 +290,31 @@ pub fn decode_empty_request<I>(_parts: &request::Parts, _body: I) -> Result<(),
     Ok(())
 }
 
+// FIXME remove in favor of decode_serializable_request2
 pub fn decode_serializable_request<I, T>(parts: &request::Parts, body: I) -> Result<T, Error>
 where
     I: Iterator<Item = Result<Bytes, Error>>,
     T: DeserializeOwned,
 {
-    check_deserializable_request_headers(parts)?;
-    let body = read_body(body, Some(SERIALIZABLE_REQUEST_SIZE_LIMIT))?;
+    decode_serializable_request2(parts, body, SERIALIZABLE_REQUEST_SIZE_LIMIT)
+}
 
-    json::server_from_slice(&body).map_err(|e| Error::service(e, InvalidArgument::new()))
+pub fn decode_serializable_request2<I, T>(
+    parts: &request::Parts,
+    body: I,
+    limit: usize,
+) -> Result<T, Error>
+where
+    I: Iterator<Item = Result<Bytes, Error>>,
+    T: DeserializeOwned,
+{
+    let encoding = check_deserializable_request_headers(parts)?;
+    let body = read_body(body, Some(limit))?;
+
+    encoding.server_from_slice(&body)
 }
 
+// FIXME remove in favor of async_decode_serializable_request2
 pub async fn async_decode_serializable_request<I, T>(
     parts: &request::Parts,
     body: I,
@@ -301,27 +323,57 @@ where
     I: Stream<Item = Result<Bytes, Error>>,
     T: DeserializeOwned,
 {
-    check_deserializable_request_headers(parts)?;
-    let body = async_read_body(body, Some(SERIALIZABLE_REQUEST_SIZE_LIMIT)).await?;
+    async_decode_serializable_request2(parts, body, SERIALIZABLE_REQUEST_SIZE_LIMIT).await
+}
 
-    json::server_from_slice(&body).map_err(|e| Error::service(e, InvalidArgument::new()))
+pub async fn async_decode_serializable_request2<I, T>(
+    parts: &request::Parts,
+    body: I,
+    limit: usize,
+) -> Result<T, Error>
+where
+    I: Stream<Item = Result<Bytes, Error>>,
+    T: DeserializeOwned,
+{
+    let encoding = check_deserializable_request_headers(parts)?;
+    let body = async_read_body(body, Some(limit)).await?;
+
+    encoding.server_from_slice(&body)
 }
 
-fn check_deserializable_request_headers(parts: &request::Parts) -> Result<(), Error> {
-    if parts.headers.get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-        return Err(Error::service_safe(
-            "unexpected Content-Type",
-            InvalidArgument::new(),
-        ));
-    }
+fn check_deserializable_request_headers(parts: &request::Parts) -> Result<Encoding, Error> {
+    Encoding::from_content_type(&parts.headers)
+        .ok_or_else(|| unsupported_media_type(&parts.headers))
+}
 
-    Ok(())
+pub(crate) fn unsupported_media_type(headers: &HeaderMap) -> Error {
+    let error = Error::service_safe("unexpected Content-Type", UnsupportedMediaType::new());
+    match headers.get(CONTENT_TYPE) {
+        Some(content_type) => error.with_unsafe_param(
+            "contentType",
+            String::from_utf8_lossy(content_type.as_bytes()),
+        ),
+        None => error,
+    }
 }
 
+// FIXME remove in favor of decode_optional_serializable_request2
 pub fn decode_optional_serializable_request<I, T>(
     parts: &request::Parts,
     body: I,
 ) -> Result<T, Error>
+where
+    I: Iterator<Item = Result<Bytes, Error>>,
+    T: DeserializeOwned + Default,
+{
+    decode_optional_serializable_request2(parts, body, SERIALIZABLE_REQUEST_SIZE_LIMIT)
+}
+
+pub fn decode_optional_serializable_request2<I, T>(
+    parts: &request::Parts,
+    body: I,
+    limit: usize,
+) -> Result<T, Error>
 where
     I: Iterator<Item = Result<Bytes, Error>>,
     T: DeserializeOwned + Default,
@@ -330,13 +382,26 @@ where
         return Ok(T::default());
     }
 
-    decode_serializable_request(parts, body)
+    decode_serializable_request2(parts, body, limit)
 }
 
+// FIXME remove in favor of async_decode_optional_serializable_request2
 pub async fn async_decode_optional_serializable_request<I, T>(
     parts: &request::Parts,
     body: I,
 ) -> Result<T, Error>
+where
+    I: Stream<Item = Result<Bytes, Error>>,
+    T: DeserializeOwned + Default,
+{
+    async_decode_optional_serializable_request2(parts, body, SERIALIZABLE_REQUEST_SIZE_LIMIT).await
+}
+
+pub async fn async_decode_optional_serializable_request2<I, T>(
+    parts: &request::Parts,
+    body: I,
+    limit: usize,
+) -> Result<T, Error>
 where
     I: Stream<Item = Result<Bytes, Error>>,
     T: DeserializeOwned + Default,
@@ -345,15 +410,12 @@ where
         return Ok(T::default());
     }
 
-    async_decode_serializable_request(parts, body).await
+    async_decode_serializable_request2(parts, body, limit).await
 }
 
 pub fn decode_binary_request<I>(parts: &request::Parts, body: I) -> Result<I, Error> {
-    if parts.headers.get(CONTENT_TYPE) != Some(&APPLICATION_OCTET_STREAM) {
-        return Err(Error::service_safe(
-            "unexpected Content-Type",
-            InvalidArgument::new(),
-        ));
+    if !media_type::is_content_type(&parts.headers, "application", "octet-stream") {
+        return Err(unsupported_media_type(&parts.headers));
     }
 
     Ok(body)
@@ -374,32 +436,81 @@ fn inner_encode_empty_response<B>(body: B) -> Response<B> {
     response
 }
 
+// FIXME remove in favor of the content negotiating encode_serializable_response2
 pub fn encode_serializable_response<T, O>(value: &T) -> Response<ResponseBody<O>>
 where
     T: Serialize,
 {
-    inner_encode_serializable_response(value, ResponseBody::Fixed)
+    json_serializable_response(value, ResponseBody::Fixed)
 }
 
+pub fn encode_serializable_response2<T, O>(
+    parts: &request::Parts,
+    value: &T,
+) -> Result<Response<ResponseBody<O>>, Error>
+where
+    T: Serialize,
+{
+    inner_encode_serializable_response(parts, value, ResponseBody::Fixed)
+}
+
+// FIXME remove in favor of the content negotiating async_encode_serializable_response2
 pub fn async_encode_serializable_response<T, O>(value: &T) -> Response<AsyncResponseBody<O>>
 where
     T: Serialize,
 {
-    inner_encode_serializable_response(value, AsyncResponseBody::Fixed)
+    json_serializable_response(value, AsyncResponseBody::Fixed)
 }
 
-fn inner_encode_serializable_response<T, B, F>(value: &T, make_body: F) -> Response<B>
+pub fn async_encode_serializable_response2<T, O>(
+    parts: &request::Parts,
+    value: &T,
+) -> Result<Response<AsyncResponseBody<O>>, Error>
+where
+    T: Serialize,
+{
+    inner_encode_serializable_response(parts, value, AsyncResponseBody::Fixed)
+}
+
+fn json_serializable_response<T, B, F>(value: &T, make_body: F) -> Response<B>
 where
     T: Serialize,
     F: FnOnce(Bytes) -> B,
 {
-    let body = json::to_vec(value).expect("Conjure types can serialize to JSON");
+    let body = Encoding::Json
+        .to_vec(value)
+        .expect("Conjure types can serialize to JSON");
+
+    fixed_serializable_response(Encoding::Json, body, make_body)
+}
+
+fn inner_encode_serializable_response<T, B, F>(
+    parts: &request::Parts,
+    value: &T,
+    make_body: F,
+) -> Result<Response<B>, Error>
+where
+    T: Serialize,
+    F: FnOnce(Bytes) -> B,
+{
+    let encoding = Encoding::from_accept(&parts.headers).ok_or_else(not_acceptable)?;
+    let body = encoding
+        .to_vec(value)
+        .expect("Conjure types can serialize to JSON and Smile");
+
+    Ok(fixed_serializable_response(encoding, body, make_body))
+}
+
+fn fixed_serializable_response<B, F>(encoding: Encoding, body: Vec<u8>, make_body: F) -> Response<B>
+where
+    F: FnOnce(Bytes) -> B,
+{
     let len = body.len();
 
     let mut response = Response::new(make_body(Bytes::from(body)));
     response
         .headers_mut()
-        .insert(CONTENT_TYPE, APPLICATION_JSON);
+        .insert(CONTENT_TYPE, encoding.content_type());
     response
         .headers_mut()
         .insert(CONTENT_LENGTH, HeaderValue::from(len));
@@ -407,6 +518,224 @@ where
     response
 }
 
+pub fn encode_streaming_serializable_response<T, O>(
+    parts: &request::Parts,
+    value: T,
+    buffer_limit: usize,
+) -> Result<Response<ResponseBody<O>>, Error>
+where
+    T: Serialize + 'static,
+    O: Write,
+{
+    streaming_serializable_response(&parts.headers, value, buffer_limit)
+}
+
+pub fn encode_streaming_default_serializable_response<T, O>(
+    parts: &request::Parts,
+    value: T,
+    buffer_limit: usize,
+) -> Result<Response<ResponseBody<O>>, Error>
+where
+    T: Serialize + Default + PartialEq + 'static,
+    O: Write,
+{
+    if value == T::default() {
+        Ok(encode_empty_response())
+    } else {
+        encode_streaming_serializable_response(parts, value, buffer_limit)
+    }
+}
+
+pub fn async_encode_streaming_serializable_response<T, O>(
+    parts: &request::Parts,
+    value: T,
+    buffer_limit: usize,
+) -> Result<Response<AsyncResponseBody<O>>, Error>
+where
+    T: Serialize + Send + 'static,
+    O: AsyncWrite + Send,
+{
+    async_streaming_serializable_response(&parts.headers, value, buffer_limit)
+}
+
+pub fn async_encode_streaming_default_serializable_response<T, O>(
+    parts: &request::Parts,
+    value: T,
+    buffer_limit: usize,
+) -> Result<Response<AsyncResponseBody<O>>, Error>
+where
+    T: Serialize + Default + PartialEq + Send + 'static,
+    O: AsyncWrite + Send,
+{
+    if value == T::default() {
+        Ok(async_encode_empty_response())
+    } else {
+        async_encode_streaming_serializable_response(parts, value, buffer_limit)
+    }
+}
+
+// Responses that fit in `buffer_limit` bytes are sent as normal fixed bodies. Serialization stops
+// as soon as the limit is exceeded and restarts directly into the response writer when the body is
+// written.
+pub(crate) fn streaming_serializable_response<T, O>(
+    request_headers: &HeaderMap,
+    value: T,
+    buffer_limit: usize,
+) -> Result<Response<ResponseBody<O>>, Error>
+where
+    T: Serialize + 'static,
+    O: Write,
+{
+    let encoding = Encoding::from_accept(request_headers).ok_or_else(not_acceptable)?;
+
+    match buffer_serializable_body(encoding, &value, buffer_limit)? {
+        Some(body) => Ok(fixed_serializable_response(
+            encoding,
+            body,
+            ResponseBody::Fixed,
+        )),
+        None => Ok(streaming_serializable_response_inner(
+            encoding,
+            ResponseBody::Streaming(Box::new(SerializableBody { encoding, value })),
+        )),
+    }
+}
+
+// Like streaming_serializable_response, but the streamed body is serialized on a separate thread
+// since serialization can't yield to the executor while the response writer applies backpressure.
+pub(crate) fn async_streaming_serializable_response<T, O>(
+    request_headers: &HeaderMap,
+    value: T,
+    buffer_limit: usize,
+) -> Result<Response<AsyncResponseBody<O>>, Error>
+where
+    T: Serialize + Send + 'static,
+    O: AsyncWrite + Send,
+{
+    let encoding = Encoding::from_accept(request_headers).ok_or_else(not_acceptable)?;
+
+    match buffer_serializable_body(encoding, &value, buffer_limit)? {
+        Some(body) => Ok(fixed_serializable_response(
+            encoding,
+            body,
+            AsyncResponseBody::Fixed,
+        )),
+        None => Ok(streaming_serializable_response_inner(
+            encoding,
+            AsyncResponseBody::Streaming(Box::new(AsyncSerializableBody { encoding, value })),
+        )),
+    }
+}
+
+// Returns `None` if the value serializes to more than `limit` bytes.
+fn buffer_serializable_body<T>(
+    encoding: Encoding,
+    value: &T,
+    limit: usize,
+) -> Result<Option<Vec<u8>>, Error>
+where
+    T: Serialize,
+{
+    let mut buf = LimitedWriter {
+        buf: vec![],
+        limit,
+        exceeded: false,
+    };
+    match encoding.to_writer(&mut buf, value) {
+        Ok(()) => Ok(Some(buf.buf)),
+        Err(_) if buf.exceeded => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn streaming_serializable_response_inner<B>(encoding: Encoding, body: B) -> Response<B> {
+    let mut response = Response::new(body);
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, encoding.content_type());
+    response
+}
+
+struct LimitedWriter {
+    buf: Vec<u8>,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buf.len() + buf.len() > self.limit {
+            self.exceeded = true;
+            // the error is discarded once `exceeded` is checked
+            return Err(io::ErrorKind::Other.into());
+        }
+
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct SerializableBody<T> {
+    encoding: Encoding,
+    value: T,
+}
+
+impl<T, O> WriteBody<O> for SerializableBody<T>
+where
+    T: Serialize,
+    O: Write,
+{
+    fn write_body(self: Box<Self>, w: &mut O) -> Result<(), Error> {
+        let mut w = BufWriter::new(w);
+        self.encoding.to_writer(&mut w, &self.value)?;
+        w.flush().map_err(Error::internal_safe)
+    }
+}
+
+// The size of the chunks streamed async responses are serialized in.
+const STREAMING_CHUNK_SIZE: usize = 64 * 1024;
+
+struct AsyncSerializableBody<T> {
+    encoding: Encoding,
+    value: T,
+}
+
+#[async_trait]
+impl<T, O> AsyncWriteBody<O> for AsyncSerializableBody<T>
+where
+    T: Serialize + Send + 'static,
+    O: AsyncWrite + Send,
+{
+    async fn write_body(self: Box<Self>, mut w: Pin<&mut O>) -> Result<(), Error> {
+        let AsyncSerializableBody { encoding, value } = *self;
+
+        // The serializer blocks while each chunk is written, so at most two chunks are in memory.
+        let (mut writer, mut receiver) = chunks::channel(STREAMING_CHUNK_SIZE);
+        thread::Builder::new()
+            .name("conjure-response-serializer".to_string())
+            .spawn(move || {
+                let result = encoding.to_writer(&mut writer, &value);
+                writer.finish(result);
+            })
+            .map_err(Error::internal_safe)?;
+
+        while let Some(chunk) = receiver.recv().await {
+            w.write_all(&chunk?).await.map_err(Error::internal_safe)?;
+        }
+
+        w.flush().await.map_err(Error::internal_safe)
+    }
+}
+
+pub(crate) fn not_acceptable() -> Error {
+    Error::service_safe("no acceptable response Content-Type", NotAcceptable::new())
+}
+
+// FIXME remove in favor of the content negotiating encode_default_serializable_response2
 pub fn encode_default_serializable_response<T, O>(value: &T) -> Response<ResponseBody<O>>
 where
     T: Serialize + Default + PartialEq,
@@ -418,6 +747,21 @@ where
     }
 }
 
+pub fn encode_default_serializable_response2<T, O>(
+    parts: &request::Parts,
+    value: &T,
+) -> Result<Response<ResponseBody<O>>, Error>
+where
+    T: Serialize + Default + PartialEq,
+{
+    if value == &T::default() {
+        Ok(encode_empty_response())
+    } else {
+        encode_serializable_response2(parts, value)
+    }
+}
+
+// FIXME remove in favor of the content negotiating async_encode_default_serializable_response2
 pub fn async_encode_default_serializable_response<T, O>(value: &T) -> Response<AsyncResponseBody<O>>
 where
     T: Serialize + Default + PartialEq,
@@ -429,6 +773,20 @@ where
     }
 }
 
+pub fn async_encode_default_serializable_response2<T, O>(
+    parts: &request::Parts,
+    value: &T,
+) -> Result<Response<AsyncResponseBody<O>>, Error>
+where
+    T: Serialize + Default + PartialEq,
+{
+    if value == &T::default() {
+        Ok(async_encode_empty_response())
+    } else {
+        async_encode_serializable_response2(parts, value)
+    }
+}
+
 pub fn encode_binary_response<T, O>(value: T) -> Response<ResponseBody<O>>
 where
     T: WriteBody<O> + 'static,
@@ -0,0 +1,171 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structured request logging.
+//!
+//! Request logs are emitted by [`RequestLogLayer`](crate::server::RequestLogLayer) on the server
+//! side and [`LoggingClient`](crate::client::LoggingClient) on the client side, and are passed to a
+//! [`RequestLogSink`] which is responsible for writing them to whatever logging system is in use.
+use crate::SafeParams;
+use conjure_error::Error;
+use conjure_object::Any;
+use http::{Method, StatusCode};
+use std::time::Duration;
+
+/// A sink receiving request logs.
+pub trait RequestLogSink {
+    /// Records the log of a single request.
+    fn log(&self, log: &RequestLog<'_>);
+}
+
+impl<F> RequestLogSink for F
+where
+    F: ?Sized + Fn(&RequestLog<'_>),
+{
+    fn log(&self, log: &RequestLog<'_>) {
+        self(log)
+    }
+}
+
+/// The value of a parameter in a request log.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoggedParam<'a> {
+    /// The value of a safe parameter.
+    Safe(&'a Any),
+    /// The placeholder for a parameter whose value is unsafe or must not be logged.
+    Redacted,
+}
+
+/// A structured log of a single request.
+#[derive(Debug)]
+pub struct RequestLog<'a> {
+    service: &'a str,
+    endpoint: &'a str,
+    method: Method,
+    path: &'a str,
+    status: Option<StatusCode>,
+    duration: Duration,
+    params: Vec<(&'a str, LoggedParam<'a>)>,
+    error: Option<&'a Error>,
+}
+
+impl<'a> RequestLog<'a> {
+    pub(crate) fn new(
+        service: &'a str,
+        endpoint: &'a str,
+        method: Method,
+        path: &'a str,
+        duration: Duration,
+    ) -> Self {
+        RequestLog {
+            service,
+            endpoint,
+            method,
+            path,
+            status: None,
+            duration,
+            params: vec![],
+            error: None,
+        }
+    }
+
+    pub(crate) fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub(crate) fn with_error(mut self, error: &'a Error) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    pub(crate) fn with_param(mut self, name: &'a str, value: LoggedParam<'a>) -> Self {
+        self.params.push((name, value));
+        self
+    }
+
+    /// Adds the safe parameters which have not already been added, in a deterministic order.
+    pub(crate) fn with_safe_params(mut self, safe_params: Option<&'a SafeParams>) -> Self {
+        let mut extra = safe_params
+            .into_iter()
+            .flatten()
+            .filter(|(name, _)| !self.params.iter().any(|(n, _)| n == name))
+            .map(|(name, value)| (name, LoggedParam::Safe(value)))
+            .collect::<Vec<_>>();
+        extra.sort_by_key(|(name, _)| *name);
+        self.params.extend(extra);
+        self
+    }
+
+    /// Returns the name of the service the endpoint is part of.
+    ///
+    /// This is empty if the endpoint is unknown.
+    #[inline]
+    pub fn service(&self) -> &'a str {
+        self.service
+    }
+
+    /// Returns the name of the endpoint.
+    ///
+    /// This is empty if the endpoint is unknown.
+    #[inline]
+    pub fn endpoint(&self) -> &'a str {
+        self.endpoint
+    }
+
+    /// Returns the HTTP method of the request.
+    #[inline]
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the endpoint's path template.
+    ///
+    /// Unlike the request's URI, the template does not contain the values of path parameters.
+    #[inline]
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Returns the status code of the response.
+    ///
+    /// This is `None` if the request failed without a response, for example due to an IO error.
+    #[inline]
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    /// Returns the time taken to produce the response.
+    ///
+    /// This does not include the time taken to transfer a streaming response body.
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the request's parameters.
+    ///
+    /// Only the values of safe parameters are included. Parameters whose values are unsafe or must
+    /// not be logged are included as [`LoggedParam::Redacted`] when they are known.
+    #[inline]
+    pub fn params(&self) -> &[(&'a str, LoggedParam<'a>)] {
+        &self.params
+    }
+
+    /// Returns the error the request failed with, if any.
+    #[inline]
+    pub fn error(&self) -> Option<&'a Error> {
+        self.error
+    }
+}
@@ -46,6 +46,12 @@ impl SafeParams {
         );
     }
 
+    /// Returns the value of a parameter.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&Any> {
+        self.0.get(name)
+    }
+
     /// Returns an iterator over the parameters.
     #[inline]
     pub fn iter(&self) -> Iter<'_> {
@@ -13,13 +13,16 @@
 // limitations under the License.
 
 //! The Conjure HTTP server API.
-use crate::private::{self, APPLICATION_JSON, SERIALIZABLE_REQUEST_SIZE_LIMIT};
+use crate::deadline::Deadline;
+use crate::private::{self, Encoding, APPLICATION_JSON};
+use crate::trace::TraceContext;
 use async_trait::async_trait;
 use bytes::Bytes;
-use conjure_error::{Error, InvalidArgument};
+use conjure_error::{Error, ErrorKind, Internal, InvalidArgument, SerializableError};
 use conjure_serde::json;
 use futures_core::Stream;
-use http::header::CONTENT_TYPE;
+use futures_io::AsyncWrite;
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE, RETRY_AFTER};
 use http::{
     request, Extensions, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri,
 };
@@ -36,6 +39,28 @@ use std::pin::Pin;
 use std::str;
 use std::str::FromStr;
 
+pub use self::auth::{AsyncAuthLayer, AsyncAuthValidator, AuthLayer, AuthValidator};
+pub use self::deadline::DeadlineLayer;
+pub use self::errors::{NotAcceptable, UnsupportedMediaType};
+pub use self::layer::{AsyncEndpointLayer, EndpointLayer, LayeredService};
+pub use self::logging::RequestLogLayer;
+pub use self::metadata::{ArgumentMetadata, AuthType, LogSafety, ParameterType};
+#[cfg(any(feature = "testing", feature = "tower"))]
+pub(crate) use self::router::Unmatched;
+pub use self::router::{Route, Router, RouterBuilder};
+pub use self::trace::TraceLayer;
+
+mod auth;
+mod deadline;
+mod errors;
+mod layer;
+mod logging;
+mod metadata;
+mod router;
+#[cfg(feature = "tower")]
+pub mod tower;
+mod trace;
+
 /// Metadata about an HTTP endpoint.
 pub trait EndpointMetadata {
     /// The endpoint's HTTP method.
@@ -59,6 +84,35 @@ pub trait EndpointMetadata {
 
     /// If the endpoint is deprecated, returns the deprecation documentation.
     fn deprecated(&self) -> Option<&str>;
+
+    /// The endpoint's authentication scheme, if it requires authentication.
+    fn auth(&self) -> Option<AuthType> {
+        None
+    }
+
+    /// The endpoint's arguments.
+    ///
+    /// Authentication and request context arguments are not included.
+    fn args(&self) -> &[ArgumentMetadata] {
+        &[]
+    }
+
+    /// A description of the endpoint's return type, if it returns a value.
+    ///
+    /// See [`ArgumentMetadata::type_`] for the format of the description.
+    fn returns(&self) -> Option<&str> {
+        None
+    }
+
+    /// The names of the endpoint's marker types.
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
+
+    /// The endpoint's tags.
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
 }
 
 impl<T> EndpointMetadata for Box<T>
@@ -88,6 +142,26 @@ where
     fn deprecated(&self) -> Option<&str> {
         (**self).deprecated()
     }
+
+    fn auth(&self) -> Option<AuthType> {
+        (**self).auth()
+    }
+
+    fn args(&self) -> &[ArgumentMetadata] {
+        (**self).args()
+    }
+
+    fn returns(&self) -> Option<&str> {
+        (**self).returns()
+    }
+
+    fn markers(&self) -> &[&str] {
+        (**self).markers()
+    }
+
+    fn tags(&self) -> &[&str] {
+        (**self).tags()
+    }
 }
 
 /// A blocking HTTP endpoint.
@@ -216,6 +290,134 @@ pub trait AsyncService<I, O> {
     fn endpoints(&self) -> Vec<Box<dyn AsyncEndpoint<I, O> + Sync + Send>>;
 }
 
+/// A hook invoked with errors as they are encoded into responses.
+///
+/// This is typically used to log the error's cause, parameters, and backtraces, which are not
+/// included in the response sent to the client.
+pub trait ErrorHook {
+    /// Handles an error which will be encoded into a response with the specified status code.
+    fn on_error(&self, error: &Error, status: StatusCode);
+}
+
+impl<F> ErrorHook for F
+where
+    F: ?Sized + Fn(&Error, StatusCode),
+{
+    fn on_error(&self, error: &Error, status: StatusCode) {
+        self(error, status)
+    }
+}
+
+/// Encodes an error into a response for a blocking endpoint.
+///
+/// The encoding follows the Conjure wire specification:
+///
+/// * Service errors are encoded as a JSON `SerializableError` body with a status code determined
+///   by the error's code. [`UnsupportedMediaType`] and [`NotAcceptable`] errors use
+///   `415 Unsupported Media Type` and `406 Not Acceptable` respectively.
+/// * Throttle errors are encoded as an empty `429 Too Many Requests` response, with a
+///   `Retry-After` header if the error has a duration.
+/// * Unavailable errors are encoded as an empty `503 Service Unavailable` response.
+///
+/// Only the serialized form of a service error is sent to the client. Its cause, safe and unsafe
+/// parameters, and backtraces are not included in the response.
+pub fn encode_error<O>(error: &Error) -> Response<ResponseBody<O>> {
+    encode_error_with_hook(error, &|_: &Error, _| {})
+}
+
+/// Like [`encode_error`], but passes the error to a hook before encoding it.
+pub fn encode_error_with_hook<O, H>(error: &Error, hook: &H) -> Response<ResponseBody<O>>
+where
+    H: ?Sized + ErrorHook,
+{
+    encode_error_inner(error, hook, ResponseBody::Empty, ResponseBody::Fixed)
+}
+
+/// Encodes an error into a response for an async endpoint.
+///
+/// The encoding is identical to [`encode_error`].
+pub fn async_encode_error<O>(error: &Error) -> Response<AsyncResponseBody<O>> {
+    async_encode_error_with_hook(error, &|_: &Error, _| {})
+}
+
+/// Like [`async_encode_error`], but passes the error to a hook before encoding it.
+pub fn async_encode_error_with_hook<O, H>(error: &Error, hook: &H) -> Response<AsyncResponseBody<O>>
+where
+    H: ?Sized + ErrorHook,
+{
+    encode_error_inner(
+        error,
+        hook,
+        AsyncResponseBody::Empty,
+        AsyncResponseBody::Fixed,
+    )
+}
+
+fn encode_error_inner<B, H, F>(error: &Error, hook: &H, empty: B, make_body: F) -> Response<B>
+where
+    H: ?Sized + ErrorHook,
+    F: FnOnce(Bytes) -> B,
+{
+    let internal;
+    let body = match error.kind() {
+        ErrorKind::Service(e) => Some(e),
+        ErrorKind::Throttle(_) | ErrorKind::Unavailable(_) => None,
+        _ => {
+            internal = conjure_error::encode(&Internal::new());
+            Some(&internal)
+        }
+    };
+    let status = error_status(error);
+
+    hook.on_error(error, status);
+
+    let mut response = match body {
+        Some(body) => {
+            let body = json::to_vec(body).expect("SerializableError can serialize to JSON");
+            let len = body.len();
+            let mut response = Response::new(make_body(Bytes::from(body)));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, APPLICATION_JSON);
+            response
+                .headers_mut()
+                .insert(CONTENT_LENGTH, HeaderValue::from(len));
+            response
+        }
+        None => Response::new(empty),
+    };
+    *response.status_mut() = status;
+
+    if let ErrorKind::Throttle(e) = error.kind() {
+        if let Some(duration) = e.duration() {
+            // round up so clients don't retry early
+            let secs = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+    }
+
+    response
+}
+
+// The status code of the response an error is encoded into.
+pub(crate) fn error_status(error: &Error) -> StatusCode {
+    match error.kind() {
+        ErrorKind::Service(e) => service_status(e),
+        ErrorKind::Throttle(_) => StatusCode::TOO_MANY_REQUESTS,
+        ErrorKind::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn service_status(error: &SerializableError) -> StatusCode {
+    errors::status_code(error.error_name()).unwrap_or_else(|| {
+        StatusCode::from_u16(error.error_code().status_code())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    })
+}
+
 /// A trait implemented by streaming bodies.
 pub trait WriteBody<W> {
     /// Writes the body out, in its entirety.
@@ -271,6 +473,7 @@ pub trait AsyncWriteBody<W> {
 pub struct RequestContext<'a> {
     request_parts: MaybeBorrowed<'a, request::Parts>,
     response_extensions: &'a mut Extensions,
+    deadline: Option<Deadline>,
 }
 
 impl<'a> RequestContext<'a> {
@@ -280,6 +483,7 @@ impl<'a> RequestContext<'a> {
     // FIXME remove in favor of borrowed constructor
     pub fn new(request_parts: request::Parts, response_extensions: &'a mut Extensions) -> Self {
         RequestContext {
+            deadline: request_deadline(&request_parts),
             request_parts: MaybeBorrowed::Owned(request_parts),
             response_extensions,
         }
@@ -294,6 +498,7 @@ impl<'a> RequestContext<'a> {
         RequestContext {
             request_parts: MaybeBorrowed::Borrowed(request_parts),
             response_extensions,
+            deadline: request_deadline(request_parts),
         }
     }
 
@@ -315,6 +520,42 @@ impl<'a> RequestContext<'a> {
         &self.request_parts.extensions
     }
 
+    /// Returns the principal identified by the request's bearer token.
+    ///
+    /// The principal is inserted into the request's extensions by an [`AuthLayer`] or
+    /// [`AsyncAuthLayer`], and is `None` if neither has been applied to the service, the endpoint
+    /// is unauthenticated, or `P` is not the validator's principal type.
+    #[inline]
+    pub fn principal<P>(&self) -> Option<&P>
+    where
+        P: 'static + Sync + Send,
+    {
+        self.request_parts.extensions.get::<P>()
+    }
+
+    /// Returns the request's deadline, if the client provided one.
+    ///
+    /// The deadline is taken from the request's extensions if a [`DeadlineLayer`] has been
+    /// applied to the service, and is otherwise parsed from the request's headers when the
+    /// context is created.
+    #[inline]
+    pub fn deadline(&self) -> Option<Deadline> {
+        self.deadline
+    }
+
+    /// Returns the request's trace context, if the client provided one.
+    ///
+    /// The context is taken from the request's extensions if a [`TraceLayer`] has been applied to
+    /// the service, and is otherwise extracted from the request's headers in any of the formats
+    /// supported by [`TraceContext::from_headers`].
+    #[inline]
+    pub fn trace_context(&self) -> Option<TraceContext> {
+        match self.request_parts.extensions.get::<TraceContext>() {
+            Some(context) => Some(context.clone()),
+            None => TraceContext::from_headers(&self.request_parts.headers),
+        }
+    }
+
     /// Returns a shared reference to extensions that will be added to the response.
     #[inline]
     pub fn response_extensions(&self) -> &Extensions {
@@ -328,6 +569,14 @@ impl<'a> RequestContext<'a> {
     }
 }
 
+// The header is relative to the time it's parsed, so it must only be parsed once per request.
+fn request_deadline(parts: &request::Parts) -> Option<Deadline> {
+    match parts.extensions.get::<Deadline>() {
+        Some(deadline) => Some(*deadline),
+        None => Deadline::from_headers(&parts.headers),
+    }
+}
+
 enum MaybeBorrowed<'a, T> {
     Borrowed(&'a T),
     Owned(T),
@@ -362,36 +611,35 @@ pub trait AsyncDeserializeRequest<T, R> {
         R: 'async_trait;
 }
 
+/// The default limit on the size of serializable request bodies, in bytes.
+pub const DEFAULT_REQUEST_SIZE_LIMIT: usize = 50 * 1024 * 1024;
+
 /// A request deserializer which acts as a Conjure-generated endpoint would.
-pub enum ConjureRequestDeserializer {}
-
-impl ConjureRequestDeserializer {
-    fn check_content_type(headers: &HeaderMap) -> Result<(), Error> {
-        if headers.get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-            return Err(Error::service_safe(
-                "invalid request Content-Type",
-                InvalidArgument::new(),
-            ));
-        }
+///
+/// Both JSON and Smile request bodies are supported. Bodies larger than `LIMIT` bytes are rejected
+/// with a `RequestEntityTooLarge` error.
+pub enum ConjureRequestDeserializer<const LIMIT: usize = DEFAULT_REQUEST_SIZE_LIMIT> {}
 
-        Ok(())
+impl<const LIMIT: usize> ConjureRequestDeserializer<LIMIT> {
+    fn check_content_type(headers: &HeaderMap) -> Result<Encoding, Error> {
+        Encoding::from_content_type(headers).ok_or_else(|| private::unsupported_media_type(headers))
     }
 }
 
-impl<T, R> DeserializeRequest<T, R> for ConjureRequestDeserializer
+impl<T, R, const LIMIT: usize> DeserializeRequest<T, R> for ConjureRequestDeserializer<LIMIT>
 where
     T: DeserializeOwned,
     R: Iterator<Item = Result<Bytes, Error>>,
 {
     fn deserialize(headers: &HeaderMap, body: R) -> Result<T, Error> {
-        Self::check_content_type(headers)?;
-        let buf = private::read_body(body, Some(SERIALIZABLE_REQUEST_SIZE_LIMIT))?;
-        json::server_from_slice(&buf).map_err(|e| Error::service(e, InvalidArgument::new()))
+        let encoding = Self::check_content_type(headers)?;
+        let buf = private::read_body(body, Some(LIMIT))?;
+        encoding.server_from_slice(&buf)
     }
 }
 
 #[async_trait]
-impl<T, R> AsyncDeserializeRequest<T, R> for ConjureRequestDeserializer
+impl<T, R, const LIMIT: usize> AsyncDeserializeRequest<T, R> for ConjureRequestDeserializer<LIMIT>
 where
     T: DeserializeOwned,
     R: Stream<Item = Result<Bytes, Error>> + Send,
@@ -400,9 +648,9 @@ where
     where
         R: 'async_trait,
     {
-        Self::check_content_type(headers)?;
-        let buf = private::async_read_body(body, Some(SERIALIZABLE_REQUEST_SIZE_LIMIT)).await?;
-        json::server_from_slice(&buf).map_err(|e| Error::service(e, InvalidArgument::new()))
+        let encoding = Self::check_content_type(headers)?;
+        let buf = private::async_read_body(body, Some(LIMIT)).await?;
+        encoding.server_from_slice(&buf)
     }
 }
 
@@ -447,22 +695,28 @@ impl<W> AsyncSerializeResponse<(), W> for EmptyResponseSerializer {
 }
 
 /// A serializer which acts like a Conjure-generated client would.
+///
+/// The response is Smile-encoded if the request's `Accept` header prefers it, and JSON-encoded
+/// otherwise.
 pub enum ConjureResponseSerializer {}
 
 impl ConjureResponseSerializer {
     fn serialize_inner<T, B>(
+        request_headers: &HeaderMap,
         value: T,
         make_body: impl FnOnce(Bytes) -> B,
     ) -> Result<Response<B>, Error>
     where
         T: Serialize,
     {
-        let body = json::to_vec(&value).map_err(Error::internal)?;
+        let encoding =
+            Encoding::from_accept(request_headers).ok_or_else(private::not_acceptable)?;
+        let body = encoding.to_vec(&value)?;
 
         let mut response = Response::new(make_body(body.into()));
         response
             .headers_mut()
-            .insert(CONTENT_TYPE, APPLICATION_JSON);
+            .insert(CONTENT_TYPE, encoding.content_type());
 
         Ok(response)
     }
@@ -473,10 +727,10 @@ where
     T: Serialize,
 {
     fn serialize(
-        _request_headers: &HeaderMap,
+        request_headers: &HeaderMap,
         value: T,
     ) -> Result<Response<ResponseBody<W>>, Error> {
-        Self::serialize_inner(value, ResponseBody::Fixed)
+        Self::serialize_inner(request_headers, value, ResponseBody::Fixed)
     }
 }
 
@@ -485,10 +739,59 @@ where
     T: Serialize,
 {
     fn serialize(
-        _request_headers: &HeaderMap,
+        request_headers: &HeaderMap,
+        value: T,
+    ) -> Result<Response<AsyncResponseBody<W>>, Error> {
+        Self::serialize_inner(request_headers, value, AsyncResponseBody::Fixed)
+    }
+}
+
+/// A serializer which writes responses directly into the response body rather than buffering them
+/// in memory first.
+///
+/// The response is encoded in the same way as by [`ConjureResponseSerializer`]. Responses which
+/// serialize to at most `BUFFER_LIMIT` bytes are buffered and sent with a `Content-Length` header
+/// as normal. Larger responses are serialized again as the body is written, so they are never held
+/// in memory in their entirety.
+///
+/// Async endpoints serialize streamed responses on a separate thread, since serialization cannot
+/// yield while waiting on the writer. The thread blocks while each chunk of the body is written.
+///
+/// # Errors
+///
+/// Errors encountered while buffering the response are returned from `serialize` as usual. Errors
+/// encountered once the body is being streamed, whether from serialization or from the writer
+/// itself, are returned from [`WriteBody::write_body`] or [`AsyncWriteBody::write_body`]. The
+/// response status and headers have already been sent at that point, so servers must abort the
+/// response (for example by closing the connection) rather than completing it. Otherwise the client
+/// could mistake a truncated body for a complete one.
+pub enum StreamingConjureResponseSerializer<const BUFFER_LIMIT: usize = 0> {}
+
+impl<T, W, const BUFFER_LIMIT: usize> SerializeResponse<T, W>
+    for StreamingConjureResponseSerializer<BUFFER_LIMIT>
+where
+    T: Serialize + 'static,
+    W: Write,
+{
+    fn serialize(
+        request_headers: &HeaderMap,
+        value: T,
+    ) -> Result<Response<ResponseBody<W>>, Error> {
+        private::streaming_serializable_response(request_headers, value, BUFFER_LIMIT)
+    }
+}
+
+impl<T, W, const BUFFER_LIMIT: usize> AsyncSerializeResponse<T, W>
+    for StreamingConjureResponseSerializer<BUFFER_LIMIT>
+where
+    T: Serialize + Send + 'static,
+    W: AsyncWrite + Send,
+{
+    fn serialize(
+        request_headers: &HeaderMap,
         value: T,
     ) -> Result<Response<AsyncResponseBody<W>>, Error> {
-        Self::serialize_inner(value, AsyncResponseBody::Fixed)
+        private::async_streaming_serializable_response(request_headers, value, BUFFER_LIMIT)
     }
 }
 
@@ -0,0 +1,215 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::private;
+use crate::server::{AsyncEndpointLayer, AuthType, EndpointLayer, EndpointMetadata};
+use async_trait::async_trait;
+use conjure_error::{Error, PermissionDenied};
+use conjure_object::BearerToken;
+use http::{request, response, Extensions};
+use std::sync::Arc;
+
+/// A validator of the bearer tokens sent to authenticated endpoints.
+///
+/// Validators are applied to services with an [`AuthLayer`].
+pub trait AuthValidator {
+    /// The principal a valid token identifies.
+    type Principal: 'static + Sync + Send;
+
+    /// Validates a token sent to an endpoint.
+    ///
+    /// Returns `Ok(None)` if the token is invalid, in which case the request is rejected with a
+    /// [`PermissionDenied`] error. Errors, for example if the source of truth for tokens is
+    /// unavailable, are returned to the client as-is.
+    fn validate(
+        &self,
+        endpoint: &dyn EndpointMetadata,
+        token: &BearerToken,
+    ) -> Result<Option<Self::Principal>, Error>;
+}
+
+impl<T> AuthValidator for Arc<T>
+where
+    T: ?Sized + AuthValidator,
+{
+    type Principal = T::Principal;
+
+    fn validate(
+        &self,
+        endpoint: &dyn EndpointMetadata,
+        token: &BearerToken,
+    ) -> Result<Option<Self::Principal>, Error> {
+        (**self).validate(endpoint, token)
+    }
+}
+
+/// An async validator of the bearer tokens sent to authenticated endpoints.
+///
+/// Validators are applied to async services with an [`AsyncAuthLayer`].
+#[async_trait]
+pub trait AsyncAuthValidator {
+    /// The principal a valid token identifies.
+    type Principal: 'static + Sync + Send;
+
+    /// Validates a token sent to an endpoint.
+    ///
+    /// This behaves like [`AuthValidator::validate`].
+    async fn validate(
+        &self,
+        endpoint: &(dyn EndpointMetadata + Sync),
+        token: &BearerToken,
+    ) -> Result<Option<Self::Principal>, Error>;
+}
+
+#[async_trait]
+impl<T> AsyncAuthValidator for Arc<T>
+where
+    T: ?Sized + AsyncAuthValidator + Sync + Send,
+{
+    type Principal = T::Principal;
+
+    async fn validate(
+        &self,
+        endpoint: &(dyn EndpointMetadata + Sync),
+        token: &BearerToken,
+    ) -> Result<Option<Self::Principal>, Error> {
+        (**self).validate(endpoint, token).await
+    }
+}
+
+/// An [`EndpointLayer`] which validates the bearer tokens sent to authenticated endpoints.
+///
+/// For endpoints with an [`AuthType`], the token is parsed from the request and passed to an
+/// [`AuthValidator`] before any of the endpoint's arguments are decoded. Requests with a missing or
+/// invalid token are rejected with a [`PermissionDenied`] error. Otherwise, the validated principal
+/// is inserted into the request's extensions, where handlers can retrieve it with
+/// [`RequestContext::principal`](crate::server::RequestContext::principal).
+///
+/// Endpoints without an `AuthType` are passed through unchanged.
+pub struct AuthLayer<V> {
+    validator: V,
+}
+
+impl<V> AuthLayer<V> {
+    /// Creates a new `AuthLayer` using the provided validator.
+    pub fn new(validator: V) -> Self {
+        AuthLayer { validator }
+    }
+}
+
+impl<V> EndpointLayer for AuthLayer<V>
+where
+    V: AuthValidator,
+{
+    type State = ();
+
+    fn on_request(
+        &self,
+        endpoint: &dyn EndpointMetadata,
+        request: &mut request::Parts,
+        _: &mut Extensions,
+    ) -> Result<Self::State, Error> {
+        let token = match parse_token(endpoint, request)? {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+
+        let principal = self.validator.validate(endpoint, &token)?;
+        insert_principal(request, principal)
+    }
+
+    fn on_response(
+        &self,
+        _: Self::State,
+        _: &dyn EndpointMetadata,
+        _: &mut Extensions,
+        _: Result<&mut response::Parts, &mut Error>,
+    ) {
+    }
+}
+
+/// An [`AsyncEndpointLayer`] which validates the bearer tokens sent to authenticated endpoints with
+/// an [`AsyncAuthValidator`].
+///
+/// It otherwise behaves like an [`AuthLayer`], and can only be applied to async services.
+pub struct AsyncAuthLayer<V> {
+    validator: V,
+}
+
+impl<V> AsyncAuthLayer<V> {
+    /// Creates a new `AsyncAuthLayer` using the provided validator.
+    pub fn new(validator: V) -> Self {
+        AsyncAuthLayer { validator }
+    }
+}
+
+#[async_trait]
+impl<V> AsyncEndpointLayer for AsyncAuthLayer<V>
+where
+    V: AsyncAuthValidator + Sync,
+{
+    type State = ();
+
+    async fn on_request(
+        &self,
+        endpoint: &(dyn EndpointMetadata + Sync),
+        request: &mut request::Parts,
+        _: &mut Extensions,
+    ) -> Result<Self::State, Error> {
+        let token = match parse_token(endpoint, request)? {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+
+        let principal = self.validator.validate(endpoint, &token).await?;
+        insert_principal(request, principal)
+    }
+
+    fn on_response(
+        &self,
+        _: Self::State,
+        _: &dyn EndpointMetadata,
+        _: &mut Extensions,
+        _: Result<&mut response::Parts, &mut Error>,
+    ) {
+    }
+}
+
+fn parse_token(
+    endpoint: &dyn EndpointMetadata,
+    request: &request::Parts,
+) -> Result<Option<BearerToken>, Error> {
+    match endpoint.auth() {
+        Some(AuthType::Header) => private::parse_header_auth(request).map(Some),
+        Some(AuthType::Cookie(name)) => {
+            private::parse_cookie_auth(request, &format!("{}=", name)).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+fn insert_principal<P>(request: &mut request::Parts, principal: Option<P>) -> Result<(), Error>
+where
+    P: 'static + Sync + Send,
+{
+    match principal {
+        Some(principal) => {
+            request.extensions.insert(principal);
+            Ok(())
+        }
+        None => Err(Error::service_safe(
+            "bearer token failed validation",
+            PermissionDenied::new(),
+        )),
+    }
+}
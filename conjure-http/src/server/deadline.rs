@@ -0,0 +1,67 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::deadline::Deadline;
+use crate::server::{EndpointLayer, EndpointMetadata};
+use conjure_error::Error;
+use http::{request, response, Extensions};
+
+/// An [`EndpointLayer`] which parses the [`Deadline`] propagated by clients.
+///
+/// The deadline is parsed from the [`DEADLINE_HEADER`](crate::deadline::DEADLINE_HEADER) header
+/// and inserted into the request's extensions, where handlers can retrieve it with
+/// [`RequestContext::deadline`](crate::server::RequestContext::deadline). Requests whose deadline
+/// has already passed are rejected with a [`Timeout`](conjure_error::Timeout) error before any of
+/// the endpoint's arguments are decoded.
+///
+/// Async handlers can use [`Deadline::enforce`] to abandon work once the deadline passes.
+#[derive(Debug, Default)]
+pub struct DeadlineLayer {
+    _p: (),
+}
+
+impl DeadlineLayer {
+    /// Creates a new `DeadlineLayer`.
+    pub fn new() -> Self {
+        DeadlineLayer::default()
+    }
+}
+
+impl EndpointLayer for DeadlineLayer {
+    type State = ();
+
+    fn on_request(
+        &self,
+        _: &dyn EndpointMetadata,
+        request: &mut request::Parts,
+        _: &mut Extensions,
+    ) -> Result<Self::State, Error> {
+        let deadline = match Deadline::from_headers(&request.headers) {
+            Some(deadline) => deadline,
+            None => return Ok(()),
+        };
+        deadline.check()?;
+        request.extensions.insert(deadline);
+
+        Ok(())
+    }
+
+    fn on_response(
+        &self,
+        _: Self::State,
+        _: &dyn EndpointMetadata,
+        _: &mut Extensions,
+        _: Result<&mut response::Parts, &mut Error>,
+    ) {
+    }
+}
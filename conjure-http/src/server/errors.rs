@@ -0,0 +1,127 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use conjure_error::{ErrorCode, ErrorType};
+use conjure_object::Uuid;
+use http::StatusCode;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// An error indicating that the request body's `Content-Type` is not supported by the endpoint.
+///
+/// It is encoded by [`encode_error`](crate::server::encode_error) as a `415 Unsupported Media Type`
+/// response.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Default)]
+pub struct UnsupportedMediaType(());
+
+impl UnsupportedMediaType {
+    /// The error's name.
+    pub const NAME: &'static str = "Conjure:UnsupportedMediaType";
+
+    /// Constructs a new instance of the type.
+    #[inline]
+    pub fn new() -> UnsupportedMediaType {
+        UnsupportedMediaType(())
+    }
+}
+
+impl ErrorType for UnsupportedMediaType {
+    #[inline]
+    fn code(&self) -> ErrorCode {
+        ErrorCode::CustomClient
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    #[inline]
+    fn instance_id(&self) -> Option<Uuid> {
+        None
+    }
+
+    #[inline]
+    fn safe_args(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+impl Serialize for UnsupportedMediaType {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.serialize_struct("UnsupportedMediaType", 0)?.end()
+    }
+}
+
+/// An error indicating that none of the media types in the request's `Accept` header can be
+/// produced by the endpoint.
+///
+/// It is encoded by [`encode_error`](crate::server::encode_error) as a `406 Not Acceptable`
+/// response.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Default)]
+pub struct NotAcceptable(());
+
+impl NotAcceptable {
+    /// The error's name.
+    pub const NAME: &'static str = "Conjure:NotAcceptable";
+
+    /// Constructs a new instance of the type.
+    #[inline]
+    pub fn new() -> NotAcceptable {
+        NotAcceptable(())
+    }
+}
+
+impl ErrorType for NotAcceptable {
+    #[inline]
+    fn code(&self) -> ErrorCode {
+        ErrorCode::CustomClient
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    #[inline]
+    fn instance_id(&self) -> Option<Uuid> {
+        None
+    }
+
+    #[inline]
+    fn safe_args(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+impl Serialize for NotAcceptable {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.serialize_struct("NotAcceptable", 0)?.end()
+    }
+}
+
+// Returns the status code of an error defined here. They are all `CUSTOM_CLIENT` errors, which
+// Conjure allows to use any 4xx status code.
+pub(crate) fn status_code(name: &str) -> Option<StatusCode> {
+    match name {
+        UnsupportedMediaType::NAME => Some(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+        NotAcceptable::NAME => Some(StatusCode::NOT_ACCEPTABLE),
+        _ => None,
+    }
+}
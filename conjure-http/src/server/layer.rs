@@ -0,0 +1,338 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::server::{
+    ArgumentMetadata, AsyncEndpoint, AsyncResponseBody, AsyncService, AuthType, Endpoint,
+    EndpointMetadata, PathSegment, ResponseBody, Service,
+};
+use async_trait::async_trait;
+use conjure_error::Error;
+use http::{request, response, Extensions, Method, Request, Response};
+use std::sync::Arc;
+
+/// A layer applying cross-cutting behavior like logging, metrics, or auditing to every endpoint of
+/// a service.
+///
+/// Layers are applied to services with [`LayeredService`], and work with both blocking and async
+/// endpoints.
+pub trait EndpointLayer {
+    /// State passed from [`Self::on_request`] to [`Self::on_response`] for a single request.
+    type State;
+
+    /// Invoked before a request is passed to the endpoint.
+    ///
+    /// The request's parts can be modified before the endpoint sees them, for example to add an
+    /// extension. If an error is returned, the endpoint is not invoked, [`Self::on_response`] is
+    /// not called, and the error is returned as the endpoint's result.
+    fn on_request(
+        &self,
+        endpoint: &dyn EndpointMetadata,
+        request: &mut request::Parts,
+        response_extensions: &mut Extensions,
+    ) -> Result<Self::State, Error>;
+
+    /// Invoked with the endpoint's result.
+    ///
+    /// The response's status, headers, and extensions, or the error, can be modified before they
+    /// are returned to the caller. The response body is not available, since a streaming body is
+    /// only written after the endpoint returns.
+    fn on_response(
+        &self,
+        state: Self::State,
+        endpoint: &dyn EndpointMetadata,
+        response_extensions: &mut Extensions,
+        result: Result<&mut response::Parts, &mut Error>,
+    );
+}
+
+/// An async variant of [`EndpointLayer`], whose request hook can wait on asynchronous work such
+/// as a network call.
+///
+/// Every `EndpointLayer` is also an `AsyncEndpointLayer`. [`LayeredService`] only applies layers
+/// which aren't `EndpointLayer`s to async services.
+#[async_trait]
+pub trait AsyncEndpointLayer {
+    /// State passed from [`Self::on_request`] to [`Self::on_response`] for a single request.
+    type State;
+
+    /// Invoked before a request is passed to the endpoint.
+    ///
+    /// This behaves like [`EndpointLayer::on_request`].
+    async fn on_request(
+        &self,
+        endpoint: &(dyn EndpointMetadata + Sync),
+        request: &mut request::Parts,
+        response_extensions: &mut Extensions,
+    ) -> Result<Self::State, Error>;
+
+    /// Invoked with the endpoint's result.
+    ///
+    /// This behaves like [`EndpointLayer::on_response`].
+    fn on_response(
+        &self,
+        state: Self::State,
+        endpoint: &dyn EndpointMetadata,
+        response_extensions: &mut Extensions,
+        result: Result<&mut response::Parts, &mut Error>,
+    );
+}
+
+#[async_trait]
+impl<T> AsyncEndpointLayer for T
+where
+    T: EndpointLayer + Sync,
+{
+    type State = T::State;
+
+    async fn on_request(
+        &self,
+        endpoint: &(dyn EndpointMetadata + Sync),
+        request: &mut request::Parts,
+        response_extensions: &mut Extensions,
+    ) -> Result<Self::State, Error> {
+        EndpointLayer::on_request(self, endpoint, request, response_extensions)
+    }
+
+    fn on_response(
+        &self,
+        state: Self::State,
+        endpoint: &dyn EndpointMetadata,
+        response_extensions: &mut Extensions,
+        result: Result<&mut response::Parts, &mut Error>,
+    ) {
+        EndpointLayer::on_response(self, state, endpoint, response_extensions, result)
+    }
+}
+
+impl<T> EndpointLayer for Arc<T>
+where
+    T: ?Sized + EndpointLayer,
+{
+    type State = T::State;
+
+    fn on_request(
+        &self,
+        endpoint: &dyn EndpointMetadata,
+        request: &mut request::Parts,
+        response_extensions: &mut Extensions,
+    ) -> Result<Self::State, Error> {
+        (**self).on_request(endpoint, request, response_extensions)
+    }
+
+    fn on_response(
+        &self,
+        state: Self::State,
+        endpoint: &dyn EndpointMetadata,
+        response_extensions: &mut Extensions,
+        result: Result<&mut response::Parts, &mut Error>,
+    ) {
+        (**self).on_response(state, endpoint, response_extensions, result)
+    }
+}
+
+/// A service which applies an [`EndpointLayer`] to all of the endpoints of another service.
+///
+/// It implements [`Service`] and [`AsyncService`] if the inner service does, though only an
+/// [`EndpointLayer`] can be applied to a blocking service. Layers can be stacked
+/// by nesting `LayeredService`s, with the outermost layer invoked first.
+pub struct LayeredService<S, L> {
+    service: S,
+    layer: Arc<L>,
+}
+
+impl<S, L> LayeredService<S, L> {
+    /// Creates a new `LayeredService`.
+    pub fn new(service: S, layer: L) -> Self {
+        LayeredService {
+            service,
+            layer: Arc::new(layer),
+        }
+    }
+}
+
+impl<S, L, I, O> Service<I, O> for LayeredService<S, L>
+where
+    S: Service<I, O>,
+    L: EndpointLayer + 'static + Sync + Send,
+    I: 'static,
+    O: 'static,
+{
+    fn endpoints(&self) -> Vec<Box<dyn Endpoint<I, O> + Sync + Send>> {
+        self.service
+            .endpoints()
+            .into_iter()
+            .map(|endpoint| {
+                Box::new(LayeredEndpoint {
+                    endpoint,
+                    layer: self.layer.clone(),
+                }) as _
+            })
+            .collect()
+    }
+}
+
+impl<S, L, I, O> AsyncService<I, O> for LayeredService<S, L>
+where
+    S: AsyncService<I, O>,
+    L: AsyncEndpointLayer + 'static + Sync + Send,
+    L::State: Send,
+    I: 'static + Send,
+    O: 'static,
+{
+    fn endpoints(&self) -> Vec<Box<dyn AsyncEndpoint<I, O> + Sync + Send>> {
+        self.service
+            .endpoints()
+            .into_iter()
+            .map(|endpoint| {
+                Box::new(LayeredEndpoint {
+                    endpoint,
+                    layer: self.layer.clone(),
+                }) as _
+            })
+            .collect()
+    }
+}
+
+struct LayeredEndpoint<E, L> {
+    endpoint: E,
+    layer: Arc<L>,
+}
+
+fn on_response<B, F>(
+    result: Result<Response<B>, Error>,
+    on_response: F,
+) -> Result<Response<B>, Error>
+where
+    F: FnOnce(Result<&mut response::Parts, &mut Error>),
+{
+    match result {
+        Ok(response) => {
+            let (mut parts, body) = response.into_parts();
+            on_response(Ok(&mut parts));
+            Ok(Response::from_parts(parts, body))
+        }
+        Err(mut error) => {
+            on_response(Err(&mut error));
+            Err(error)
+        }
+    }
+}
+
+impl<E, L> EndpointMetadata for LayeredEndpoint<E, L>
+where
+    E: EndpointMetadata,
+{
+    fn method(&self) -> Method {
+        self.endpoint.method()
+    }
+
+    fn path(&self) -> &[PathSegment] {
+        self.endpoint.path()
+    }
+
+    fn template(&self) -> &str {
+        self.endpoint.template()
+    }
+
+    fn service_name(&self) -> &str {
+        self.endpoint.service_name()
+    }
+
+    fn name(&self) -> &str {
+        self.endpoint.name()
+    }
+
+    fn deprecated(&self) -> Option<&str> {
+        self.endpoint.deprecated()
+    }
+
+    fn auth(&self) -> Option<AuthType> {
+        self.endpoint.auth()
+    }
+
+    fn args(&self) -> &[ArgumentMetadata] {
+        self.endpoint.args()
+    }
+
+    fn returns(&self) -> Option<&str> {
+        self.endpoint.returns()
+    }
+
+    fn markers(&self) -> &[&str] {
+        self.endpoint.markers()
+    }
+
+    fn tags(&self) -> &[&str] {
+        self.endpoint.tags()
+    }
+}
+
+impl<E, L, I, O> Endpoint<I, O> for LayeredEndpoint<E, L>
+where
+    E: Endpoint<I, O>,
+    L: EndpointLayer,
+{
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+    ) -> Result<Response<ResponseBody<O>>, Error> {
+        let (mut parts, body) = req.into_parts();
+        let state = self
+            .layer
+            .on_request(&self.endpoint, &mut parts, response_extensions)?;
+
+        let result = self
+            .endpoint
+            .handle(Request::from_parts(parts, body), response_extensions);
+
+        on_response(result, |result| {
+            self.layer
+                .on_response(state, &self.endpoint, response_extensions, result)
+        })
+    }
+}
+
+#[async_trait]
+impl<E, L, I, O> AsyncEndpoint<I, O> for LayeredEndpoint<E, L>
+where
+    E: AsyncEndpoint<I, O> + Sync + Send,
+    L: AsyncEndpointLayer + Sync + Send,
+    L::State: Send,
+    I: Send,
+{
+    async fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+    ) -> Result<Response<AsyncResponseBody<O>>, Error>
+    where
+        I: 'async_trait,
+    {
+        let (mut parts, body) = req.into_parts();
+        let state = self
+            .layer
+            .on_request(&self.endpoint, &mut parts, response_extensions)
+            .await?;
+
+        let result = self
+            .endpoint
+            .handle(Request::from_parts(parts, body), response_extensions)
+            .await;
+
+        on_response(result, |result| {
+            self.layer
+                .on_response(state, &self.endpoint, response_extensions, result)
+        })
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::request_log::{LoggedParam, RequestLog, RequestLogSink};
+use crate::server::{self, EndpointLayer, EndpointMetadata, LogSafety};
+use crate::SafeParams;
+use conjure_error::Error;
+use http::{request, response, Extensions};
+use std::time::Instant;
+
+/// An [`EndpointLayer`] which emits a [`RequestLog`] for every request.
+///
+/// The values of safe parameters are taken from the [`SafeParams`] response extension, and the
+/// remaining arguments reported by [`EndpointMetadata::args`] are included as redacted.
+///
+/// The logged duration covers the handling of the request up to the point the endpoint returns its
+/// response, so it does not include the time taken to write a streaming response body.
+pub struct RequestLogLayer<S> {
+    sink: S,
+}
+
+impl<S> RequestLogLayer<S> {
+    /// Creates a new `RequestLogLayer` which passes logs to the provided sink.
+    pub fn new(sink: S) -> Self {
+        RequestLogLayer { sink }
+    }
+}
+
+impl<S> EndpointLayer for RequestLogLayer<S>
+where
+    S: RequestLogSink,
+{
+    type State = Instant;
+
+    fn on_request(
+        &self,
+        _: &dyn EndpointMetadata,
+        _: &mut request::Parts,
+        _: &mut Extensions,
+    ) -> Result<Self::State, Error> {
+        Ok(Instant::now())
+    }
+
+    fn on_response(
+        &self,
+        start: Self::State,
+        endpoint: &dyn EndpointMetadata,
+        response_extensions: &mut Extensions,
+        result: Result<&mut response::Parts, &mut Error>,
+    ) {
+        let safe_params = response_extensions.get::<SafeParams>();
+
+        let mut log = RequestLog::new(
+            endpoint.service_name(),
+            endpoint.name(),
+            endpoint.method(),
+            endpoint.template(),
+            start.elapsed(),
+        );
+
+        log = match result {
+            Ok(parts) => log.with_status(parts.status),
+            Err(error) => log
+                .with_status(server::error_status(error))
+                .with_error(error),
+        };
+
+        for arg in endpoint.args() {
+            let value = match (arg.safety(), safe_params.and_then(|p| p.get(arg.name()))) {
+                (Some(LogSafety::Safe), Some(value)) => LoggedParam::Safe(value),
+                (Some(LogSafety::Safe), None) => continue,
+                _ => LoggedParam::Redacted,
+            };
+            log = log.with_param(arg.name(), value);
+        }
+
+        self.sink.log(&log.with_safe_params(safe_params));
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The authentication scheme of an HTTP endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthType {
+    /// A bearer token in the `Authorization` header.
+    Header,
+    /// A bearer token in the cookie with the specified name.
+    Cookie(&'static str),
+}
+
+/// The location of an argument in an HTTP request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParameterType {
+    /// A path parameter.
+    Path,
+    /// A query parameter with the specified name.
+    Query(&'static str),
+    /// A header parameter with the specified name.
+    Header(&'static str),
+    /// The request body.
+    Body,
+}
+
+/// The safety of a value with regards to logging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogSafety {
+    /// The value is safe to log.
+    Safe,
+    /// The value may be logged, but not as a safe value.
+    Unsafe,
+    /// The value must never be logged.
+    DoNotLog,
+}
+
+/// Metadata about an argument of an HTTP endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArgumentMetadata {
+    name: &'static str,
+    param_type: ParameterType,
+    type_: &'static str,
+    safety: Option<LogSafety>,
+    markers: &'static [&'static str],
+    tags: &'static [&'static str],
+}
+
+impl ArgumentMetadata {
+    /// Creates a new `ArgumentMetadata` with no safety, markers, or tags.
+    #[inline]
+    pub const fn new(name: &'static str, param_type: ParameterType, type_: &'static str) -> Self {
+        ArgumentMetadata {
+            name,
+            param_type,
+            type_,
+            safety: None,
+            markers: &[],
+            tags: &[],
+        }
+    }
+
+    /// Sets the argument's log safety.
+    #[inline]
+    pub const fn with_safety(mut self, safety: LogSafety) -> Self {
+        self.safety = Some(safety);
+        self
+    }
+
+    /// Sets the argument's markers.
+    #[inline]
+    pub const fn with_markers(mut self, markers: &'static [&'static str]) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Sets the argument's tags.
+    #[inline]
+    pub const fn with_tags(mut self, tags: &'static [&'static str]) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Returns the name of the argument.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the location of the argument in the request.
+    #[inline]
+    pub fn param_type(&self) -> ParameterType {
+        self.param_type
+    }
+
+    /// Returns a description of the argument's type.
+    ///
+    /// For endpoints generated from a Conjure definition this is the Conjure type of the argument
+    /// (e.g. `list<string>`), and for endpoints defined with the `conjure_endpoints` macro it is
+    /// the Rust type.
+    #[inline]
+    pub fn type_(&self) -> &'static str {
+        self.type_
+    }
+
+    /// Returns the argument's log safety, if known.
+    #[inline]
+    pub fn safety(&self) -> Option<LogSafety> {
+        self.safety
+    }
+
+    /// Returns the names of the argument's marker types.
+    #[inline]
+    pub fn markers(&self) -> &'static [&'static str] {
+        self.markers
+    }
+
+    /// Returns the argument's tags.
+    #[inline]
+    pub fn tags(&self) -> &'static [&'static str] {
+        self.tags
+    }
+}
@@ -0,0 +1,481 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::server::{
+    AsyncEndpoint, AsyncService, Endpoint, EndpointMetadata, PathSegment, Service,
+};
+use crate::PathParams;
+use conjure_error::Error;
+#[cfg(any(feature = "testing", feature = "tower"))]
+use conjure_error::NotFound;
+#[cfg(any(feature = "testing", feature = "tower"))]
+use http::header::ALLOW;
+#[cfg(any(feature = "testing", feature = "tower"))]
+use http::{HeaderValue, Response, StatusCode};
+use http::{Method, Request};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// A builder for [`Router`]s.
+pub struct RouterBuilder<E> {
+    endpoints: Vec<E>,
+}
+
+impl<E> Default for RouterBuilder<E> {
+    fn default() -> Self {
+        RouterBuilder { endpoints: vec![] }
+    }
+}
+
+impl<E> RouterBuilder<E>
+where
+    E: EndpointMetadata,
+{
+    /// Creates a new, empty builder.
+    #[inline]
+    pub fn new() -> Self {
+        RouterBuilder::default()
+    }
+
+    /// Adds an endpoint to the router.
+    pub fn endpoint(mut self, endpoint: E) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
+    /// Adds a collection of endpoints to the router.
+    pub fn endpoints<T>(mut self, endpoints: T) -> Self
+    where
+        T: IntoIterator<Item = E>,
+    {
+        self.endpoints.extend(endpoints);
+        self
+    }
+
+    /// Builds the router.
+    ///
+    /// Returns an error if an endpoint has an invalid parameter regex, or if two endpoints with the
+    /// same method have routes which conflict or are ambiguous.
+    ///
+    /// Two routes conflict if they are identical other than the names of their parameters. They
+    /// are ambiguous if a request path could match both, and neither route takes precedence over
+    /// the other. Moving from left to right through the path, literal segments take precedence
+    /// over parameters and parameters with a regex take precedence over those without. For
+    /// example, `/foo/{a:[0-9]+}` and `/foo/{b:[a-f]+}` are ambiguous, while `/foo/{bar}` and
+    /// `/foo/bar` are not.
+    pub fn build(self) -> Result<Router<E>, Error> {
+        let mut root = Node::default();
+
+        for endpoint in self.endpoints {
+            let mut node = &mut root;
+            let mut params = vec![];
+            for segment in endpoint.path() {
+                node = match segment {
+                    PathSegment::Literal(literal) => {
+                        node.literals.entry(literal.to_string()).or_default()
+                    }
+                    PathSegment::Parameter { name, regex } => {
+                        params.push(name.to_string());
+                        let regex = regex.as_deref();
+                        let idx = match node.params.iter().position(|p| p.pattern() == regex) {
+                            Some(idx) => idx,
+                            None => {
+                                // regex parameters take precedence over plain ones
+                                let idx = match regex {
+                                    Some(_) => node
+                                        .params
+                                        .iter()
+                                        .position(|p| p.pattern().is_none())
+                                        .unwrap_or(node.params.len()),
+                                    None => node.params.len(),
+                                };
+                                node.params.insert(idx, ParamNode::new(&endpoint, regex)?);
+                                idx
+                            }
+                        };
+                        &mut node.params[idx].node
+                    }
+                };
+            }
+
+            let method = endpoint.method();
+            if let Some(existing) = node
+                .endpoints
+                .iter()
+                .find(|e| e.endpoint.method() == method)
+            {
+                return Err(
+                    Error::internal_safe("conflicting routes registered with router")
+                        .with_safe_param("method", method.as_str())
+                        .with_safe_param("template", endpoint.template())
+                        .with_safe_param("endpoint", endpoint_name(&endpoint))
+                        .with_safe_param("conflictingTemplate", existing.endpoint.template())
+                        .with_safe_param("conflictingEndpoint", endpoint_name(&existing.endpoint)),
+                );
+            }
+            node.endpoints.push(Leaf { endpoint, params });
+        }
+
+        let mut routes = vec![];
+        root.collect(&mut routes);
+        for (i, a) in routes.iter().enumerate() {
+            for b in &routes[i + 1..] {
+                if a.method() == b.method() && ambiguous(a.path(), b.path()) {
+                    return Err(
+                        Error::internal_safe("ambiguous routes registered with router")
+                            .with_safe_param("method", a.method().as_str())
+                            .with_safe_param("template", a.template())
+                            .with_safe_param("endpoint", endpoint_name(*a))
+                            .with_safe_param("conflictingTemplate", b.template())
+                            .with_safe_param("conflictingEndpoint", endpoint_name(*b)),
+                    );
+                }
+            }
+        }
+
+        Ok(Router { root })
+    }
+}
+
+impl<I, O> RouterBuilder<Box<dyn Endpoint<I, O> + Sync + Send>> {
+    /// Adds all endpoints of a blocking service to the router.
+    pub fn service<T>(self, service: &T) -> Self
+    where
+        T: ?Sized + Service<I, O>,
+    {
+        self.endpoints(service.endpoints())
+    }
+}
+
+impl<I, O> RouterBuilder<Box<dyn AsyncEndpoint<I, O> + Sync + Send>> {
+    /// Adds all endpoints of an async service to the router.
+    pub fn async_service<T>(self, service: &T) -> Self
+    where
+        T: ?Sized + AsyncService<I, O>,
+    {
+        self.endpoints(service.endpoints())
+    }
+}
+
+/// A router dispatching requests to endpoints based on their method and path.
+///
+/// Routes are compiled into a trie keyed by path segment. Literal segments are matched exactly
+/// against the raw (still percent-encoded) request path segments. Parameter segments match any
+/// single nonempty segment, or only those matching the parameter's regex if one is present. A
+/// regex parameter in the final position of a template is additionally allowed to match the
+/// remainder of the request path, including `/` characters, so a template like `/files/{path:.+}`
+/// matches `/files/a/b/c`.
+pub struct Router<E> {
+    root: Node<E>,
+}
+
+impl<E> Router<E>
+where
+    E: EndpointMetadata,
+{
+    /// Returns a new builder.
+    #[inline]
+    pub fn builder() -> RouterBuilder<E> {
+        RouterBuilder::new()
+    }
+
+    /// Routes a request to an endpoint.
+    ///
+    /// If an endpoint is found, a [`PathParams`] extension containing the raw path parameters
+    /// extracted from the URI is inserted into the request, as required by
+    /// [`Endpoint::handle`] and [`AsyncEndpoint::handle`].
+    pub fn route<I>(&self, request: &mut Request<I>) -> Route<'_, E> {
+        match self.find(request.method(), request.uri().path()) {
+            Ok((endpoint, path_params)) => {
+                request.extensions_mut().insert(path_params);
+                Route::Matched(endpoint)
+            }
+            Err(allowed) if allowed.is_empty() => Route::NotFound,
+            Err(allowed) => Route::MethodNotAllowed(allowed),
+        }
+    }
+
+    fn find(&self, method: &Method, path: &str) -> Result<(&E, PathParams), Vec<Method>> {
+        let segments = path
+            .strip_prefix('/')
+            .unwrap_or(path)
+            .split('/')
+            .collect::<Vec<_>>();
+
+        let mut matches = vec![];
+        self.root.find(&segments, &mut vec![], &mut matches, method);
+
+        let mut allowed = vec![];
+        for (leaf, values) in matches {
+            match leaf {
+                Ok(leaf) => {
+                    let mut path_params = PathParams::new();
+                    for (name, value) in leaf.params.iter().zip(values) {
+                        path_params.insert(&**name, value);
+                    }
+                    return Ok((&leaf.endpoint, path_params));
+                }
+                Err(leaves) => {
+                    for leaf in leaves {
+                        let method = leaf.endpoint.method();
+                        if !allowed.contains(&method) {
+                            allowed.push(method);
+                        }
+                    }
+                }
+            }
+        }
+
+        Err(allowed)
+    }
+}
+
+/// The result of routing a request.
+pub enum Route<'a, E> {
+    /// An endpoint matched the request's method and path.
+    Matched(&'a E),
+
+    /// No endpoint matched the request's path.
+    ///
+    /// Servers should typically respond with a `404 Not Found`.
+    NotFound,
+
+    /// At least one endpoint matched the request's path, but none matched its method.
+    ///
+    /// The methods supported for the path are provided. Servers should typically respond with a
+    /// `405 Method Not Allowed` with an `Allow` header containing the methods.
+    MethodNotAllowed(Vec<Method>),
+}
+
+#[cfg(any(feature = "testing", feature = "tower"))]
+impl<'a, E> Route<'a, E> {
+    /// Returns the matched endpoint, or how the server should respond if no endpoint matched.
+    ///
+    /// A `NotFound` route responds with a [`NotFound`] error, and a `MethodNotAllowed` route with
+    /// an empty `405 Method Not Allowed` response whose `Allow` header lists the methods.
+    pub(crate) fn into_matched<B>(self, empty_body: B) -> Result<&'a E, Unmatched<B>> {
+        match self {
+            Route::Matched(endpoint) => Ok(endpoint),
+            Route::NotFound => Err(Unmatched::Error(Error::service_safe(
+                "no endpoint matched the request",
+                NotFound::new(),
+            ))),
+            Route::MethodNotAllowed(methods) => {
+                let allow = methods
+                    .iter()
+                    .map(Method::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                let mut response = Response::new(empty_body);
+                *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+                response.headers_mut().insert(
+                    ALLOW,
+                    HeaderValue::from_str(&allow).expect("methods are valid header values"),
+                );
+
+                Err(Unmatched::Response(response))
+            }
+        }
+    }
+}
+
+/// The response to a request which didn't match an endpoint.
+#[cfg(any(feature = "testing", feature = "tower"))]
+pub(crate) enum Unmatched<B> {
+    /// An error to be encoded into the response.
+    Error(Error),
+    /// The complete response.
+    Response(Response<B>),
+}
+
+fn endpoint_name<E>(endpoint: &E) -> String
+where
+    E: ?Sized + EndpointMetadata,
+{
+    format!("{}.{}", endpoint.service_name(), endpoint.name())
+}
+
+// Determines if a request path could match both routes without either taking precedence.
+fn ambiguous(a: &[PathSegment], b: &[PathSegment]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut ambiguous = false;
+    for (a, b) in a.iter().zip(b) {
+        match (a, b) {
+            (PathSegment::Literal(a), PathSegment::Literal(b)) => {
+                if a != b {
+                    return false;
+                }
+            }
+            (PathSegment::Literal(_), PathSegment::Parameter { .. })
+            | (PathSegment::Parameter { .. }, PathSegment::Literal(_)) => {
+                // the literal always wins, so the routes can't conflict at this position
+                return false;
+            }
+            (PathSegment::Parameter { regex: a, .. }, PathSegment::Parameter { regex: b, .. }) => {
+                // A regex parameter takes precedence over a plain one, but we can't tell if two
+                // distinct regexes overlap.
+                if a.is_some() && b.is_some() && a != b {
+                    ambiguous = true;
+                }
+            }
+        }
+    }
+
+    ambiguous
+}
+
+struct Node<E> {
+    literals: HashMap<String, Node<E>>,
+    params: Vec<ParamNode<E>>,
+    endpoints: Vec<Leaf<E>>,
+}
+
+impl<E> Default for Node<E> {
+    fn default() -> Self {
+        Node {
+            literals: HashMap::new(),
+            params: vec![],
+            endpoints: vec![],
+        }
+    }
+}
+
+type Match<'a, 'b, E> = (Result<&'a Leaf<E>, &'a [Leaf<E>]>, Vec<Cow<'b, str>>);
+
+impl<E> Node<E>
+where
+    E: EndpointMetadata,
+{
+    fn collect<'a>(&'a self, out: &mut Vec<&'a E>) {
+        out.extend(self.endpoints.iter().map(|l| &l.endpoint));
+        for node in self.literals.values() {
+            node.collect(out);
+        }
+        for param in &self.params {
+            param.node.collect(out);
+        }
+    }
+
+    // Matches are pushed in precedence order. A node matching the path but not the method is
+    // recorded as an `Err` so the caller can construct the allowed method list.
+    fn find<'a, 'b>(
+        &'a self,
+        segments: &[&'b str],
+        values: &mut Vec<Cow<'b, str>>,
+        out: &mut Vec<Match<'a, 'b, E>>,
+        method: &Method,
+    ) {
+        let (segment, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                self.push_match(values.clone(), out, method);
+                return;
+            }
+        };
+
+        if let Some(node) = self.literals.get(*segment) {
+            node.find(rest, values, out, method);
+        }
+
+        for param in &self.params {
+            if param.matches(segment) {
+                values.push(Cow::Borrowed(segment));
+                param.node.find(rest, values, out, method);
+                values.pop();
+            }
+
+            // regex parameters at the end of a template can consume the rest of the path
+            if !rest.is_empty() && !param.node.endpoints.is_empty() {
+                if let Some(regex) = &param.regex {
+                    let remainder = segments.join("/");
+                    if regex.is_match(&remainder) {
+                        let mut values = values.clone();
+                        values.push(Cow::Owned(remainder));
+                        param.node.push_match(values, out, method);
+                    }
+                }
+            }
+        }
+    }
+
+    fn push_match<'a, 'b>(
+        &'a self,
+        values: Vec<Cow<'b, str>>,
+        out: &mut Vec<Match<'a, 'b, E>>,
+        method: &Method,
+    ) {
+        if self.endpoints.is_empty() {
+            return;
+        }
+
+        let leaf = self
+            .endpoints
+            .iter()
+            .find(|l| l.endpoint.method() == method)
+            .ok_or(&*self.endpoints);
+        out.push((leaf, values));
+    }
+}
+
+struct ParamNode<E> {
+    pattern: Option<String>,
+    regex: Option<Regex>,
+    node: Node<E>,
+}
+
+impl<E> ParamNode<E>
+where
+    E: EndpointMetadata,
+{
+    fn new(endpoint: &E, pattern: Option<&str>) -> Result<Self, Error> {
+        let regex = match pattern {
+            Some(pattern) => {
+                let regex = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| {
+                    Error::internal_safe(e)
+                        .with_safe_param("template", endpoint.template())
+                        .with_safe_param("endpoint", endpoint_name(endpoint))
+                })?;
+                Some(regex)
+            }
+            None => None,
+        };
+
+        Ok(ParamNode {
+            pattern: pattern.map(str::to_string),
+            regex,
+            node: Node::default(),
+        })
+    }
+
+    fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    fn matches(&self, segment: &str) -> bool {
+        match &self.regex {
+            Some(regex) => regex.is_match(segment),
+            None => !segment.is_empty(),
+        }
+    }
+}
+
+struct Leaf<E> {
+    endpoint: E,
+    params: Vec<String>,
+}
@@ -0,0 +1,393 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! An adapter exposing Conjure services as a [tower] `Service`.
+//!
+//! Requires the `tower` Cargo feature.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use conjure_http::server::tower::ConjureService;
+//! use tower::ServiceBuilder;
+//!
+//! let service = ConjureService::builder()
+//!     .service(&MyServiceEndpoints::new(MyServiceHandler))
+//!     .service(&OtherServiceEndpoints::new(OtherServiceHandler))
+//!     .build()?;
+//!
+//! let service = ServiceBuilder::new()
+//!     .timeout(Duration::from_secs(30))
+//!     .concurrency_limit(100)
+//!     .service(service);
+//! ```
+//!
+//! [tower]: https://docs.rs/tower
+use crate::server::{
+    async_encode_error_with_hook, AsyncEndpoint, AsyncResponseBody, AsyncService, ErrorHook,
+    Router, RouterBuilder, Unmatched,
+};
+use bytes::{Buf, Bytes, BytesMut};
+use conjure_error::Error;
+use futures_core::Stream;
+use futures_io::AsyncWrite;
+use http::{Extensions, HeaderMap, Request, Response};
+use http_body::{Body, SizeHint};
+use std::convert::Infallible;
+use std::error;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+// The amount of data buffered by a streaming response body before it is handed to the server.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+type BoxEndpoint<B> = Box<dyn AsyncEndpoint<RequestBody<B>, ResponseWriter> + Sync + Send>;
+
+/// A builder for [`ConjureService`]s.
+pub struct ConjureServiceBuilder<B> {
+    router: RouterBuilder<BoxEndpoint<B>>,
+    error_hook: Arc<dyn ErrorHook + Sync + Send>,
+}
+
+impl<B> ConjureServiceBuilder<B> {
+    /// Adds all endpoints of an async service.
+    pub fn service<T>(mut self, service: &T) -> Self
+    where
+        T: ?Sized + AsyncService<RequestBody<B>, ResponseWriter>,
+    {
+        self.router = self.router.async_service(service);
+        self
+    }
+
+    /// Sets a hook which will be passed every error encoded into a response.
+    ///
+    /// Defaults to a no-op.
+    pub fn error_hook<H>(mut self, error_hook: H) -> Self
+    where
+        H: ErrorHook + 'static + Sync + Send,
+    {
+        self.error_hook = Arc::new(error_hook);
+        self
+    }
+
+    /// Builds the service.
+    ///
+    /// Returns an error if the routes of the registered endpoints conflict, as described by
+    /// [`RouterBuilder::build`].
+    pub fn build(self) -> Result<ConjureService<B>, Error> {
+        Ok(ConjureService {
+            router: Arc::new(self.router.build()?),
+            error_hook: self.error_hook,
+        })
+    }
+}
+
+/// A [tower] `Service` dispatching requests to the endpoints of a set of async Conjure services.
+///
+/// Requests are routed with a [`Router`]. Errors returned by endpoints are encoded into responses
+/// by [`async_encode_error`](crate::server::async_encode_error), and requests which do not match
+/// any endpoint are answered with a `404 Not Found` or `405 Method Not Allowed` response. The
+/// service itself never fails, and is always ready.
+///
+/// The extensions endpoints add to their response, like [`SafeParams`](crate::SafeParams), are
+/// copied into the extensions of the returned response so that they are available to outer layers.
+///
+/// [tower]: https://docs.rs/tower
+pub struct ConjureService<B> {
+    router: Arc<Router<BoxEndpoint<B>>>,
+    error_hook: Arc<dyn ErrorHook + Sync + Send>,
+}
+
+impl<B> Clone for ConjureService<B> {
+    fn clone(&self) -> Self {
+        ConjureService {
+            router: self.router.clone(),
+            error_hook: self.error_hook.clone(),
+        }
+    }
+}
+
+impl<B> ConjureService<B> {
+    /// Returns a new builder.
+    pub fn builder() -> ConjureServiceBuilder<B> {
+        ConjureServiceBuilder {
+            router: RouterBuilder::new(),
+            error_hook: Arc::new(|_: &Error, _| {}),
+        }
+    }
+}
+
+impl<B> ConjureService<B>
+where
+    B: Body + 'static + Sync + Send,
+    B::Error: Into<Box<dyn error::Error + Sync + Send>>,
+{
+    async fn handle(self, request: Request<B>) -> Response<ResponseBody> {
+        let mut request = request.map(RequestBody::new);
+        let route = self.router.route(&mut request);
+        let endpoint = match route.into_matched(ResponseBody::empty()) {
+            Ok(endpoint) => endpoint,
+            Err(Unmatched::Error(error)) => {
+                return async_encode_error_with_hook(&error, &*self.error_hook)
+                    .map(ResponseBody::new);
+            }
+            Err(Unmatched::Response(response)) => return response,
+        };
+
+        let mut response_extensions = Extensions::new();
+        let response = match endpoint.handle(request, &mut response_extensions).await {
+            Ok(response) => response,
+            Err(error) => async_encode_error_with_hook(&error, &*self.error_hook),
+        };
+
+        let mut response = response.map(ResponseBody::new);
+        response.extensions_mut().extend(response_extensions);
+        response
+    }
+}
+
+impl<B> tower_service::Service<Request<B>> for ConjureService<B>
+where
+    B: Body + 'static + Sync + Send,
+    B::Error: Into<Box<dyn error::Error + Sync + Send>>,
+{
+    type Response = Response<ResponseBody>;
+    type Error = Infallible;
+    type Future = ResponseFuture;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        ResponseFuture(Box::pin(self.clone().handle(req)))
+    }
+}
+
+/// The future returned by [`ConjureService`].
+pub struct ResponseFuture(Pin<Box<dyn Future<Output = Response<ResponseBody>> + Send>>);
+
+impl Future for ResponseFuture {
+    type Output = Result<Response<ResponseBody>, Infallible>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.as_mut().poll(cx).map(Ok)
+    }
+}
+
+/// The request body type passed to endpoints by [`ConjureService`].
+///
+/// It adapts an [`http_body::Body`] into the stream of bytes expected by Conjure endpoints.
+pub struct RequestBody<B> {
+    body: Pin<Box<B>>,
+}
+
+impl<B> RequestBody<B> {
+    fn new(body: B) -> Self {
+        RequestBody {
+            body: Box::pin(body),
+        }
+    }
+}
+
+impl<B> Stream for RequestBody<B>
+where
+    B: Body,
+    B::Error: Into<Box<dyn error::Error + Sync + Send>>,
+{
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.body.as_mut().poll_data(cx).map(|data| {
+            data.map(|data| match data {
+                Ok(mut data) => Ok(data.copy_to_bytes(data.remaining())),
+                Err(e) => Err(Error::internal_safe(e)),
+            })
+        })
+    }
+}
+
+/// The writer passed to streaming response bodies by [`ConjureService`].
+///
+/// Data written is buffered in chunks which are passed along as the server consumes the
+/// [`ResponseBody`].
+pub struct ResponseWriter {
+    buf: Arc<Mutex<BytesMut>>,
+}
+
+impl AsyncWrite for ResponseWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut shared = self.buf.lock().unwrap();
+        // The writer is only ever polled from ResponseBody::poll_data, which yields the pending
+        // chunk and then polls again, so no wakeup needs to be scheduled.
+        if shared.len() >= CHUNK_SIZE {
+            return Poll::Pending;
+        }
+
+        shared.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.buf.lock().unwrap().is_empty() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+/// The response body type returned by [`ConjureService`].
+pub struct ResponseBody {
+    state: State,
+}
+
+enum State {
+    Done,
+    Fixed(Bytes),
+    Streaming {
+        write: Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>,
+        buf: Arc<Mutex<BytesMut>>,
+        written: bool,
+    },
+}
+
+impl ResponseBody {
+    fn new(body: AsyncResponseBody<ResponseWriter>) -> Self {
+        let state = match body {
+            AsyncResponseBody::Empty => State::Done,
+            AsyncResponseBody::Fixed(bytes) => State::Fixed(bytes),
+            AsyncResponseBody::Streaming(body) => {
+                let buf = Arc::new(Mutex::new(BytesMut::new()));
+                let mut writer = ResponseWriter { buf: buf.clone() };
+                State::Streaming {
+                    write: Box::pin(async move { body.write_body(Pin::new(&mut writer)).await }),
+                    buf,
+                    written: false,
+                }
+            }
+        };
+
+        ResponseBody { state }
+    }
+
+    fn empty() -> Self {
+        ResponseBody { state: State::Done }
+    }
+}
+
+impl Body for ResponseBody {
+    type Data = Bytes;
+    type Error = BodyError;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let (write, buf, written) = match &mut self.state {
+            State::Done => return Poll::Ready(None),
+            State::Fixed(_) => match std::mem::replace(&mut self.state, State::Done) {
+                State::Fixed(bytes) => return Poll::Ready(Some(Ok(bytes))),
+                _ => unreachable!(),
+            },
+            State::Streaming {
+                write,
+                buf,
+                written,
+            } => (write, buf, written),
+        };
+
+        if !*written {
+            match write.as_mut().poll(cx) {
+                Poll::Ready(Ok(())) => *written = true,
+                Poll::Ready(Err(e)) => {
+                    self.state = State::Done;
+                    return Poll::Ready(Some(Err(BodyError(e))));
+                }
+                Poll::Pending => {}
+            }
+        }
+
+        let chunk = buf.lock().unwrap().split().freeze();
+        if !chunk.is_empty() {
+            Poll::Ready(Some(Ok(chunk)))
+        } else if *written {
+            self.state = State::Done;
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.state {
+            State::Done => SizeHint::with_exact(0),
+            State::Fixed(bytes) => SizeHint::with_exact(bytes.len() as u64),
+            State::Streaming { .. } => SizeHint::new(),
+        }
+    }
+}
+
+/// An error encountered while writing a streaming [`ResponseBody`].
+///
+/// The response's status and headers have already been sent when this error occurs, so the server
+/// should abort the response rather than completing it.
+#[derive(Debug)]
+pub struct BodyError(Error);
+
+impl BodyError {
+    /// Returns the underlying Conjure error.
+    pub fn error(&self) -> &Error {
+        &self.0
+    }
+
+    /// Consumes the `BodyError`, returning the underlying Conjure error.
+    pub fn into_error(self) -> Error {
+        self.0
+    }
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("error writing response body")
+    }
+}
+
+impl error::Error for BodyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.0.cause())
+    }
+}
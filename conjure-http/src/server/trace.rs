@@ -0,0 +1,64 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::server::{EndpointLayer, EndpointMetadata};
+use crate::trace::TracePropagator;
+use conjure_error::Error;
+use http::{request, response, Extensions};
+
+/// An [`EndpointLayer`] which extracts the [`TraceContext`](crate::TraceContext) propagated by
+/// clients.
+///
+/// The context is extracted from the request's headers with a [`TracePropagator`] and inserted
+/// into the request's extensions, where handlers can retrieve it with
+/// [`RequestContext::trace_context`](crate::server::RequestContext::trace_context). Requests
+/// without a valid context are passed through unchanged.
+pub struct TraceLayer<P> {
+    propagator: P,
+}
+
+impl<P> TraceLayer<P> {
+    /// Creates a new `TraceLayer` using the provided propagator.
+    pub fn new(propagator: P) -> Self {
+        TraceLayer { propagator }
+    }
+}
+
+impl<P> EndpointLayer for TraceLayer<P>
+where
+    P: TracePropagator,
+{
+    type State = ();
+
+    fn on_request(
+        &self,
+        _: &dyn EndpointMetadata,
+        request: &mut request::Parts,
+        _: &mut Extensions,
+    ) -> Result<Self::State, Error> {
+        if let Some(context) = self.propagator.extract(&request.headers) {
+            request.extensions.insert(context);
+        }
+
+        Ok(())
+    }
+
+    fn on_response(
+        &self,
+        _: Self::State,
+        _: &dyn EndpointMetadata,
+        _: &mut Extensions,
+        _: Result<&mut response::Parts, &mut Error>,
+    ) {
+    }
+}
@@ -0,0 +1,338 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! In-memory clients which dispatch requests directly to Conjure services.
+//!
+//! Requires the `testing` Cargo feature.
+//!
+//! [`LocalClient`] and [`LocalAsyncClient`] implement [`Client`] and [`AsyncClient`] by routing
+//! requests to the endpoints of blocking and async services in the same process. Requests and
+//! responses are encoded and decoded exactly as they would be over the network, including path,
+//! query, and header parameters, content negotiation, and the serialization of errors, so a
+//! generated client can be tested against a server implementation without running an HTTP server.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use conjure_http::testing::LocalClient;
+//!
+//! let client = LocalClient::builder()
+//!     .service(&MyServiceEndpoints::new(MyServiceHandler))
+//!     .build()?;
+//! let client = MyServiceClient::new(client);
+//!
+//! assert_eq!(client.get_thing("foo")?, thing);
+//! ```
+use crate::client::{decode_error_response, AsyncClient, AsyncRequestBody, Client, RequestBody};
+use crate::server::{
+    async_encode_error_with_hook, encode_error_with_hook, AsyncEndpoint, AsyncResponseBody,
+    AsyncService, Endpoint, ErrorHook, ResponseBody, Router, RouterBuilder, Service, Unmatched,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use conjure_error::Error;
+use futures_core::Stream;
+use http::{Extensions, Request, Response};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+type BoxEndpoint = Box<dyn Endpoint<LocalBody, Vec<u8>> + Sync + Send>;
+
+type BoxAsyncEndpoint = Box<dyn AsyncEndpoint<LocalBody, Vec<u8>> + Sync + Send>;
+
+/// A builder for [`LocalClient`]s.
+pub struct LocalClientBuilder {
+    router: RouterBuilder<BoxEndpoint>,
+    error_hook: Arc<dyn ErrorHook + Sync + Send>,
+}
+
+impl LocalClientBuilder {
+    /// Adds all endpoints of a blocking service.
+    pub fn service<T>(mut self, service: &T) -> Self
+    where
+        T: ?Sized + Service<LocalBody, Vec<u8>>,
+    {
+        self.router = self.router.service(service);
+        self
+    }
+
+    /// Sets a hook which will be passed every error encoded into a response.
+    ///
+    /// Defaults to a no-op.
+    pub fn error_hook<H>(mut self, error_hook: H) -> Self
+    where
+        H: ErrorHook + 'static + Sync + Send,
+    {
+        self.error_hook = Arc::new(error_hook);
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// Returns an error if the routes of the registered endpoints conflict, as described by
+    /// [`RouterBuilder::build`].
+    pub fn build(self) -> Result<LocalClient, Error> {
+        Ok(LocalClient {
+            router: Arc::new(self.router.build()?),
+            error_hook: self.error_hook,
+        })
+    }
+}
+
+/// A blocking [`Client`] which dispatches requests to the endpoints of a set of blocking services.
+///
+/// Requests are routed with a [`Router`]. Errors returned by endpoints are encoded into responses
+/// as a server would, and then decoded back into errors by [`decode_error_response`]. Requests
+/// which do not match any endpoint fail with the error a remote server's `404 Not Found` or
+/// `405 Method Not Allowed` response would produce.
+///
+/// Streaming request and response bodies are buffered in memory. An error writing a streaming
+/// body is returned directly from [`Client::send`].
+#[derive(Clone)]
+pub struct LocalClient {
+    router: Arc<Router<BoxEndpoint>>,
+    error_hook: Arc<dyn ErrorHook + Sync + Send>,
+}
+
+impl LocalClient {
+    /// Returns a new builder.
+    pub fn builder() -> LocalClientBuilder {
+        LocalClientBuilder {
+            router: RouterBuilder::new(),
+            error_hook: Arc::new(|_: &Error, _| {}),
+        }
+    }
+
+    fn handle(&self, mut request: Request<LocalBody>) -> Result<Response<Bytes>, Error> {
+        let endpoint = match self.router.route(&mut request).into_matched(Bytes::new()) {
+            Ok(endpoint) => endpoint,
+            Err(Unmatched::Error(error)) => {
+                return write_response(encode_error_with_hook(&error, &*self.error_hook));
+            }
+            Err(Unmatched::Response(response)) => return Ok(response),
+        };
+
+        let mut response_extensions = Extensions::new();
+        let response = match endpoint.handle(request, &mut response_extensions) {
+            Ok(response) => response,
+            Err(error) => encode_error_with_hook(&error, &*self.error_hook),
+        };
+
+        let mut response = write_response(response)?;
+        response.extensions_mut().extend(response_extensions);
+        Ok(response)
+    }
+}
+
+impl Client for LocalClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = LocalBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, body) = req.into_parts();
+        let body = match body {
+            RequestBody::Empty => Bytes::new(),
+            RequestBody::Fixed(body) => body,
+            RequestBody::Streaming(body) => {
+                let mut buf = vec![];
+                body.write_body(&mut buf)?;
+                Bytes::from(buf)
+            }
+        };
+
+        let response = self.handle(Request::from_parts(parts, LocalBody::new(body)))?;
+        into_client_response(response)
+    }
+}
+
+/// A builder for [`LocalAsyncClient`]s.
+pub struct LocalAsyncClientBuilder {
+    router: RouterBuilder<BoxAsyncEndpoint>,
+    error_hook: Arc<dyn ErrorHook + Sync + Send>,
+}
+
+impl LocalAsyncClientBuilder {
+    /// Adds all endpoints of an async service.
+    pub fn service<T>(mut self, service: &T) -> Self
+    where
+        T: ?Sized + AsyncService<LocalBody, Vec<u8>>,
+    {
+        self.router = self.router.async_service(service);
+        self
+    }
+
+    /// Sets a hook which will be passed every error encoded into a response.
+    ///
+    /// Defaults to a no-op.
+    pub fn error_hook<H>(mut self, error_hook: H) -> Self
+    where
+        H: ErrorHook + 'static + Sync + Send,
+    {
+        self.error_hook = Arc::new(error_hook);
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// Returns an error if the routes of the registered endpoints conflict, as described by
+    /// [`RouterBuilder::build`].
+    pub fn build(self) -> Result<LocalAsyncClient, Error> {
+        Ok(LocalAsyncClient {
+            router: Arc::new(self.router.build()?),
+            error_hook: self.error_hook,
+        })
+    }
+}
+
+/// An [`AsyncClient`] which dispatches requests to the endpoints of a set of async services.
+///
+/// It behaves identically to [`LocalClient`], other than supporting async services.
+#[derive(Clone)]
+pub struct LocalAsyncClient {
+    router: Arc<Router<BoxAsyncEndpoint>>,
+    error_hook: Arc<dyn ErrorHook + Sync + Send>,
+}
+
+impl LocalAsyncClient {
+    /// Returns a new builder.
+    pub fn builder() -> LocalAsyncClientBuilder {
+        LocalAsyncClientBuilder {
+            router: RouterBuilder::new(),
+            error_hook: Arc::new(|_: &Error, _| {}),
+        }
+    }
+
+    async fn handle(&self, mut request: Request<LocalBody>) -> Result<Response<Bytes>, Error> {
+        let endpoint = match self.router.route(&mut request).into_matched(Bytes::new()) {
+            Ok(endpoint) => endpoint,
+            Err(Unmatched::Error(error)) => {
+                let response = async_encode_error_with_hook(&error, &*self.error_hook);
+                return async_write_response(response).await;
+            }
+            Err(Unmatched::Response(response)) => return Ok(response),
+        };
+
+        let mut response_extensions = Extensions::new();
+        let response = match endpoint.handle(request, &mut response_extensions).await {
+            Ok(response) => response,
+            Err(error) => async_encode_error_with_hook(&error, &*self.error_hook),
+        };
+
+        let mut response = async_write_response(response).await?;
+        response.extensions_mut().extend(response_extensions);
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl AsyncClient for LocalAsyncClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = LocalBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, body) = req.into_parts();
+        let body = match body {
+            AsyncRequestBody::Empty => Bytes::new(),
+            AsyncRequestBody::Fixed(body) => body,
+            AsyncRequestBody::Streaming(mut body) => {
+                let mut buf = vec![];
+                body.as_mut().write_body(Pin::new(&mut buf)).await?;
+                Bytes::from(buf)
+            }
+        };
+
+        let response = self
+            .handle(Request::from_parts(parts, LocalBody::new(body)))
+            .await?;
+        into_client_response(response)
+    }
+}
+
+/// The request and response body type used by [`LocalClient`] and [`LocalAsyncClient`].
+///
+/// It implements both `Iterator` and `Stream`, yielding the entire body in a single chunk.
+pub struct LocalBody(Option<Bytes>);
+
+impl LocalBody {
+    fn new(body: Bytes) -> Self {
+        if body.is_empty() {
+            LocalBody(None)
+        } else {
+            LocalBody(Some(body))
+        }
+    }
+}
+
+impl Iterator for LocalBody {
+    type Item = Result<Bytes, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.take().map(Ok)
+    }
+}
+
+impl Stream for LocalBody {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.0.take().map(Ok))
+    }
+}
+
+fn write_response(response: Response<ResponseBody<Vec<u8>>>) -> Result<Response<Bytes>, Error> {
+    let (parts, body) = response.into_parts();
+    let body = match body {
+        ResponseBody::Empty => Bytes::new(),
+        ResponseBody::Fixed(body) => body,
+        ResponseBody::Streaming(body) => {
+            let mut buf = vec![];
+            body.write_body(&mut buf)?;
+            Bytes::from(buf)
+        }
+    };
+
+    Ok(Response::from_parts(parts, body))
+}
+
+async fn async_write_response(
+    response: Response<AsyncResponseBody<Vec<u8>>>,
+) -> Result<Response<Bytes>, Error> {
+    let (parts, body) = response.into_parts();
+    let body = match body {
+        AsyncResponseBody::Empty => Bytes::new(),
+        AsyncResponseBody::Fixed(body) => body,
+        AsyncResponseBody::Streaming(body) => {
+            let mut buf = vec![];
+            body.write_body(Pin::new(&mut buf)).await?;
+            Bytes::from(buf)
+        }
+    };
+
+    Ok(Response::from_parts(parts, body))
+}
+
+fn into_client_response(response: Response<Bytes>) -> Result<Response<LocalBody>, Error> {
+    let response = response.map(LocalBody::new);
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(decode_error_response(response))
+    }
+}
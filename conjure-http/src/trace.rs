@@ -0,0 +1,267 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Distributed trace context propagation.
+//!
+//! The types in this module are independent of any specific tracing library. A [`TraceContext`]
+//! identifies a span of a trace, a [`TracePropagator`] reads and writes it in the headers of a
+//! request, and a [`TraceContextSource`] provides the context of outgoing requests. Tracing
+//! libraries can integrate with Conjure clients and servers by implementing the latter two traits.
+use http::header::{HeaderName, HeaderValue};
+use http::HeaderMap;
+use std::sync::Arc;
+
+const TRACEPARENT: &str = "traceparent";
+const B3: &str = "b3";
+const X_B3_TRACE_ID: &str = "x-b3-traceid";
+const X_B3_SPAN_ID: &str = "x-b3-spanid";
+const X_B3_SAMPLED: &str = "x-b3-sampled";
+const X_B3_FLAGS: &str = "x-b3-flags";
+
+/// The identity of a span within a distributed trace.
+///
+/// Trace and span IDs are stored as lowercase hex strings. Trace IDs are either 16 or 32
+/// characters long, and span IDs are 16 characters long.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    trace_id: String,
+    span_id: String,
+    sampled: Option<bool>,
+}
+
+impl TraceContext {
+    /// Creates a new context, returning `None` if either ID is invalid.
+    pub fn new(trace_id: &str, span_id: &str) -> Option<Self> {
+        if !is_id(trace_id, &[16, 32]) || !is_id(span_id, &[16]) {
+            return None;
+        }
+
+        Some(TraceContext {
+            trace_id: trace_id.to_ascii_lowercase(),
+            span_id: span_id.to_ascii_lowercase(),
+            sampled: None,
+        })
+    }
+
+    /// Sets the sampling decision of the trace.
+    ///
+    /// Defaults to `None`, which defers the decision to the recipient.
+    pub fn with_sampled(mut self, sampled: Option<bool>) -> Self {
+        self.sampled = sampled;
+        self
+    }
+
+    /// Returns the trace's ID.
+    #[inline]
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    /// Returns the span's ID.
+    #[inline]
+    pub fn span_id(&self) -> &str {
+        &self.span_id
+    }
+
+    /// Returns the sampling decision of the trace, if one has been made.
+    #[inline]
+    pub fn sampled(&self) -> Option<bool> {
+        self.sampled
+    }
+
+    /// Extracts a context from headers in any of the formats supported by this crate.
+    ///
+    /// The W3C `traceparent` header is preferred, followed by B3 headers.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        W3cPropagator
+            .extract(headers)
+            .or_else(|| B3Propagator.extract(headers))
+    }
+}
+
+/// A codec for trace contexts in request headers.
+pub trait TracePropagator {
+    /// Extracts a trace context from the headers of a request.
+    ///
+    /// Returns `None` if the headers don't contain a valid context.
+    fn extract(&self, headers: &HeaderMap) -> Option<TraceContext>;
+
+    /// Injects a trace context into the headers of a request.
+    fn inject(&self, context: &TraceContext, headers: &mut HeaderMap);
+}
+
+impl<T> TracePropagator for Arc<T>
+where
+    T: ?Sized + TracePropagator,
+{
+    fn extract(&self, headers: &HeaderMap) -> Option<TraceContext> {
+        (**self).extract(headers)
+    }
+
+    fn inject(&self, context: &TraceContext, headers: &mut HeaderMap) {
+        (**self).inject(context, headers)
+    }
+}
+
+/// A [`TracePropagator`] using the [W3C Trace Context](https://www.w3.org/TR/trace-context/)
+/// `traceparent` header.
+///
+/// 16 character trace IDs are left-padded with zeros when injected.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct W3cPropagator;
+
+impl TracePropagator for W3cPropagator {
+    fn extract(&self, headers: &HeaderMap) -> Option<TraceContext> {
+        let value = headers.get(TRACEPARENT)?.to_str().ok()?;
+
+        let mut parts = value.split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+        // later versions may append additional fields
+        if !is_hex(version, &[2]) || version == "ff" || (version == "00" && parts.next().is_some())
+        {
+            return None;
+        }
+        if trace_id.len() != 32 || !is_hex(flags, &[2]) {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+
+        TraceContext::new(trace_id, span_id).map(|c| c.with_sampled(Some(flags & 1 != 0)))
+    }
+
+    fn inject(&self, context: &TraceContext, headers: &mut HeaderMap) {
+        let flags = if context.sampled() == Some(true) {
+            "01"
+        } else {
+            "00"
+        };
+        let value = format!(
+            "00-{:0>32}-{}-{}",
+            context.trace_id(),
+            context.span_id(),
+            flags
+        );
+        insert(headers, TRACEPARENT, value);
+    }
+}
+
+/// A [`TracePropagator`] using [B3](https://github.com/openzipkin/b3-propagation) headers.
+///
+/// Both the multiple header and single header encodings are extracted, with the multiple header
+/// encoding preferred. The multiple header encoding is injected.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct B3Propagator;
+
+impl B3Propagator {
+    fn extract_multi(&self, headers: &HeaderMap) -> Option<TraceContext> {
+        let trace_id = headers.get(X_B3_TRACE_ID)?.to_str().ok()?;
+        let span_id = headers.get(X_B3_SPAN_ID)?.to_str().ok()?;
+
+        let debug = matches!(headers.get(X_B3_FLAGS), Some(v) if v == "1");
+        let sampled = if debug {
+            Some(true)
+        } else {
+            match headers.get(X_B3_SAMPLED) {
+                Some(v) => Some(parse_b3_sampled(v.to_str().ok()?)?),
+                None => None,
+            }
+        };
+
+        TraceContext::new(trace_id, span_id).map(|c| c.with_sampled(sampled))
+    }
+
+    fn extract_single(&self, headers: &HeaderMap) -> Option<TraceContext> {
+        let value = headers.get(B3)?.to_str().ok()?;
+
+        let mut parts = value.split('-');
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let sampled = match parts.next() {
+            Some("d") => Some(true),
+            Some(v) => Some(parse_b3_sampled(v)?),
+            None => None,
+        };
+
+        TraceContext::new(trace_id, span_id).map(|c| c.with_sampled(sampled))
+    }
+}
+
+impl TracePropagator for B3Propagator {
+    fn extract(&self, headers: &HeaderMap) -> Option<TraceContext> {
+        self.extract_multi(headers)
+            .or_else(|| self.extract_single(headers))
+    }
+
+    fn inject(&self, context: &TraceContext, headers: &mut HeaderMap) {
+        insert(headers, X_B3_TRACE_ID, context.trace_id().to_string());
+        insert(headers, X_B3_SPAN_ID, context.span_id().to_string());
+        match context.sampled() {
+            Some(sampled) => insert(headers, X_B3_SAMPLED, (sampled as u8).to_string()),
+            None => {
+                headers.remove(X_B3_SAMPLED);
+            }
+        }
+    }
+}
+
+/// A source of the trace contexts to propagate with outgoing requests.
+///
+/// Tracing libraries typically implement this to return a context identifying the span in which
+/// the request is being made.
+///
+/// The trait is implemented for `TraceContext` itself, which always returns the same context, and
+/// for closures returning an `Option<TraceContext>`.
+pub trait TraceContextSource {
+    /// Returns the context to propagate with a request, or `None` if there is no active trace.
+    fn current(&self) -> Option<TraceContext>;
+}
+
+impl TraceContextSource for TraceContext {
+    fn current(&self) -> Option<TraceContext> {
+        Some(self.clone())
+    }
+}
+
+impl<F> TraceContextSource for F
+where
+    F: Fn() -> Option<TraceContext>,
+{
+    fn current(&self) -> Option<TraceContext> {
+        self()
+    }
+}
+
+fn parse_b3_sampled(value: &str) -> Option<bool> {
+    match value {
+        "1" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn is_hex(s: &str, lens: &[usize]) -> bool {
+    lens.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// all-zero IDs are invalid in both W3C and B3
+fn is_id(s: &str, lens: &[usize]) -> bool {
+    is_hex(s, lens) && s.bytes().any(|b| b != b'0')
+}
+
+fn insert(headers: &mut HeaderMap, name: &'static str, value: String) {
+    let value = HeaderValue::from_str(&value).expect("trace contexts are valid headers");
+    headers.insert(HeaderName::from_static(name), value);
+}
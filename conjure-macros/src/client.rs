@@ -18,9 +18,9 @@ use percent_encoding::AsciiSet;
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use std::collections::HashMap;
-use structmeta::StructMeta;
+use structmeta::{NameArgs, StructMeta};
 use syn::{
-    parse_macro_input, Error, FnArg, ItemTrait, LitStr, Meta, Pat, ReturnType, TraitItem,
+    parse_macro_input, Error, FnArg, ItemTrait, LitBool, LitStr, Meta, Pat, ReturnType, TraitItem,
     TraitItemFn, Type,
 };
 
@@ -52,12 +52,13 @@ const USERINFO: &AsciiSet = &PATH
 const COMPONENT: &AsciiSet = &USERINFO.add(b'$').add(b'%').add(b'&').add(b'+').add(b',');
 
 pub fn generate(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let params = parse_macro_input!(attr as ClientParams);
     let mut item = parse_macro_input!(item as ItemTrait);
 
-    let client = generate_client(&mut item);
+    let client = generate_client(&mut item, &params);
 
     quote! {
         #item
@@ -67,7 +68,7 @@ pub fn generate(
     .into()
 }
 
-fn generate_client(trait_: &mut ItemTrait) -> TokenStream {
+fn generate_client(trait_: &mut ItemTrait, params: &ClientParams) -> TokenStream {
     let vis = &trait_.vis;
     let trait_name = &trait_.ident;
     let type_name = Ident::new(&format!("{}Client", trait_name), trait_name.span());
@@ -102,18 +103,50 @@ fn generate_client(trait_: &mut ItemTrait) -> TokenStream {
             TraitItem::Fn(meth) => Some(meth),
             _ => None,
         })
-        .map(|m| generate_client_method(trait_name, asyncness, m));
+        .map(|m| generate_client_method(trait_name, asyncness, params, m));
 
-    quote! {
-        #vis struct #type_name<C> {
-            client: C,
+    let definition = if params.token_provider {
+        let provider = match asyncness {
+            Asyncness::Sync => quote!(TokenProvider),
+            Asyncness::Async => quote!(AsyncTokenProvider),
+        };
+        let provider_docs = format!(" `{}`.", provider);
+        quote! {
+            #vis struct #type_name<C> {
+                client: C,
+                token_provider: std::sync::Arc<dyn conjure_http::client::#provider>,
+            }
+
+            impl<C> #type_name<C> {
+                /// Creates a new client which authenticates requests with tokens from the provided
+                #[doc = #provider_docs]
+                #vis fn new(
+                    client: C,
+                    token_provider: std::sync::Arc<dyn conjure_http::client::#provider>,
+                ) -> Self {
+                    #type_name {
+                        client,
+                        token_provider,
+                    }
+                }
+            }
         }
+    } else {
+        quote! {
+            #vis struct #type_name<C> {
+                client: C,
+            }
 
-        impl<C> conjure_http::client::#service_trait<C> for #type_name<C> {
-            fn new(client: C) -> Self {
-                #type_name { client }
+            impl<C> conjure_http::client::#service_trait<C> for #type_name<C> {
+                fn new(client: C) -> Self {
+                    #type_name { client }
+                }
             }
         }
+    };
+
+    quote! {
+        #definition
 
         #impl_attrs
         impl<C> #trait_name for #type_name<C>
@@ -127,6 +160,7 @@ fn generate_client(trait_: &mut ItemTrait) -> TokenStream {
 fn generate_client_method(
     trait_name: &Ident,
     asyncness: Asyncness,
+    params: &ClientParams,
     method: &mut TraitItemFn,
 ) -> TokenStream {
     let mut endpoint_attrs = method
@@ -184,9 +218,9 @@ fn generate_client_method(
     let create_request = create_request(asyncness, &request, &request_args);
     let add_path = add_path(&request, &request_args, &endpoint);
     let add_accept = add_accept(asyncness, &request, &endpoint, &method.sig.output);
-    let add_auth = add_auth(&request, &request_args);
+    let add_auth = add_auth(&request, asyncness, params, &endpoint, &request_args);
     let add_headers = add_headers(&request, &request_args);
-    let add_endpoint = add_endpoint(trait_name, method, &endpoint, &request);
+    let add_endpoint = add_endpoint(trait_name, asyncness, method, &endpoint, &request);
     let handle_response = handle_response(asyncness, &endpoint, &response);
 
     quote! {
@@ -408,11 +442,53 @@ fn add_accept(
     }
 }
 
-fn add_auth(request: &TokenStream, args: &[ArgType]) -> TokenStream {
+fn add_auth(
+    request: &TokenStream,
+    asyncness: Asyncness,
+    params: &ClientParams,
+    endpoint: &EndpointConfig,
+    args: &[ArgType],
+) -> TokenStream {
     let mut it = args.iter().filter_map(|a| match a {
         ArgType::Auth(auth) => Some(auth),
         _ => None,
     });
+
+    if params.token_provider {
+        if let Some(param) = it.next() {
+            return Error::new_spanned(
+                &param.ident,
+                "#[auth] arguments are not allowed with a token provider; use the `auth` endpoint \
+                 parameter instead",
+            )
+            .into_compile_error();
+        }
+
+        let Some(auth) = &endpoint.auth else {
+            return quote!();
+        };
+        let cookie_name = auth.args.as_ref().and_then(|a| a.cookie_name.as_ref());
+        let token = quote!(__token);
+        let encode = encode_auth(request, cookie_name, &quote!(&#token));
+
+        let await_ = match asyncness {
+            Asyncness::Sync => quote!(),
+            Asyncness::Async => quote!(.await),
+        };
+        return quote! {
+            let #token = self.token_provider.token() #await_?;
+            #encode
+        };
+    }
+
+    if let Some(auth) = &endpoint.auth {
+        return Error::new(
+            auth.name_span,
+            "the `auth` endpoint parameter requires #[conjure_client(token_provider)]",
+        )
+        .into_compile_error();
+    }
+
     let Some(auth_param) = it.next() else {
         return quote!();
     };
@@ -423,16 +499,23 @@ fn add_auth(request: &TokenStream, args: &[ArgType]) -> TokenStream {
     }
 
     let pat = &auth_param.ident;
+    encode_auth(request, auth_param.attr.cookie_name.as_ref(), &quote!(#pat))
+}
 
-    match &auth_param.attr.cookie_name {
+fn encode_auth(
+    request: &TokenStream,
+    cookie_name: Option<&LitStr>,
+    token: &TokenStream,
+) -> TokenStream {
+    match cookie_name {
         Some(cookie_name) => {
             let prefix = format!("{}=", cookie_name.value());
             quote! {
-                conjure_http::private::encode_cookie_auth(&mut #request, #prefix, #pat);
+                conjure_http::private::encode_cookie_auth(&mut #request, #prefix, #token);
             }
         }
         None => quote! {
-            conjure_http::private::encode_header_auth(&mut #request, #pat);
+            conjure_http::private::encode_header_auth(&mut #request, #token);
         },
     }
 }
@@ -477,6 +560,7 @@ fn add_header(request: &TokenStream, arg: &Arg<ParamAttr>) -> TokenStream {
 
 fn add_endpoint(
     trait_name: &Ident,
+    asyncness: Asyncness,
     method: &TraitItemFn,
     endpoint: &EndpointConfig,
     request: &TokenStream,
@@ -484,14 +568,34 @@ fn add_endpoint(
     let service = trait_name.to_string();
     let name = method.sig.ident.to_string();
     let path = &endpoint.path;
+    let http_method = &endpoint.method;
+
+    let body = match asyncness {
+        Asyncness::Sync => quote!(RequestBody),
+        Asyncness::Async => quote!(AsyncRequestBody),
+    };
+
+    let idempotent = endpoint
+        .idempotent
+        .as_ref()
+        .map(|idempotent| quote!(.with_idempotent(#idempotent)));
 
     quote! {
-        #request.extensions_mut().insert(conjure_http::client::Endpoint::new(
-            #service,
-            std::option::Option::Some(std::env!("CARGO_PKG_VERSION")),
-            #name,
-            #path,
-        ));
+        let __replayable_body = !std::matches!(
+            #request.body(),
+            conjure_http::client::#body::Streaming(_),
+        );
+        #request.extensions_mut().insert(
+            conjure_http::client::Endpoint::new(
+                #service,
+                std::option::Option::Some(std::env!("CARGO_PKG_VERSION")),
+                #name,
+                #path,
+            )
+            .with_method(conjure_http::private::Method::#http_method)
+            .with_replayable_body(__replayable_body)
+            #idempotent,
+        );
     }
 }
 
@@ -519,11 +623,18 @@ fn handle_response(
     }
 }
 
+#[derive(StructMeta)]
+struct ClientParams {
+    token_provider: bool,
+}
+
 #[derive(StructMeta)]
 struct EndpointConfig {
     method: Ident,
     path: LitStr,
     accept: Option<Type>,
+    idempotent: Option<LitBool>,
+    auth: Option<NameArgs<Option<AuthAttr>>>,
 }
 
 enum ArgType {
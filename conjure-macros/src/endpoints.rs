@@ -5,16 +5,18 @@ use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use structmeta::StructMeta;
 use syn::{
-    parse_macro_input, Error, FnArg, GenericParam, Generics, ItemTrait, LitStr, Meta, Pat, PatType,
-    TraitItem, TraitItemFn, Type, Visibility,
+    parse_macro_input, Error, Expr, FnArg, GenericArgument, GenericParam, Generics, ItemTrait,
+    LitStr, Meta, Pat, PatType, PathArguments, ReturnType, TraitItem, TraitItemFn, Type,
+    Visibility,
 };
 
 pub fn generate(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let params = parse_macro_input!(attr as ServiceParams);
     let mut item = parse_macro_input!(item as ItemTrait);
-    let service = match Service::new(&mut item) {
+    let service = match Service::new(&mut item, params) {
         Ok(service) => service,
         Err(e) => return e.into_compile_error().into(),
     };
@@ -241,6 +243,34 @@ fn generate_endpoint_metadata(service: &Service, endpoint: &Endpoint) -> TokenSt
     let service_name = service.name.to_string();
     let name = endpoint.ident.to_string();
 
+    let auth = endpoint
+        .args
+        .iter()
+        .find_map(|arg| match arg {
+            ArgType::Auth(arg) => Some(arg),
+            _ => None,
+        })
+        .map_or_else(
+            || quote!(conjure_http::private::Option::None),
+            |arg| {
+                let auth = match &arg.params.cookie_name {
+                    Some(cookie_name) => quote!(Cookie(#cookie_name)),
+                    None => quote!(Header),
+                };
+                quote!(conjure_http::private::Option::Some(conjure_http::server::AuthType::#auth))
+            },
+        );
+
+    let args = endpoint.args.iter().filter_map(generate_argument_metadata);
+
+    let returns = endpoint.returns.as_ref().map_or_else(
+        || quote!(conjure_http::private::Option::None),
+        |ty| {
+            let ty = type_description(ty);
+            quote!(conjure_http::private::Option::Some(#ty))
+        },
+    );
+
     quote! {
         impl<T> conjure_http::server::EndpointMetadata for #struct_name<T> {
             fn method(&self) -> conjure_http::private::Method {
@@ -266,10 +296,74 @@ fn generate_endpoint_metadata(service: &Service, endpoint: &Endpoint) -> TokenSt
             fn deprecated(&self) -> conjure_http::private::Option<&str> {
                 conjure_http::private::Option::None
             }
+
+            fn auth(&self) -> conjure_http::private::Option<conjure_http::server::AuthType> {
+                #auth
+            }
+
+            fn args(&self) -> &[conjure_http::server::ArgumentMetadata] {
+                const ARGS: &[conjure_http::server::ArgumentMetadata] = &[#(#args,)*];
+                ARGS
+            }
+
+            fn returns(&self) -> conjure_http::private::Option<&str> {
+                #returns
+            }
         }
     }
 }
 
+fn generate_argument_metadata(arg: &ArgType) -> Option<TokenStream> {
+    let (param_type, ty) = match arg {
+        ArgType::Path(arg) => (quote!(Path), &arg.ty),
+        ArgType::Query(arg) => {
+            let name = &arg.params.name;
+            (quote!(Query(#name)), &arg.ty)
+        }
+        ArgType::Header(arg) => {
+            let name = &arg.params.name;
+            (quote!(Header(#name)), &arg.ty)
+        }
+        ArgType::Body(arg) => (quote!(Body), &arg.ty),
+        ArgType::Auth(_) | ArgType::Context(_) => return None,
+    };
+
+    let name = arg.ident().to_string();
+    let ty = type_description(ty);
+    let safety = if arg.safe() {
+        quote!(.with_safety(conjure_http::server::LogSafety::Safe))
+    } else {
+        quote!()
+    };
+
+    Some(quote! {
+        conjure_http::server::ArgumentMetadata::new(
+            #name,
+            conjure_http::server::ParameterType::#param_type,
+            #ty,
+        )
+        #safety
+    })
+}
+
+// Renders a type without the extra whitespace inserted by TokenStream's Display impl.
+fn type_description(ty: &Type) -> String {
+    let mut out = String::new();
+    for token in quote!(#ty).to_string().split(' ') {
+        let last = out.chars().last();
+        let tight = matches!(token, "<" | ">" | "," | "::" | "]" | ";")
+            || matches!(
+                last,
+                None | Some('<') | Some('&') | Some('[') | Some(':') | Some('\'')
+            );
+        if !tight {
+            out.push(' ');
+        }
+        out.push_str(token);
+    }
+    out
+}
+
 fn generate_endpoint_handler(service: &Service, endpoint: &Endpoint) -> TokenStream {
     let struct_name = endpoint_name(endpoint);
 
@@ -534,6 +628,11 @@ fn generate_response(
     }
 }
 
+#[derive(StructMeta)]
+struct ServiceParams {
+    request_size_limit: Option<Expr>,
+}
+
 struct Service {
     vis: Visibility,
     name: Ident,
@@ -545,7 +644,7 @@ struct Service {
 }
 
 impl Service {
-    fn new(trait_: &mut ItemTrait) -> Result<Self, Error> {
+    fn new(trait_: &mut ItemTrait, params: ServiceParams) -> Result<Self, Error> {
         let mut errors = Errors::new();
         let mut endpoints = vec![];
         for item in &trait_.items {
@@ -557,6 +656,10 @@ impl Service {
             }
         }
 
+        for endpoint in &mut endpoints {
+            endpoint.apply_request_size_limit(params.request_size_limit.as_ref());
+        }
+
         let asyncness = match Asyncness::resolve(trait_) {
             Ok(asyncness) => Some(asyncness),
             Err(e) => {
@@ -599,6 +702,7 @@ impl Service {
 struct Endpoint {
     ident: Ident,
     args: Vec<ArgType>,
+    returns: Option<Type>,
     params: EndpointParams,
     path: Vec<PathComponent>,
 }
@@ -653,10 +757,48 @@ impl Endpoint {
         Ok(Endpoint {
             ident: item.sig.ident.clone(),
             args,
+            returns: return_type(&item.sig.output),
             params: params.unwrap(),
             path: path.unwrap(),
         })
     }
+
+    // The default body deserializer implements the limit, so swap in one with the right limit
+    fn apply_request_size_limit(&mut self, service_limit: Option<&Expr>) {
+        let Some(limit) = self.params.request_size_limit.as_ref().or(service_limit) else {
+            return;
+        };
+
+        for arg in &mut self.args {
+            if let ArgType::Body(arg) = arg {
+                if arg.params.deserializer.is_none() {
+                    arg.params.deserializer = Some(syn::parse_quote! {
+                        conjure_http::server::ConjureRequestDeserializer<{ #limit }>
+                    });
+                }
+            }
+        }
+    }
+}
+
+// Extracts `T` from a `Result<T, Error>` return type, ignoring `()`.
+fn return_type(output: &ReturnType) -> Option<Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let Type::Path(path) = &**ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(Type::Tuple(tuple)) if tuple.elems.is_empty() => None,
+        GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    }
 }
 
 #[derive(StructMeta)]
@@ -664,6 +806,7 @@ struct EndpointParams {
     method: Ident,
     path: LitStr,
     produces: Option<Type>,
+    request_size_limit: Option<Expr>,
 }
 
 enum ArgType {
@@ -695,18 +838,21 @@ impl ArgType {
                 };
                 type_ = Some(ArgType::Path(Arg {
                     ident: ident.clone(),
+                    ty: (*arg.ty).clone(),
                     params: attr,
                 }));
             } else if attr.path().is_ident("query") {
                 let attr = attr.parse_args()?;
                 type_ = Some(ArgType::Query(Arg {
                     ident: ident.clone(),
+                    ty: (*arg.ty).clone(),
                     params: attr,
                 }));
             } else if attr.path().is_ident("header") {
                 let attr = attr.parse_args()?;
                 type_ = Some(ArgType::Header(Arg {
                     ident: ident.clone(),
+                    ty: (*arg.ty).clone(),
                     params: attr,
                 }));
             } else if attr.path().is_ident("auth") {
@@ -716,6 +862,7 @@ impl ArgType {
                 };
                 type_ = Some(ArgType::Auth(Arg {
                     ident: ident.clone(),
+                    ty: (*arg.ty).clone(),
                     params: attr,
                 }));
             } else if attr.path().is_ident("body") {
@@ -725,6 +872,7 @@ impl ArgType {
                 };
                 type_ = Some(ArgType::Body(Arg {
                     ident: ident.clone(),
+                    ty: (*arg.ty).clone(),
                     params: attr,
                 }));
             } else if attr.path().is_ident("context") {
@@ -734,6 +882,7 @@ impl ArgType {
                 };
                 type_ = Some(ArgType::Context(Arg {
                     ident: ident.clone(),
+                    ty: (*arg.ty).clone(),
                     params: attr,
                 }));
             }
@@ -767,6 +916,7 @@ impl ArgType {
 
 struct Arg<T> {
     ident: Ident,
+    ty: Type,
     params: T,
 }
 
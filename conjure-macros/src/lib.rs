@@ -44,6 +44,15 @@ mod path;
 /// For a trait named `MyService`, the macro will create a type named `MyServiceClient` which
 /// implements the Conjure `Client` and `MyService` traits.
 ///
+/// # Parameters
+///
+/// The `#[conjure_client]` annotation itself accepts an optional parameter:
+///
+/// * `token_provider` - If set, the client is created with an inherent `new` method taking a
+///   `TokenProvider` (or an `AsyncTokenProvider` for async clients) in addition to the HTTP
+///   client, and authenticated endpoints obtain their tokens from it rather than an `#[auth]`
+///   argument. Endpoints opt into authentication with the `auth` parameter of `#[endpoint]`.
+///
 /// # Endpoints
 ///
 /// Each method corresponds to a separate HTTP endpoint, and is expected to take `&self` and return
@@ -55,6 +64,14 @@ mod path;
 ///     make up an entire path component. Required.
 /// * `accept` - A type implementing `DeserializeResponse` which will be used to create the return
 ///     value. Defaults to returning `()`.
+/// * `idempotent` - Whether the endpoint is safe to retry. Defaults to being determined by the
+///     HTTP method.
+/// * `auth` - If set, the request is authenticated with a token from the client's
+///     token provider. Only allowed with `#[conjure_client(token_provider)]`.
+///
+///     Parameters:
+///     * `cookie_name` - The name of the cookie used if the token is to be passed via a `Cookie`
+///         header. If unset, it will be passed via an `Authorization` header instead.
 ///
 /// Each method argument must have an annotation describing the type of parameter. One of:
 ///
@@ -172,6 +189,13 @@ pub fn conjure_client(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// The trait can optionally be declared generic over the request body and response writer types by
 /// using the `#[request_body]` and `#[response_writer]` annotations on the type parameters.
 ///
+/// The `#[conjure_endpoints]` annotation itself accepts an optional parameter:
+///
+/// * `request_size_limit` - The maximum size of request bodies in bytes, applied to each `#[body]`
+///   argument using the default `ConjureRequestDeserializer`. Requests exceeding the limit are
+///   rejected with a `RequestEntityTooLarge` error. Defaults to
+///   `conjure_http::server::DEFAULT_REQUEST_SIZE_LIMIT`.
+///
 /// # Endpoints
 ///
 /// Each method corresponds to a separate HTTP endpoint, and is expected to take `&self` and return
@@ -182,6 +206,8 @@ pub fn conjure_client(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///     make up an entire path component. Required.
 /// * `produces` - A type implementing `SerializeResponse` which will be used to convert the value
 ///     returned by the method into a response. Defaults to `EmptyResponseSerializer`.
+/// * `request_size_limit` - The maximum size of the request body in bytes. Overrides the
+///   service-level limit.
 ///
 /// Each method argument must have an annotation describing the type of parameter. One of:
 ///
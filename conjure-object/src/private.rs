@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 pub use educe::Educe;
+pub use lazy_static::lazy_static;
 use ordered_float::OrderedFloat;
 use serde::de::{self, IntoDeserializer};
 use std::cmp::Ordering;
@@ -19,6 +20,8 @@ use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::{fmt, mem};
 
+use crate::SafeLong;
+
 pub trait DoubleOps {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering>;
 
@@ -166,6 +169,10 @@ where
     }
 }
 
+pub const fn safe_long(value: i64) -> SafeLong {
+    SafeLong::new_const(value)
+}
+
 pub fn valid_enum_variant(s: &str) -> bool {
     if s.is_empty() {
         return false;
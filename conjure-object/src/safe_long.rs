@@ -57,6 +57,15 @@ impl SafeLong {
             Err(BoundsError(()))
         }
     }
+
+    // Used by generated constants, whose values are validated at code generation time.
+    pub(crate) const fn new_const(value: i64) -> SafeLong {
+        assert!(
+            value > -(1 << 53) && value < (1 << 53),
+            "value out of range"
+        );
+        SafeLong(value)
+    }
 }
 
 impl Deref for SafeLong {
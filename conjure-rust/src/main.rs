@@ -32,6 +32,9 @@ struct Args {
     #[clap(long = "useStagedBuilders")]
     /// Generate compile-time safe builders to ensure all required attributes are set
     use_staged_builders: bool,
+    #[clap(long = "useTokenProvider")]
+    /// Generate clients which obtain bearer tokens from a token provider
+    use_token_provider: bool,
     #[clap(long = "stripPrefix", value_name = "prefix")]
     /// Strip a prefix from types's package paths
     strip_prefix: Option<String>,
@@ -70,7 +73,8 @@ fn main() {
     let mut config = conjure_codegen::Config::new();
     config
         .exhaustive(args.exhaustive)
-        .staged_builders(args.use_staged_builders);
+        .staged_builders(args.use_staged_builders)
+        .token_provider(args.use_token_provider);
     if let Some(prefix) = args.strip_prefix {
         config.strip_prefix(prefix);
     }
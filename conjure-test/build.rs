@@ -4,6 +4,8 @@ use std::path::PathBuf;
 fn main() {
     let input = "test-ir.json";
     println!("cargo:rerun-if-changed={}", input);
+    let extern_input = "test-extern-ir.json";
+    println!("cargo:rerun-if-changed={}", extern_input);
 
     let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure");
     conjure_codegen::Config::new()
@@ -17,4 +19,36 @@ fn main() {
         .staged_builders(true)
         .generate_files(input, output)
         .unwrap();
+
+    let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure-smile");
+    conjure_codegen::Config::new()
+        .strip_prefix("com.palantir.conjure".to_string())
+        .prefer_smile(true)
+        .generate_files(input, output)
+        .unwrap();
+
+    let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure-token-provider");
+    conjure_codegen::Config::new()
+        .strip_prefix("com.palantir.conjure".to_string())
+        .token_provider(true)
+        .generate_files(input, output)
+        .unwrap();
+
+    let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure-extern");
+    conjure_codegen::Config::new()
+        .strip_prefix("com.palantir.ext".to_string())
+        .extern_package("com.palantir.conjure", "crate::types")
+        .external_type(
+            "java.lang.Long",
+            conjure_codegen::ExternalType::new("i64").copy(true),
+        )
+        .external_type(
+            "com.palantir.ext.Id",
+            conjure_codegen::ExternalType::new("conjure_object::Uuid")
+                .plain(true)
+                .copy(true),
+        )
+        .additional_ir_file(input)
+        .generate_files(extern_input, output)
+        .unwrap();
 }
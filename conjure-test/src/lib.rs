@@ -25,3 +25,18 @@ mod types {
 mod staged_types {
     include!(concat!(env!("OUT_DIR"), "/conjure-staged/mod.rs"));
 }
+
+#[allow(dead_code, clippy::all)]
+mod smile_types {
+    include!(concat!(env!("OUT_DIR"), "/conjure-smile/mod.rs"));
+}
+
+#[allow(dead_code, clippy::all)]
+mod token_provider_types {
+    include!(concat!(env!("OUT_DIR"), "/conjure-token-provider/mod.rs"));
+}
+
+#[allow(dead_code, clippy::all)]
+mod extern_types {
+    include!(concat!(env!("OUT_DIR"), "/conjure-extern/mod.rs"));
+}
@@ -0,0 +1,258 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::recording;
+use async_trait::async_trait;
+use conjure_error::{Error, ErrorKind};
+use conjure_http::server::{
+    AsyncAuthLayer, AsyncAuthValidator, AuthLayer, AuthValidator, ConjureResponseSerializer,
+    EndpointMetadata, LayeredService, RequestContext,
+};
+use conjure_macros::{conjure_endpoints, endpoint};
+use conjure_object::BearerToken;
+use http::header::{HeaderName, AUTHORIZATION, COOKIE};
+use http::Request;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Principal(String);
+
+#[derive(Default)]
+struct TestValidator {
+    calls: AtomicUsize,
+}
+
+impl AuthValidator for TestValidator {
+    type Principal = Principal;
+
+    fn validate(
+        &self,
+        _: &dyn EndpointMetadata,
+        token: &BearerToken,
+    ) -> Result<Option<Self::Principal>, Error> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        match token.as_str() {
+            "valid" => Ok(Some(Principal("user".to_string()))),
+            "unavailable" => Err(Error::unavailable_safe("validator unavailable")),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncAuthValidator for TestValidator {
+    type Principal = Principal;
+
+    async fn validate(
+        &self,
+        endpoint: &(dyn EndpointMetadata + Sync),
+        token: &BearerToken,
+    ) -> Result<Option<Self::Principal>, Error> {
+        AuthValidator::validate(self, endpoint, token)
+    }
+}
+
+#[conjure_endpoints]
+trait AuthTestService {
+    #[endpoint(method = GET, path = "/test/header", produces = ConjureResponseSerializer)]
+    fn header(
+        &self,
+        #[auth] auth: BearerToken,
+        #[context] context: RequestContext<'_>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/test/cookie", produces = ConjureResponseSerializer)]
+    fn cookie(
+        &self,
+        #[auth(cookie_name = "session")] auth: BearerToken,
+        #[context] context: RequestContext<'_>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/test/unauthenticated")]
+    fn unauthenticated(&self) -> Result<(), Error>;
+}
+
+#[conjure_endpoints]
+#[async_trait]
+trait AsyncAuthTestService {
+    #[endpoint(method = GET, path = "/test/header", produces = ConjureResponseSerializer)]
+    async fn header(
+        &self,
+        #[auth] auth: BearerToken,
+        #[context] context: RequestContext<'_>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/test/unauthenticated")]
+    async fn unauthenticated(&self) -> Result<(), Error>;
+}
+
+struct AuthTestServiceHandler;
+
+impl AuthTestServiceHandler {
+    fn principal(&self, context: RequestContext<'_>) -> Result<String, Error> {
+        let principal = context
+            .principal::<Principal>()
+            .ok_or_else(|| Error::internal_safe("missing principal"))?;
+        Ok(principal.0.clone())
+    }
+}
+
+impl AuthTestService for AuthTestServiceHandler {
+    fn header(&self, _: BearerToken, context: RequestContext<'_>) -> Result<String, Error> {
+        self.principal(context)
+    }
+
+    fn cookie(&self, _: BearerToken, context: RequestContext<'_>) -> Result<String, Error> {
+        self.principal(context)
+    }
+
+    fn unauthenticated(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncAuthTestService for AuthTestServiceHandler {
+    async fn header(&self, _: BearerToken, context: RequestContext<'_>) -> Result<String, Error> {
+        self.principal(context)
+    }
+
+    async fn unauthenticated(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+fn request(header: Option<(HeaderName, &str)>) -> Request<()> {
+    let mut request = Request::new(());
+    if let Some((name, value)) = header {
+        request.headers_mut().insert(name, value.parse().unwrap());
+    }
+    request
+}
+
+fn handle(
+    validator: &Arc<TestValidator>,
+    name: &str,
+    header: Option<(HeaderName, &str)>,
+) -> Result<String, Error> {
+    let service = LayeredService::new(
+        AuthTestServiceEndpoints::new(AuthTestServiceHandler),
+        AuthLayer::new(validator.clone()),
+    );
+    recording::handle(&service, name, request(header))
+}
+
+fn async_handle(
+    validator: &Arc<TestValidator>,
+    name: &str,
+    header: Option<(HeaderName, &str)>,
+) -> Result<String, Error> {
+    let service = LayeredService::new(
+        AsyncAuthTestServiceEndpoints::new(AuthTestServiceHandler),
+        AsyncAuthLayer::new(validator.clone()),
+    );
+    recording::async_handle(&service, name, request(header))
+}
+
+fn assert_permission_denied(result: Result<String, Error>) {
+    match result.unwrap_err().kind() {
+        ErrorKind::Service(e) => assert_eq!(e.error_name(), "Default:PermissionDenied"),
+        _ => panic!("expected a service error"),
+    }
+}
+
+#[test]
+fn header_auth() {
+    let validator = Arc::new(TestValidator::default());
+
+    let body = handle(&validator, "header", Some((AUTHORIZATION, "Bearer valid"))).unwrap();
+    assert_eq!(body, r#""user""#);
+
+    assert_permission_denied(handle(
+        &validator,
+        "header",
+        Some((AUTHORIZATION, "Bearer invalid")),
+    ));
+    assert_eq!(validator.calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn cookie_auth() {
+    let validator = Arc::new(TestValidator::default());
+
+    let body = handle(&validator, "cookie", Some((COOKIE, "session=valid"))).unwrap();
+    assert_eq!(body, r#""user""#);
+
+    assert_permission_denied(handle(
+        &validator,
+        "cookie",
+        Some((COOKIE, "session=invalid")),
+    ));
+}
+
+#[test]
+fn missing_token() {
+    let validator = Arc::new(TestValidator::default());
+
+    assert_permission_denied(handle(&validator, "header", None));
+    assert_eq!(validator.calls.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn validator_error() {
+    let validator = Arc::new(TestValidator::default());
+
+    let error = handle(
+        &validator,
+        "header",
+        Some((AUTHORIZATION, "Bearer unavailable")),
+    )
+    .unwrap_err();
+    match error.kind() {
+        ErrorKind::Unavailable(_) => {}
+        _ => panic!("expected an unavailable error"),
+    }
+}
+
+#[test]
+fn unauthenticated_endpoint() {
+    let validator = Arc::new(TestValidator::default());
+
+    handle(&validator, "unauthenticated", None).unwrap();
+    assert_eq!(validator.calls.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn async_header_auth() {
+    let validator = Arc::new(TestValidator::default());
+
+    let body = async_handle(&validator, "header", Some((AUTHORIZATION, "Bearer valid"))).unwrap();
+    assert_eq!(body, r#""user""#);
+
+    assert_permission_denied(async_handle(
+        &validator,
+        "header",
+        Some((AUTHORIZATION, "Bearer invalid")),
+    ));
+    assert_permission_denied(async_handle(&validator, "header", None));
+    assert_eq!(validator.calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn async_unauthenticated_endpoint() {
+    let validator = Arc::new(TestValidator::default());
+
+    async_handle(&validator, "unauthenticated", None).unwrap();
+    assert_eq!(validator.calls.load(Ordering::SeqCst), 0);
+}
@@ -15,19 +15,22 @@
 use crate::test::RemoteBody;
 use crate::types::*;
 use async_trait::async_trait;
-use conjure_error::Error;
+use conjure_error::{Error, ErrorCode, ErrorKind};
 use conjure_http::client::{
-    AsyncClient, AsyncRequestBody, AsyncService, AsyncWriteBody, Client,
-    ConjureResponseDeserializer, DisplaySeqHeaderEncoder, DisplaySeqParamEncoder, RequestBody,
-    Service, WriteBody,
+    async_decode_error_response, decode_error_response, is_idempotent, AsyncClient,
+    AsyncDeserializeResponse, AsyncRequestBody, AsyncService, AsyncWriteBody, Client,
+    ConjureResponseDeserializer, DeserializeResponse, DisplaySeqHeaderEncoder,
+    DisplaySeqParamEncoder, Endpoint, RequestBody, Service, WriteBody,
 };
 use conjure_macros::{conjure_client, endpoint};
-use conjure_object::{BearerToken, ResourceIdentifier};
+use conjure_object::{Any, BearerToken, ResourceIdentifier};
 use futures::executor;
 use http::header::CONTENT_TYPE;
 use http::{HeaderMap, Method, Request, Response, StatusCode};
 use std::collections::{BTreeMap, BTreeSet};
 use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
 
 struct StreamingBody<'a>(&'a [u8]);
 
@@ -58,6 +61,7 @@ impl AsyncWriteBody<Vec<u8>> for StreamingBody<'_> {
 enum TestBody<T = Vec<u8>> {
     Empty,
     Json(String),
+    Smile(Vec<u8>),
     Streaming(T),
 }
 
@@ -129,6 +133,11 @@ impl<'b> Client for &'b TestClient {
                 .header(CONTENT_TYPE, "application/json")
                 .body(RemoteBody(json.as_bytes().to_vec()))
                 .unwrap()),
+            TestBody::Smile(buf) => Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "application/x-jackson-smile")
+                .body(RemoteBody(buf.clone()))
+                .unwrap()),
             TestBody::Streaming(buf) => Ok(Response::builder()
                 .status(StatusCode::OK)
                 .header(CONTENT_TYPE, "application/octet-stream")
@@ -174,6 +183,11 @@ impl AsyncClient for &'_ TestClient {
                 .header(CONTENT_TYPE, "application/json")
                 .body(RemoteBody(json.as_bytes().to_vec()))
                 .unwrap()),
+            TestBody::Smile(buf) => Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "application/x-jackson-smile")
+                .body(RemoteBody(buf.clone()))
+                .unwrap()),
             TestBody::Streaming(buf) => Ok(Response::builder()
                 .status(StatusCode::OK)
                 .header(CONTENT_TYPE, "application/octet-stream")
@@ -492,6 +506,37 @@ fn json_response() {
     check!(client, client.json_response(), "hello world");
 }
 
+#[test]
+fn smile_response() {
+    let client = TestClient::new(Method::GET, "/test/jsonResponse")
+        .header("Accept", "application/json")
+        .response(TestBody::Smile(
+            conjure_serde::smile::to_vec("hello world").unwrap(),
+        ));
+    check!(client, client.json_response(), "hello world");
+}
+
+#[test]
+fn prefer_smile_response() {
+    let raw_client = TestClient::new(Method::GET, "/test/jsonResponse")
+        .header(
+            "Accept",
+            "application/x-jackson-smile, application/json;q=0.9",
+        )
+        .response(TestBody::Smile(
+            conjure_serde::smile::to_vec("hello world").unwrap(),
+        ));
+
+    let client = crate::smile_types::TestServiceClient::new(&raw_client);
+    assert_eq!(client.json_response().unwrap(), "hello world");
+
+    let client = crate::smile_types::TestServiceAsyncClient::new(&raw_client);
+    assert_eq!(
+        executor::block_on(client.json_response()).unwrap(),
+        "hello world"
+    );
+}
+
 #[test]
 fn optional_json_response() {
     let client = TestClient::new(Method::GET, "/test/optionalJsonResponse")
@@ -629,3 +674,311 @@ fn cookie_auth() {
         client.cookie_auth(&BearerToken::new("fizzbuzz").unwrap())
     );
 }
+
+fn check_error_response<F>(
+    status: StatusCode,
+    headers: &[(&'static str, &str)],
+    body: &[u8],
+    check: F,
+) where
+    F: Fn(&Error),
+{
+    let response = || {
+        let mut builder = Response::builder().status(status);
+        for (key, value) in headers {
+            builder = builder.header(*key, *value);
+        }
+        builder.body(RemoteBody(body.to_vec())).unwrap()
+    };
+
+    check(&decode_error_response(response()));
+    check(&executor::block_on(async_decode_error_response(response())));
+}
+
+fn check_internal_error(error: &Error, status: u16) {
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::Internal),
+        _ => panic!("expected a service error"),
+    }
+    assert_eq!(error.safe_params()["status"], Any::new(status).unwrap());
+}
+
+#[test]
+fn decode_service_error() {
+    let body = br#"{
+        "errorCode": "NOT_FOUND",
+        "errorName": "Default:NotFound",
+        "errorInstanceId": "5b1c0a25-3d1e-4a53-9e1c-67e4a5d09b2a",
+        "parameters": {"foo": "bar"}
+    }"#;
+    check_error_response(
+        StatusCode::NOT_FOUND,
+        &[("Content-Type", "application/json")],
+        body,
+        |error| {
+            match error.kind() {
+                ErrorKind::Service(e) => {
+                    assert_eq!(*e.error_code(), ErrorCode::NotFound);
+                    assert_eq!(e.error_name(), "Default:NotFound");
+                    assert_eq!(e.parameters()["foo"], "bar");
+                }
+                _ => panic!("expected a service error"),
+            }
+            assert_eq!(error.safe_params()["status"], Any::new(404u16).unwrap());
+        },
+    );
+}
+
+#[test]
+fn decode_response_content_type_params() {
+    let response = |content_type: &str| {
+        Response::builder()
+            .header(CONTENT_TYPE, content_type)
+            .body(RemoteBody(br#""hello world""#.to_vec()))
+            .unwrap()
+    };
+
+    let deserialize = |content_type| -> Result<String, Error> {
+        <ConjureResponseDeserializer as DeserializeResponse<_, _>>::deserialize(response(
+            content_type,
+        ))
+    };
+    let async_deserialize = |content_type| -> Result<String, Error> {
+        let response = response(content_type);
+        executor::block_on(<ConjureResponseDeserializer as AsyncDeserializeResponse<
+            _,
+            _,
+        >>::deserialize(response))
+    };
+
+    for content_type in &["application/json; charset=utf-8", "Application/Json"] {
+        assert_eq!(deserialize(content_type).unwrap(), "hello world");
+        assert_eq!(async_deserialize(content_type).unwrap(), "hello world");
+    }
+    deserialize("text/plain").err().unwrap();
+    async_deserialize("text/plain").err().unwrap();
+
+    let body = br#"{
+        "errorCode": "NOT_FOUND",
+        "errorName": "Default:NotFound",
+        "errorInstanceId": "5b1c0a25-3d1e-4a53-9e1c-67e4a5d09b2a",
+        "parameters": {}
+    }"#;
+    check_error_response(
+        StatusCode::NOT_FOUND,
+        &[("Content-Type", "application/json;charset=UTF-8")],
+        body,
+        |error| match error.kind() {
+            ErrorKind::Service(e) => assert_eq!(e.error_name(), "Default:NotFound"),
+            _ => panic!("expected a service error"),
+        },
+    );
+}
+
+#[test]
+fn decode_throttle_error() {
+    check_error_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        &[("Retry-After", "15")],
+        b"",
+        |error| match error.kind() {
+            ErrorKind::Throttle(e) => assert_eq!(e.duration(), Some(Duration::from_secs(15))),
+            _ => panic!("expected a throttle error"),
+        },
+    );
+
+    check_error_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        &[("Retry-After", "Wed, 21 Oct 2015 07:28:00 GMT")],
+        b"",
+        |error| match error.kind() {
+            ErrorKind::Throttle(e) => assert_eq!(e.duration(), None),
+            _ => panic!("expected a throttle error"),
+        },
+    );
+}
+
+#[test]
+fn decode_unavailable_error() {
+    check_error_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        &[],
+        b"",
+        |error| match error.kind() {
+            ErrorKind::Unavailable(_) => {}
+            _ => panic!("expected an unavailable error"),
+        },
+    );
+}
+
+#[test]
+fn decode_malformed_error() {
+    check_error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &[("Content-Type", "text/plain")],
+        b"something went wrong",
+        |error| {
+            check_internal_error(error, 500);
+            assert_eq!(
+                error.unsafe_params()["body"],
+                Any::new("something went wrong").unwrap()
+            );
+        },
+    );
+
+    check_error_response(
+        StatusCode::BAD_REQUEST,
+        &[("Content-Type", "application/json")],
+        br#"{"foo":"bar"}"#,
+        |error| check_internal_error(error, 400),
+    );
+
+    check_error_response(
+        StatusCode::BAD_REQUEST,
+        &[("Content-Type", "application/json")],
+        &vec![b' '; 2 * 1024 * 1024],
+        |error| check_internal_error(error, 400),
+    );
+}
+
+#[derive(Default)]
+struct EndpointClient(Mutex<Option<Endpoint>>);
+
+impl EndpointClient {
+    fn endpoint(&self) -> Endpoint {
+        self.0.lock().unwrap().take().unwrap()
+    }
+}
+
+impl Client for &'_ EndpointClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = RemoteBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        *self.0.lock().unwrap() = req.extensions().get::<Endpoint>().cloned();
+        Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(RemoteBody(vec![]))
+            .unwrap())
+    }
+}
+
+#[async_trait]
+impl AsyncClient for &'_ EndpointClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = RemoteBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        *self.0.lock().unwrap() = req.extensions().get::<Endpoint>().cloned();
+        Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(RemoteBody(vec![]))
+            .unwrap())
+    }
+}
+
+#[test]
+fn generated_endpoint_extension() {
+    let raw_client = EndpointClient::default();
+    let client = TestServiceClient::new(&raw_client);
+
+    client.empty_request().unwrap();
+    let endpoint = raw_client.endpoint();
+    assert_eq!(endpoint.service(), "TestService");
+    assert_eq!(endpoint.name(), "emptyRequest");
+    assert_eq!(endpoint.path(), "/test/emptyRequest");
+    assert_eq!(endpoint.method(), Some(&Method::POST));
+    assert!(!endpoint.idempotent());
+    assert!(endpoint.replayable_body());
+    assert!(endpoint.tags().is_empty());
+
+    client.limited_json_request("hi").unwrap();
+    let endpoint = raw_client.endpoint();
+    assert!(endpoint.replayable_body());
+    assert_eq!(endpoint.tags(), &["server-request-size-limit:16"]);
+
+    client.json_response().unwrap_err();
+    let endpoint = raw_client.endpoint();
+    assert_eq!(endpoint.method(), Some(&Method::GET));
+    assert!(endpoint.idempotent());
+
+    client.streaming_request(StreamingBody(b"hello")).unwrap();
+    let endpoint = raw_client.endpoint();
+    assert!(!endpoint.replayable_body());
+
+    let client = TestServiceAsyncClient::new(&raw_client);
+    executor::block_on(client.streaming_request(StreamingBody(b"hello"))).unwrap();
+    let endpoint = raw_client.endpoint();
+    assert_eq!(endpoint.method(), Some(&Method::POST));
+    assert!(!endpoint.replayable_body());
+}
+
+#[conjure_client]
+trait EndpointExtensionService {
+    #[endpoint(method = GET, path = "/get")]
+    fn get(&self) -> Result<(), Error>;
+
+    #[endpoint(method = POST, path = "/post", idempotent = true)]
+    fn post(&self, #[body] body: &str) -> Result<(), Error>;
+
+    #[endpoint(method = PUT, path = "/put", idempotent = false)]
+    fn put(&self) -> Result<(), Error>;
+}
+
+#[test]
+fn custom_endpoint_extension() {
+    let raw_client = EndpointClient::default();
+    let client = EndpointExtensionServiceClient::new(&raw_client);
+
+    client.get().unwrap();
+    let endpoint = raw_client.endpoint();
+    assert_eq!(endpoint.service(), "EndpointExtensionService");
+    assert_eq!(endpoint.name(), "get");
+    assert_eq!(endpoint.path(), "/get");
+    assert_eq!(endpoint.method(), Some(&Method::GET));
+    assert!(endpoint.idempotent());
+    assert!(endpoint.replayable_body());
+
+    client.post("hi").unwrap();
+    let endpoint = raw_client.endpoint();
+    assert_eq!(endpoint.method(), Some(&Method::POST));
+    assert!(endpoint.idempotent());
+    assert!(endpoint.replayable_body());
+
+    client.put().unwrap();
+    let endpoint = raw_client.endpoint();
+    assert_eq!(endpoint.method(), Some(&Method::PUT));
+    assert!(!endpoint.idempotent());
+}
+
+#[test]
+fn request_idempotency() {
+    let parts = |method, endpoint: Option<Endpoint>| {
+        let mut request = Request::new(());
+        *request.method_mut() = method;
+        if let Some(endpoint) = endpoint {
+            request.extensions_mut().insert(endpoint);
+        }
+        request.into_parts().0
+    };
+    let endpoint = || Endpoint::new("TestService", None, "test", "/test");
+
+    assert!(endpoint().replayable_body());
+    assert!(is_idempotent(&parts(Method::GET, None)));
+    assert!(!is_idempotent(&parts(Method::POST, None)));
+    assert!(is_idempotent(&parts(Method::PUT, Some(endpoint()))));
+    assert!(is_idempotent(&parts(
+        Method::POST,
+        Some(endpoint().with_idempotent(true))
+    )));
+    assert!(!is_idempotent(&parts(
+        Method::GET,
+        Some(endpoint().with_idempotent(false))
+    )));
+}
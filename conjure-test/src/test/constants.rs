@@ -0,0 +1,52 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::types::constants::*;
+use conjure_object::{BearerToken, DateTime, ResourceIdentifier, SafeLong, Utc, Uuid};
+
+#[test]
+#[allow(clippy::assertions_on_constants)]
+fn const_constants() {
+    const STRING: &str = STRING_CONSTANT;
+    const INTEGER: i32 = INTEGER_CONSTANT;
+    const SAFELONG: SafeLong = SAFELONG_CONSTANT;
+    const UUID: Uuid = UUID_CONSTANT;
+
+    assert_eq!(STRING, "hello \"world\"");
+    assert_eq!(INTEGER, -123);
+    assert_eq!(DOUBLE_CONSTANT, 1.5);
+    assert!(NAN_CONSTANT.is_nan());
+    assert_eq!(SAFELONG, SafeLong::max_value());
+    assert!(BOOLEAN_CONSTANT);
+    assert_eq!(
+        UUID,
+        "123e4567-e89b-12d3-a456-426614174000"
+            .parse::<Uuid>()
+            .unwrap(),
+    );
+}
+
+#[test]
+fn lazy_constants() {
+    assert_eq!(
+        *RID_CONSTANT,
+        ResourceIdentifier::new("ri.service.instance.type.locator").unwrap(),
+    );
+    assert_eq!(
+        *DATETIME_CONSTANT,
+        DateTime::parse_from_rfc3339("2023-01-02T02:04:05.678Z")
+            .unwrap()
+            .with_timezone(&Utc),
+    );
+    assert_eq!(*BEARERTOKEN_CONSTANT, BearerToken::new("token").unwrap());
+}
@@ -0,0 +1,174 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::recording::{self, token, RecordingClient};
+use crate::types::*;
+use conjure_error::{Error, ErrorCode, ErrorKind};
+use conjure_http::client::{AsyncService, DeadlineAsyncClient, DeadlineClient, Service};
+use conjure_http::deadline::DEADLINE_HEADER;
+use conjure_http::server::{
+    ConjureResponseSerializer, DeadlineLayer, LayeredService, RequestContext,
+};
+use conjure_http::Deadline;
+use conjure_macros::{conjure_endpoints, endpoint};
+use futures::executor;
+use futures::future;
+use http::{Extensions, Request};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn assert_timeout<T>(result: Result<T, Error>) {
+    match result.err().unwrap().kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::Timeout),
+        _ => panic!("expected a service error"),
+    }
+}
+
+#[test]
+fn client_propagates_deadline() {
+    let client = RecordingClient::default();
+    let deadline = Deadline::after(Duration::from_secs(60));
+    TestServiceClient::new(DeadlineClient::new(client.clone(), deadline))
+        .header_auth(&token())
+        .unwrap();
+
+    let requests = client.take();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].deadline, Some(deadline));
+    let millis = requests[0].headers[DEADLINE_HEADER]
+        .to_str()
+        .unwrap()
+        .parse::<u64>()
+        .unwrap();
+    assert!(millis > 50_000 && millis <= 60_000, "{}", millis);
+}
+
+#[test]
+fn client_expired_deadline() {
+    let client = RecordingClient::default();
+    let deadline = Deadline::new(Instant::now());
+    assert_timeout(
+        TestServiceClient::new(DeadlineClient::new(client.clone(), deadline)).json_response(),
+    );
+
+    assert!(client.take().is_empty());
+}
+
+#[test]
+fn async_client_propagates_deadline() {
+    let client = RecordingClient::default();
+    let deadline = Deadline::after(Duration::from_secs(60));
+    let client_ = DeadlineAsyncClient::new(client.clone(), deadline, |_| future::pending());
+    executor::block_on(TestServiceAsyncClient::new(client_).header_auth(&token())).unwrap();
+
+    let requests = client.take();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].deadline, Some(deadline));
+    assert!(requests[0].headers.contains_key(DEADLINE_HEADER));
+}
+
+#[test]
+fn async_client_in_flight_deadline() {
+    let client = RecordingClient::hanging();
+    let deadline = Deadline::after(Duration::from_secs(60));
+    let client_ = DeadlineAsyncClient::new(client.clone(), deadline, |_| future::ready(()));
+    assert_timeout(executor::block_on(
+        TestServiceAsyncClient::new(client_).json_response(),
+    ));
+
+    assert_eq!(client.take().len(), 1);
+}
+
+#[conjure_endpoints]
+trait DeadlineTestService {
+    #[endpoint(method = GET, path = "/test/deadline", produces = ConjureResponseSerializer)]
+    fn deadline(&self, #[context] context: RequestContext<'_>) -> Result<bool, Error>;
+}
+
+struct DeadlineTestServiceHandler;
+
+impl DeadlineTestService for DeadlineTestServiceHandler {
+    fn deadline(&self, context: RequestContext<'_>) -> Result<bool, Error> {
+        Ok(context.deadline().is_some())
+    }
+}
+
+fn handle(layered: bool, header: Option<&str>) -> Result<String, Error> {
+    let service = DeadlineTestServiceEndpoints::new(DeadlineTestServiceHandler);
+    let mut request = Request::new(());
+    if let Some(header) = header {
+        request
+            .headers_mut()
+            .insert(DEADLINE_HEADER, header.parse().unwrap());
+    }
+
+    if layered {
+        recording::handle(
+            &LayeredService::new(service, DeadlineLayer::new()),
+            "deadline",
+            request,
+        )
+    } else {
+        recording::handle(&service, "deadline", request)
+    }
+}
+
+#[test]
+fn server_deadline() {
+    assert_eq!(handle(true, Some("60000")).unwrap(), "true");
+    assert_eq!(handle(true, None).unwrap(), "false");
+    assert_eq!(handle(true, Some("bogus")).unwrap(), "false");
+}
+
+#[test]
+fn server_expired_deadline() {
+    assert_timeout(handle(true, Some("0")));
+}
+
+#[test]
+fn server_deadline_without_layer() {
+    assert_eq!(handle(false, Some("60000")).unwrap(), "true");
+    assert_eq!(handle(false, None).unwrap(), "false");
+}
+
+#[test]
+fn server_deadline_is_stable() {
+    let mut request = Request::new(());
+    request
+        .headers_mut()
+        .insert(DEADLINE_HEADER, "60000".parse().unwrap());
+    let (parts, ()) = request.into_parts();
+    let mut response_extensions = Extensions::new();
+    let context = RequestContext::new2(&parts, &mut response_extensions);
+
+    let deadline = context.deadline().unwrap();
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(context.deadline(), Some(deadline));
+}
+
+#[test]
+fn enforce() {
+    let deadline = Deadline::after(Duration::from_secs(60));
+    let result = executor::block_on(deadline.enforce(future::pending(), async { Ok(1) }));
+    assert_eq!(result.unwrap(), 1);
+
+    let result = executor::block_on(
+        deadline.enforce(future::ready(()), future::pending::<Result<(), Error>>()),
+    );
+    assert_timeout(result);
+
+    let deadline = Deadline::new(Instant::now());
+    assert_timeout(executor::block_on(
+        deadline.enforce(future::pending(), async { Ok(()) }),
+    ));
+}
@@ -0,0 +1,173 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::extern_types::{ExternObject, ExternService, ExternServiceEndpoints};
+use crate::test::RemoteBody;
+use crate::types::foo::SubpackageObject;
+use crate::types::IntegerAlias;
+use conjure_codegen::{Config, ExternalType, ValidationError};
+use conjure_error::Error;
+use conjure_http::server::{Endpoint, ResponseBody, Service};
+use conjure_http::PathParams;
+use conjure_object::Uuid;
+use http::{Extensions, Request};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn ir_file(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(name)
+}
+
+#[test]
+fn extern_references() {
+    let object = ExternObject::builder()
+        .sub(SubpackageObject::new(IntegerAlias(1)))
+        .alias(IntegerAlias(2))
+        .long(i64::MAX)
+        .fallback(3)
+        .build();
+    let json = conjure_serde::json::to_string(&object).unwrap();
+    assert_eq!(
+        json,
+        r#"{"sub":{"foo":1},"alias":2,"long":9223372036854775807,"fallback":3}"#,
+    );
+
+    let object = conjure_serde::json::client_from_str::<ExternObject>(&json).unwrap();
+    assert_eq!(*object.sub(), SubpackageObject::new(IntegerAlias(1)));
+}
+
+#[test]
+fn extern_types_not_generated() {
+    let module = Config::new()
+        .strip_prefix("com.palantir.ext".to_string())
+        .extern_package("com.palantir.conjure", "crate::types")
+        .additional_ir_file(ir_file("test-ir.json"))
+        .generate_module(ir_file("test-extern-ir.json"))
+        .unwrap();
+
+    let files = module.files();
+    assert!(files.contains_key(Path::new("extern_object.rs")));
+    assert!(!files.contains_key(Path::new("test_object.rs")));
+    assert!(!files.contains_key(Path::new("foo/mod.rs")));
+}
+
+#[test]
+fn conflicting_definitions() {
+    let ir = fs::read_to_string(ir_file("test-extern-ir.json")).unwrap();
+    let conflicting = Path::new(env!("OUT_DIR")).join("conflicting-extern-ir.json");
+    fs::write(&conflicting, ir.replace("\"alias\"", "\"other\"")).unwrap();
+
+    let error = Config::new()
+        .additional_ir_file(conflicting)
+        .generate_module(ir_file("test-extern-ir.json"))
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "conflicting definitions of type com.palantir.ext.ExternObject",
+    );
+
+    Config::new()
+        .extern_package("com.palantir.conjure", "crate::types")
+        .additional_ir_file(ir_file("test-ir.json"))
+        .additional_ir_file(ir_file("test-extern-ir.json"))
+        .generate_module(ir_file("test-extern-ir.json"))
+        .unwrap();
+}
+
+#[test]
+fn invalid_extern_path() {
+    let result = Config::new()
+        .extern_package("com.palantir.conjure", "not a path")
+        .generate_module(ir_file("test-ir.json"));
+    assert!(result.is_err());
+}
+
+struct ExternServiceHandler;
+
+impl ExternService for ExternServiceHandler {
+    fn get_long(&self, id: Uuid) -> Result<i64, Error> {
+        Ok(id.as_u128() as i64)
+    }
+}
+
+#[test]
+fn external_types() {
+    let endpoints: Vec<Box<dyn Endpoint<RemoteBody, Vec<u8>> + Sync + Send>> =
+        ExternServiceEndpoints::new(ExternServiceHandler).endpoints();
+    let endpoint = endpoints.into_iter().next().unwrap();
+
+    let mut request = Request::new(RemoteBody(vec![]));
+    *request.uri_mut() = "/extern/00000000-0000-0000-0000-00000000000a"
+        .parse()
+        .unwrap();
+    let mut path_params = PathParams::new();
+    path_params.insert("id", "00000000-0000-0000-0000-00000000000a");
+    request.extensions_mut().insert(path_params);
+    let response = endpoint.handle(request, &mut Extensions::new()).unwrap();
+    match response.into_body() {
+        ResponseBody::Fixed(body) => assert_eq!(&body[..], b"10"),
+        _ => panic!("expected a fixed body"),
+    }
+}
+
+fn generate_external(long_type: ExternalType, id_type: ExternalType) -> Result<(), Vec<String>> {
+    Config::new()
+        .extern_package("com.palantir.conjure", "crate::types")
+        .external_type("java.lang.Long", long_type)
+        .external_type("com.palantir.ext.Id", id_type)
+        .additional_ir_file(ir_file("test-ir.json"))
+        .generate_module(ir_file("test-extern-ir.json"))
+        .map(|_| ())
+        .map_err(|e| match e.downcast_ref::<ValidationError>() {
+            Some(e) => e.errors().iter().map(|e| e.to_string()).collect(),
+            None => vec![e.to_string()],
+        })
+}
+
+#[test]
+fn external_type_requirements() {
+    generate_external(
+        ExternalType::new("i64"),
+        ExternalType::new("my_crate::Id").plain(true),
+    )
+    .unwrap();
+
+    let errors = generate_external(
+        ExternalType::new("f64").ord(false),
+        ExternalType::new("my_crate::Id"),
+    )
+    .unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            "object com.palantir.ext.ExternObject, field long: external type java.lang.Long must \
+             implement Eq, Ord, and Hash",
+            "service com.palantir.ext.ExternService, endpoint getLong, argument id: external type \
+             com.palantir.ext.Id must implement Plain and FromPlain",
+        ],
+    );
+}
+
+#[test]
+fn invalid_external_type() {
+    let errors = generate_external(
+        ExternalType::new("i64"),
+        ExternalType::new("not a type").plain(true),
+    )
+    .unwrap_err();
+    assert_eq!(
+        errors,
+        vec!["external type com.palantir.ext.Id: invalid Rust type `not a type`"],
+    );
+}
@@ -0,0 +1,412 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use async_trait::async_trait;
+use conjure_error::{Error, ErrorKind};
+use conjure_http::client::{
+    AsyncClient, AsyncRequestBody, AsyncService, AsyncWriteBody, Client,
+    ConjureResponseDeserializer, RequestBody, Service, WriteBody,
+};
+use conjure_http_client::{blocking, Builder};
+use conjure_macros::{conjure_client, endpoint};
+use conjure_object::Any;
+use futures::io::{AsyncWrite, AsyncWriteExt};
+use http::header::{CONTENT_TYPE, RETRY_AFTER};
+use http::{HeaderValue, Method, StatusCode, Uri};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use std::convert::Infallible;
+use std::io::Write;
+use std::net::TcpListener;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+#[conjure_client]
+trait HttpClientTestService {
+    #[endpoint(method = GET, path = "/test/hello/{name}", accept = ConjureResponseDeserializer)]
+    fn hello(&self, #[path] name: &str, #[query(name = "q")] q: &str) -> Result<String, Error>;
+}
+
+#[conjure_client]
+#[async_trait]
+trait AsyncHttpClientTestService {
+    #[endpoint(method = GET, path = "/test/hello/{name}", accept = ConjureResponseDeserializer)]
+    async fn hello(
+        &self,
+        #[path] name: &str,
+        #[query(name = "q")] q: &str,
+    ) -> Result<String, Error>;
+}
+
+struct TestBody {
+    len: usize,
+    resettable: bool,
+    resets: usize,
+}
+
+impl TestBody {
+    fn new(len: usize) -> TestBody {
+        TestBody {
+            len,
+            resettable: true,
+            resets: 0,
+        }
+    }
+
+    fn data(&self) -> Vec<u8> {
+        (0..self.len).map(|i| i as u8).collect()
+    }
+}
+
+impl<W> WriteBody<W> for TestBody
+where
+    W: Write,
+{
+    fn write_body(&mut self, w: &mut W) -> Result<(), Error> {
+        for chunk in self.data().chunks(100) {
+            w.write_all(chunk).map_err(Error::internal_safe)?;
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self) -> bool {
+        self.resets += 1;
+        self.resettable
+    }
+}
+
+#[async_trait]
+impl<W> AsyncWriteBody<W> for TestBody
+where
+    W: AsyncWrite + Send,
+{
+    async fn write_body(self: Pin<&mut Self>, mut w: Pin<&mut W>) -> Result<(), Error> {
+        for chunk in self.data().chunks(100) {
+            w.write_all(chunk).await.map_err(Error::internal_safe)?;
+        }
+        Ok(())
+    }
+
+    async fn reset(mut self: Pin<&mut Self>) -> bool
+    where
+        W: 'async_trait,
+    {
+        self.resets += 1;
+        self.resettable
+    }
+}
+
+fn upload(client: &blocking::Client, body: &mut TestBody) -> Result<(), Error> {
+    let mut request = http::Request::new(RequestBody::Streaming(body));
+    *request.method_mut() = Method::POST;
+    *request.uri_mut() = Uri::from_static("/test/upload");
+    client.send(request).map(|_| ())
+}
+
+async fn async_upload(
+    client: &conjure_http_client::Client,
+    body: &mut TestBody,
+) -> Result<(), Error> {
+    let mut request = http::Request::new(AsyncRequestBody::Streaming(Pin::new(body) as _));
+    *request.method_mut() = Method::POST;
+    *request.uri_mut() = Uri::from_static("/test/upload");
+    client.send(request).await.map(|_| ())
+}
+
+struct TestRequest {
+    method: Method,
+    uri: String,
+    body: Vec<u8>,
+}
+
+struct TestServer {
+    runtime: Runtime,
+    uri: Uri,
+    requests: Arc<Mutex<Vec<TestRequest>>>,
+}
+
+impl TestServer {
+    /// Starts a server which responds to the `n`th request with `handler(n)`.
+    fn new<F>(handler: F) -> TestServer
+    where
+        F: Fn(usize) -> Response<Body> + 'static + Sync + Send,
+    {
+        let runtime = Runtime::new().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}/api", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+
+        let requests = Arc::new(Mutex::new(vec![]));
+        let handler = Arc::new(handler);
+        let make_service = make_service_fn({
+            let requests = requests.clone();
+            move |_| {
+                let requests = requests.clone();
+                let handler = handler.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                        let requests = requests.clone();
+                        let handler = handler.clone();
+                        async move {
+                            let (parts, body) = request.into_parts();
+                            let body = hyper::body::to_bytes(body).await.unwrap();
+                            let mut requests = requests.lock().unwrap();
+                            requests.push(TestRequest {
+                                method: parts.method,
+                                uri: parts.uri.to_string(),
+                                body: body.to_vec(),
+                            });
+                            Ok::<_, Infallible>(handler(requests.len() - 1))
+                        }
+                    }))
+                }
+            }
+        });
+
+        let _guard = runtime.enter();
+        let server = Server::from_tcp(listener).unwrap().serve(make_service);
+        runtime.spawn(server);
+
+        TestServer {
+            runtime,
+            uri,
+            requests,
+        }
+    }
+
+    fn builder(&self) -> Builder {
+        Builder::new()
+            .uri(self.uri.clone())
+            .backoff_slot_size(Duration::from_millis(1))
+    }
+
+    fn client(&self) -> HttpClientTestServiceClient<blocking::Client> {
+        HttpClientTestServiceClient::new(self.builder().build_blocking().unwrap())
+    }
+
+    fn num_requests(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+}
+
+fn json_response(body: &str) -> Response<Body> {
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[test]
+fn blocking_request() {
+    let server = TestServer::new(|_| json_response(r#""hello""#));
+
+    let response = server.client().hello("a b", "c/d").unwrap();
+    assert_eq!(response, "hello");
+
+    let requests = server.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, Method::GET);
+    assert_eq!(requests[0].uri, "/api/test/hello/a%20b?q=c%2Fd");
+}
+
+#[test]
+fn async_request() {
+    let server = TestServer::new(|_| json_response(r#""hello""#));
+    let client = AsyncHttpClientTestServiceClient::new(server.builder().build().unwrap());
+
+    let response = server.runtime.block_on(client.hello("world", "q")).unwrap();
+    assert_eq!(response, "hello");
+    assert_eq!(server.num_requests(), 1);
+}
+
+#[test]
+fn service_error() {
+    let server = TestServer::new(|_| {
+        let mut response = json_response(
+            r#"{
+                "errorCode": "PERMISSION_DENIED",
+                "errorName": "Default:PermissionDenied",
+                "errorInstanceId": "4a1a2a7c-7ab4-4e4e-a7d3-c2a4c3d6e5f0",
+                "parameters": {}
+            }"#,
+        );
+        *response.status_mut() = StatusCode::FORBIDDEN;
+        response
+    });
+
+    let error = server.client().hello("world", "q").unwrap_err();
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(e.error_name(), "Default:PermissionDenied"),
+        _ => panic!("expected a service error"),
+    }
+    assert_eq!(
+        error.safe_params()["service"],
+        Any::new("HttpClientTestService").unwrap()
+    );
+    assert_eq!(error.safe_params()["endpoint"], Any::new("hello").unwrap());
+    assert_eq!(server.num_requests(), 1);
+}
+
+#[test]
+fn throttle_retry() {
+    let server = TestServer::new(|i| {
+        if i == 0 {
+            let mut response = status_response(StatusCode::TOO_MANY_REQUESTS);
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from_static("0"));
+            response
+        } else {
+            json_response(r#""hello""#)
+        }
+    });
+
+    assert_eq!(server.client().hello("world", "q").unwrap(), "hello");
+    assert_eq!(server.num_requests(), 2);
+}
+
+#[test]
+fn throttle_exceeding_max_retry_after() {
+    let server = TestServer::new(|_| {
+        let mut response = status_response(StatusCode::TOO_MANY_REQUESTS);
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        response
+    });
+    let client = HttpClientTestServiceClient::new(
+        server
+            .builder()
+            .max_retry_after(Duration::from_secs(1))
+            .build_blocking()
+            .unwrap(),
+    );
+
+    let error = client.hello("world", "q").unwrap_err();
+    match error.kind() {
+        ErrorKind::Throttle(e) => assert_eq!(e.duration(), Some(Duration::from_secs(120))),
+        _ => panic!("expected a throttle error"),
+    }
+    assert_eq!(server.num_requests(), 1);
+}
+
+#[test]
+fn retries_exhausted() {
+    let server = TestServer::new(|_| status_response(StatusCode::SERVICE_UNAVAILABLE));
+    let client = HttpClientTestServiceClient::new(
+        server
+            .builder()
+            .max_num_retries(2)
+            .build_blocking()
+            .unwrap(),
+    );
+
+    let error = client.hello("world", "q").unwrap_err();
+    match error.kind() {
+        ErrorKind::Unavailable(_) => {}
+        _ => panic!("expected an unavailable error"),
+    }
+    assert_eq!(server.num_requests(), 3);
+}
+
+#[test]
+fn failover() {
+    let server = TestServer::new(|_| json_response(r#""hello""#));
+    let dead = TcpListener::bind("127.0.0.1:0").unwrap();
+    let dead_uri = format!("http://{}/api", dead.local_addr().unwrap());
+    drop(dead);
+
+    let client = Builder::new()
+        .uri(dead_uri.parse().unwrap())
+        .uri(server.uri.clone())
+        .backoff_slot_size(Duration::from_millis(1))
+        .build_blocking()
+        .unwrap();
+    let client = HttpClientTestServiceClient::new(client);
+
+    assert_eq!(client.hello("world", "q").unwrap(), "hello");
+    assert_eq!(server.num_requests(), 1);
+}
+
+#[test]
+fn streaming_body_reset() {
+    let server = TestServer::new(|i| {
+        if i == 0 {
+            status_response(StatusCode::SERVICE_UNAVAILABLE)
+        } else {
+            status_response(StatusCode::NO_CONTENT)
+        }
+    });
+
+    let client = server.builder().build_blocking().unwrap();
+    let mut body = TestBody::new(20_000);
+    upload(&client, &mut body).unwrap();
+    assert_eq!(body.resets, 1);
+
+    let requests = server.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    for request in &*requests {
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.body, body.data());
+    }
+}
+
+#[test]
+fn streaming_body_not_resettable() {
+    let server = TestServer::new(|_| status_response(StatusCode::SERVICE_UNAVAILABLE));
+
+    let mut body = TestBody::new(10);
+    body.resettable = false;
+    let client = server.builder().build_blocking().unwrap();
+    let error = upload(&client, &mut body).unwrap_err();
+    match error.kind() {
+        ErrorKind::Unavailable(_) => {}
+        _ => panic!("expected an unavailable error"),
+    }
+    assert_eq!(server.num_requests(), 1);
+}
+
+#[test]
+fn async_streaming_body_reset() {
+    let server = TestServer::new(|i| {
+        if i == 0 {
+            status_response(StatusCode::SERVICE_UNAVAILABLE)
+        } else {
+            status_response(StatusCode::NO_CONTENT)
+        }
+    });
+    let client = server.builder().build().unwrap();
+
+    let mut body = TestBody::new(20_000);
+    server
+        .runtime
+        .block_on(async_upload(&client, &mut body))
+        .unwrap();
+    assert_eq!(body.resets, 1);
+
+    let requests = server.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    for request in &*requests {
+        assert_eq!(request.body, body.data());
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use async_trait::async_trait;
+use conjure_error::{Error, PermissionDenied};
+use conjure_http::server::{ConjureResponseSerializer, StreamingConjureResponseSerializer};
+use conjure_http_server::{Server, ServerBuilder};
+use conjure_macros::{conjure_endpoints, endpoint};
+use futures::channel::oneshot;
+use http::header::{ALLOW, CONTENT_LENGTH, CONTENT_TYPE};
+use http::{HeaderMap, Method, StatusCode};
+use hyper::body::Bytes;
+use hyper::{Body, Client, Request};
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+#[conjure_endpoints]
+trait BlockingServerTestService<#[response_writer] O>
+where
+    O: Write,
+{
+    #[endpoint(method = POST, path = "/blocking/echo", produces = ConjureResponseSerializer)]
+    fn echo(&self, #[body] body: String) -> Result<String, Error>;
+
+    #[endpoint(
+        method = GET,
+        path = "/blocking/count/{n}",
+        produces = StreamingConjureResponseSerializer
+    )]
+    fn count(&self, #[path] n: usize) -> Result<Count, Error>;
+
+    #[endpoint(method = GET, path = "/blocking/denied")]
+    fn denied(&self) -> Result<(), Error>;
+}
+
+#[conjure_endpoints]
+#[async_trait]
+trait AsyncServerTestService {
+    #[endpoint(method = GET, path = "/async/hello/{name}", produces = ConjureResponseSerializer)]
+    async fn hello(&self, #[path] name: String) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/async/denied")]
+    async fn denied(&self) -> Result<(), Error>;
+}
+
+struct ServerTestServiceHandler;
+
+impl<O> BlockingServerTestService<O> for ServerTestServiceHandler
+where
+    O: Write,
+{
+    fn echo(&self, body: String) -> Result<String, Error> {
+        Ok(body)
+    }
+
+    fn count(&self, n: usize) -> Result<Count, Error> {
+        Ok(Count(n))
+    }
+
+    fn denied(&self) -> Result<(), Error> {
+        Err(Error::service_safe("denied", PermissionDenied::new()))
+    }
+}
+
+#[async_trait]
+impl AsyncServerTestService for ServerTestServiceHandler {
+    async fn hello(&self, name: String) -> Result<String, Error> {
+        Ok(format!("hello {}", name))
+    }
+
+    async fn denied(&self) -> Result<(), Error> {
+        Err(Error::service_safe("denied", PermissionDenied::new()))
+    }
+}
+
+struct Count(usize);
+
+impl Serialize for Count {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = s.serialize_seq(Some(self.0))?;
+        for i in 0..self.0 {
+            seq.serialize_element(&i)?;
+        }
+        seq.end()
+    }
+}
+
+struct TestServer {
+    runtime: Runtime,
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    handle: JoinHandle<Result<(), Error>>,
+}
+
+impl TestServer {
+    fn new() -> TestServer {
+        TestServer::with_builder(|b| b)
+    }
+
+    fn with_builder<F>(f: F) -> TestServer
+    where
+        F: FnOnce(ServerBuilder) -> ServerBuilder,
+    {
+        let builder = Server::builder()
+            .service(&AsyncServerTestServiceEndpoints::new(
+                ServerTestServiceHandler,
+            ))
+            .blocking_service(&BlockingServerTestServiceEndpoints::new(
+                ServerTestServiceHandler,
+            ));
+        let server = f(builder).bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = server.local_addr();
+
+        let runtime = Runtime::new().unwrap();
+        let (shutdown, signal) = oneshot::channel();
+        let handle = runtime.spawn(server.serve_with_graceful_shutdown(async {
+            let _ = signal.await;
+        }));
+
+        TestServer {
+            runtime,
+            addr,
+            shutdown,
+            handle,
+        }
+    }
+
+    fn send(&self, method: Method, path: &str, body: &str) -> (StatusCode, HeaderMap, Bytes) {
+        let mut request = Request::new(Body::from(body.to_string()));
+        *request.method_mut() = method;
+        *request.uri_mut() = format!("http://{}{}", self.addr, path).parse().unwrap();
+        if !body.is_empty() {
+            request
+                .headers_mut()
+                .insert(CONTENT_TYPE, "application/json".parse().unwrap());
+        }
+
+        self.runtime.block_on(async {
+            let response = Client::new().request(request).await.unwrap();
+            let (parts, body) = response.into_parts();
+            let body = hyper::body::to_bytes(body).await.unwrap();
+            (parts.status, parts.headers, body)
+        })
+    }
+
+    /// Writes raw request data to a new connection and reads until the server closes it.
+    fn send_raw(&self, request: &str) -> String {
+        let mut stream = TcpStream::connect(self.addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+}
+
+fn error_name(body: &[u8]) -> String {
+    let body = serde_json::from_slice::<serde_json::Value>(body).unwrap();
+    body["errorName"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn async_endpoint() {
+    let server = TestServer::new();
+
+    let (status, headers, body) = server.send(Method::GET, "/async/hello/world", "");
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[CONTENT_TYPE], "application/json");
+    assert_eq!(body, r#""hello world""#);
+}
+
+#[test]
+fn blocking_endpoint() {
+    let server = TestServer::new();
+
+    let (status, _, body) = server.send(Method::POST, "/blocking/echo", r#""hello world""#);
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, r#""hello world""#);
+}
+
+#[test]
+fn blocking_streaming_response() {
+    let server = TestServer::new();
+
+    let (status, headers, body) = server.send(Method::GET, "/blocking/count/10000", "");
+    assert_eq!(status, StatusCode::OK);
+    assert!(!headers.contains_key(CONTENT_LENGTH));
+    let body = serde_json::from_slice::<Vec<usize>>(&body).unwrap();
+    assert_eq!(body, (0..10000).collect::<Vec<_>>());
+}
+
+#[test]
+fn endpoint_errors() {
+    let server = TestServer::new();
+
+    let (status, _, body) = server.send(Method::GET, "/async/denied", "");
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(error_name(&body), "Default:PermissionDenied");
+
+    let (status, _, body) = server.send(Method::GET, "/blocking/denied", "");
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(error_name(&body), "Default:PermissionDenied");
+}
+
+#[test]
+fn routing_errors() {
+    let server = TestServer::new();
+
+    let (status, _, body) = server.send(Method::GET, "/missing", "");
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error_name(&body), "Default:NotFound");
+
+    let (status, headers, _) = server.send(Method::DELETE, "/blocking/echo", "");
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(headers[ALLOW], "POST");
+}
+
+#[test]
+fn keep_alive() {
+    let server = TestServer::new();
+    let response = server.send_raw(
+        "GET /async/hello/world HTTP/1.1\r\nHost: localhost\r\n\r\n\
+         GET /async/hello/world HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+    );
+    assert_eq!(response.matches("HTTP/1.1 200 OK").count(), 2);
+
+    let server = TestServer::with_builder(|b| b.keep_alive(false));
+    let response = server.send_raw(
+        "GET /async/hello/world HTTP/1.1\r\nHost: localhost\r\n\r\n\
+         GET /async/hello/world HTTP/1.1\r\nHost: localhost\r\n\r\n",
+    );
+    assert_eq!(response.matches("HTTP/1.1 200 OK").count(), 1);
+}
+
+#[test]
+fn graceful_shutdown() {
+    let server = TestServer::new();
+    let (status, _, _) = server.send(Method::GET, "/async/hello/world", "");
+    assert_eq!(status, StatusCode::OK);
+
+    server.shutdown.send(()).unwrap();
+    server.runtime.block_on(server.handle).unwrap().unwrap();
+}
@@ -0,0 +1,76 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use conjure_codegen::Config;
+use std::path::Path;
+
+#[allow(dead_code, unused_imports, clippy::all)]
+mod api {
+    conjure_codegen_macros::include_ir!("test-ir.json", strip_prefix = "com.palantir.conjure");
+}
+
+#[allow(dead_code, unused_imports, clippy::all)]
+mod exhaustive_api {
+    conjure_codegen_macros::include_ir!(
+        "test-ir.json",
+        exhaustive,
+        strip_prefix = "com.palantir.conjure",
+    );
+}
+
+#[test]
+fn included_types() {
+    let object = api::EmptyFields::builder().optional(1).build();
+    let json = conjure_serde::json::to_string(&object).unwrap();
+    assert_eq!(json, r#"{"optional":1}"#);
+
+    let object = conjure_serde::json::client_from_str::<crate::types::EmptyFields>(&json).unwrap();
+    assert_eq!(object.optional(), Some(1));
+
+    let value = api::TestEnum::One;
+    assert_eq!(value.to_string(), "ONE");
+}
+
+#[test]
+#[allow(deprecated)]
+fn included_exhaustive_types() {
+    match exhaustive_api::TestEnum::One {
+        exhaustive_api::TestEnum::One | exhaustive_api::TestEnum::Two => {}
+    }
+}
+
+#[test]
+fn generate_module() {
+    let module = Config::new()
+        .strip_prefix("com.palantir.conjure".to_string())
+        .generate_module(Path::new(env!("CARGO_MANIFEST_DIR")).join("test-ir.json"))
+        .unwrap();
+    assert_eq!(module.name(), "");
+
+    let files = module.files();
+    let root = files[Path::new("mod.rs")].to_string();
+    assert!(root.contains("pub mod test_object ;"), "{}", root);
+    assert!(files.contains_key(Path::new("test_object.rs")));
+    assert!(files.contains_key(Path::new("foo/mod.rs")));
+
+    let submodule = module
+        .submodules()
+        .iter()
+        .find(|m| m.name() == "test_object")
+        .unwrap();
+    assert!(submodule.submodules().is_empty());
+    assert_eq!(
+        submodule.contents().to_string(),
+        files[Path::new("test_object.rs")].to_string(),
+    );
+}
@@ -0,0 +1,281 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::RemoteBody;
+use async_trait::async_trait;
+use conjure_error::{Error, NotFound, PermissionDenied};
+use conjure_http::server::{
+    AsyncService, ConjureResponseSerializer, EndpointLayer, EndpointMetadata, FromStrOptionDecoder,
+    LayeredService, ResponseBody, Service,
+};
+use conjure_macros::{conjure_endpoints, endpoint};
+use futures::executor;
+use http::{request, response, Extensions, HeaderValue, Request, StatusCode};
+use std::sync::{Arc, Mutex};
+
+#[conjure_endpoints]
+trait LayerTestService {
+    #[endpoint(method = GET, path = "/test/hello", produces = ConjureResponseSerializer)]
+    fn hello(
+        &self,
+        #[header(name = "X-Layer", decoder = FromStrOptionDecoder)] layer: Option<String>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/test/fail")]
+    fn fail(&self) -> Result<(), Error>;
+}
+
+#[conjure_endpoints]
+#[async_trait]
+trait AsyncLayerTestService {
+    #[endpoint(method = GET, path = "/test/hello", produces = ConjureResponseSerializer)]
+    async fn hello(
+        &self,
+        #[header(name = "X-Layer", decoder = FromStrOptionDecoder)] layer: Option<String>,
+    ) -> Result<String, Error>;
+}
+
+struct LayerTestServiceHandler;
+
+impl LayerTestService for LayerTestServiceHandler {
+    fn hello(&self, layer: Option<String>) -> Result<String, Error> {
+        Ok(layer.unwrap_or_default())
+    }
+
+    fn fail(&self) -> Result<(), Error> {
+        Err(Error::service_safe("not found", NotFound::new()))
+    }
+}
+
+#[async_trait]
+impl AsyncLayerTestService for LayerTestServiceHandler {
+    async fn hello(&self, layer: Option<String>) -> Result<String, Error> {
+        Ok(layer.unwrap_or_default())
+    }
+}
+
+struct RecordingLayer {
+    name: &'static str,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl EndpointLayer for RecordingLayer {
+    type State = String;
+
+    fn on_request(
+        &self,
+        endpoint: &dyn EndpointMetadata,
+        request: &mut request::Parts,
+        _: &mut Extensions,
+    ) -> Result<Self::State, Error> {
+        if request.headers.contains_key("Reject") {
+            return Err(Error::service_safe("rejected", PermissionDenied::new()));
+        }
+
+        self.events.lock().unwrap().push(format!(
+            "{} request {}.{}",
+            self.name,
+            endpoint.service_name(),
+            endpoint.name()
+        ));
+        request
+            .headers
+            .insert("X-Layer", HeaderValue::from_static(self.name));
+
+        Ok(request.uri.path().to_string())
+    }
+
+    fn on_response(
+        &self,
+        state: Self::State,
+        _: &dyn EndpointMetadata,
+        response_extensions: &mut Extensions,
+        result: Result<&mut response::Parts, &mut Error>,
+    ) {
+        let event = match result {
+            Ok(response) => {
+                response
+                    .headers
+                    .append("X-Layer", HeaderValue::from_static(self.name));
+                format!("{} response {} {}", self.name, state, response.status)
+            }
+            Err(_) => format!("{} error {}", self.name, state),
+        };
+        self.events.lock().unwrap().push(event);
+        response_extensions.insert(self.name);
+    }
+}
+
+fn layered<S>(service: S, events: &Arc<Mutex<Vec<String>>>) -> impl Service<RemoteBody, Vec<u8>>
+where
+    S: Service<RemoteBody, Vec<u8>>,
+{
+    let inner = LayeredService::new(
+        service,
+        RecordingLayer {
+            name: "inner",
+            events: events.clone(),
+        },
+    );
+    LayeredService::new(
+        inner,
+        RecordingLayer {
+            name: "outer",
+            events: events.clone(),
+        },
+    )
+}
+
+fn request(path: &str) -> Request<RemoteBody> {
+    let mut request = Request::new(RemoteBody(vec![]));
+    *request.uri_mut() = path.parse().unwrap();
+    request
+}
+
+#[test]
+fn layered_response() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let service = layered(
+        LayerTestServiceEndpoints::new(LayerTestServiceHandler),
+        &events,
+    );
+    let endpoint = service
+        .endpoints()
+        .into_iter()
+        .find(|e| e.name() == "hello")
+        .unwrap();
+    assert_eq!(endpoint.template(), "/test/hello");
+
+    let mut response_extensions = Extensions::new();
+    let response = endpoint
+        .handle(request("/test/hello"), &mut response_extensions)
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get_all("X-Layer")
+            .iter()
+            .collect::<Vec<_>>(),
+        ["inner", "outer"],
+    );
+    assert_eq!(response_extensions.get::<&str>(), Some(&"outer"));
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "outer request LayerTestService.hello",
+            "inner request LayerTestService.hello",
+            "inner response /test/hello 200 OK",
+            "outer response /test/hello 200 OK",
+        ],
+    );
+}
+
+#[test]
+fn layered_request() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let service = layered(
+        LayerTestServiceEndpoints::new(LayerTestServiceHandler),
+        &events,
+    );
+    let endpoint = service
+        .endpoints()
+        .into_iter()
+        .find(|e| e.name() == "hello")
+        .unwrap();
+
+    let response = endpoint
+        .handle(request("/test/hello"), &mut Extensions::new())
+        .unwrap();
+    let body = match response.into_body() {
+        ResponseBody::Fixed(body) => body,
+        _ => panic!("expected a fixed body"),
+    };
+    assert_eq!(body, r#""inner""#);
+}
+
+#[test]
+fn layered_error() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let service = layered(
+        LayerTestServiceEndpoints::new(LayerTestServiceHandler),
+        &events,
+    );
+    let endpoint = service
+        .endpoints()
+        .into_iter()
+        .find(|e| e.name() == "fail")
+        .unwrap();
+
+    assert!(endpoint
+        .handle(request("/test/fail"), &mut Extensions::new())
+        .is_err());
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "outer request LayerTestService.fail",
+            "inner request LayerTestService.fail",
+            "inner error /test/fail",
+            "outer error /test/fail",
+        ],
+    );
+}
+
+#[test]
+fn layer_rejected_request() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let service = layered(
+        LayerTestServiceEndpoints::new(LayerTestServiceHandler),
+        &events,
+    );
+    let endpoint = service
+        .endpoints()
+        .into_iter()
+        .find(|e| e.name() == "hello")
+        .unwrap();
+
+    let mut request = request("/test/hello");
+    request
+        .headers_mut()
+        .insert("Reject", HeaderValue::from_static("true"));
+    assert!(endpoint.handle(request, &mut Extensions::new()).is_err());
+    assert!(events.lock().unwrap().is_empty());
+}
+
+#[test]
+fn layered_async_service() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let service = LayeredService::new(
+        AsyncLayerTestServiceEndpoints::new(LayerTestServiceHandler),
+        RecordingLayer {
+            name: "layer",
+            events: events.clone(),
+        },
+    );
+    let endpoint = AsyncService::<RemoteBody, Vec<u8>>::endpoints(&service)
+        .into_iter()
+        .find(|e| e.name() == "hello")
+        .unwrap();
+
+    let response =
+        executor::block_on(endpoint.handle(request("/test/hello"), &mut Extensions::new()))
+            .unwrap();
+    assert_eq!(response.headers()["X-Layer"], "layer");
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "layer request AsyncLayerTestService.hello",
+            "layer response /test/hello 200 OK",
+        ],
+    );
+}
@@ -18,10 +18,27 @@ use std::mem;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
+mod auth;
 mod clients;
+mod constants;
+mod deadline;
 mod errors;
+mod extern_types;
+mod http_client;
+mod http_server;
+mod include_ir;
+mod layers;
 mod objects;
+mod recording;
+mod request_log;
+mod retry;
+mod router;
 mod servers;
+mod testing;
+mod token_provider;
+mod tower;
+mod trace;
+mod validation;
 
 #[derive(Debug, PartialEq)]
 struct RemoteBody(Vec<u8>);
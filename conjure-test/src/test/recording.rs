@@ -0,0 +1,192 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::RemoteBody;
+use async_trait::async_trait;
+use conjure_error::Error;
+use conjure_http::client::{AsyncClient, AsyncRequestBody, Client, RequestBody};
+use conjure_http::server::{self, AsyncResponseBody, ResponseBody};
+use conjure_http::{Deadline, SafeParams, TraceContext};
+use conjure_object::BearerToken;
+use futures::{executor, future};
+use http::{request, Extensions, HeaderMap, Request, Response, StatusCode};
+use std::collections::VecDeque;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// A request sent through a `RecordingClient`.
+pub(super) struct Sent {
+    pub(super) headers: HeaderMap,
+    pub(super) body: Vec<u8>,
+    pub(super) safe_params: Option<SafeParams>,
+    pub(super) deadline: Option<Deadline>,
+    pub(super) trace_context: Option<TraceContext>,
+}
+
+/// A blocking and async client which records the requests sent through it.
+///
+/// Requests succeed with an empty `204 No Content` response unless the client was created with
+/// results to return instead.
+#[derive(Clone, Default)]
+pub(super) struct RecordingClient {
+    sent: Arc<Mutex<Vec<Sent>>>,
+    results: Arc<Mutex<VecDeque<Result<(), Error>>>>,
+    hang: bool,
+}
+
+impl RecordingClient {
+    /// Creates a client which returns the provided results for its first requests.
+    pub(super) fn with_results(results: Vec<Result<(), Error>>) -> Self {
+        RecordingClient {
+            results: Arc::new(Mutex::new(results.into())),
+            ..RecordingClient::default()
+        }
+    }
+
+    /// Creates a client whose async requests never complete after being recorded.
+    pub(super) fn hanging() -> Self {
+        RecordingClient {
+            hang: true,
+            ..RecordingClient::default()
+        }
+    }
+
+    /// Removes and returns the requests recorded so far.
+    pub(super) fn take(&self) -> Vec<Sent> {
+        mem::take(&mut *self.sent.lock().unwrap())
+    }
+
+    fn record(&self, parts: request::Parts, body: Vec<u8>) -> Result<Response<RemoteBody>, Error> {
+        self.sent.lock().unwrap().push(Sent {
+            headers: parts.headers,
+            body,
+            safe_params: parts.extensions.get().cloned(),
+            deadline: parts.extensions.get().copied(),
+            trace_context: parts.extensions.get().cloned(),
+        });
+
+        self.results
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or(Ok(()))
+            .map(|()| {
+                Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(RemoteBody(vec![]))
+                    .unwrap()
+            })
+    }
+}
+
+impl Client for RecordingClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = RemoteBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, body) = req.into_parts();
+        let body = match body {
+            RequestBody::Empty => vec![],
+            RequestBody::Fixed(bytes) => bytes.to_vec(),
+            RequestBody::Streaming(writer) => {
+                let mut buf = vec![];
+                writer.write_body(&mut buf)?;
+                buf
+            }
+        };
+        self.record(parts, body)
+    }
+}
+
+#[async_trait]
+impl AsyncClient for RecordingClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = RemoteBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, body) = req.into_parts();
+        let body = match body {
+            AsyncRequestBody::Empty => vec![],
+            AsyncRequestBody::Fixed(bytes) => bytes.to_vec(),
+            AsyncRequestBody::Streaming(writer) => {
+                let mut buf = vec![];
+                writer.write_body(Pin::new(&mut buf)).await?;
+                buf
+            }
+        };
+        let response = self.record(parts, body);
+        if self.hang {
+            future::pending::<()>().await;
+        }
+        response
+    }
+}
+
+pub(super) fn token() -> BearerToken {
+    BearerToken::new("token").unwrap()
+}
+
+/// Handles a request with the named endpoint of a blocking service.
+///
+/// Returns the response body as a string, which is empty if the response has no body.
+pub(super) fn handle<T>(service: &T, name: &str, request: Request<()>) -> Result<String, Error>
+where
+    T: server::Service<RemoteBody, Vec<u8>>,
+{
+    let endpoint = service
+        .endpoints()
+        .into_iter()
+        .find(|e| e.name() == name)
+        .unwrap();
+    let request = request.map(|()| RemoteBody(vec![]));
+
+    let response = endpoint.handle(request, &mut Extensions::new())?;
+    match response.into_body() {
+        ResponseBody::Empty => Ok(String::new()),
+        ResponseBody::Fixed(body) => Ok(String::from_utf8(body.to_vec()).unwrap()),
+        ResponseBody::Streaming(_) => panic!("unexpected streaming body"),
+    }
+}
+
+/// Handles a request with the named endpoint of an async service.
+///
+/// Returns the response body as a string, which is empty if the response has no body.
+pub(super) fn async_handle<T>(
+    service: &T,
+    name: &str,
+    request: Request<()>,
+) -> Result<String, Error>
+where
+    T: server::AsyncService<RemoteBody, Vec<u8>>,
+{
+    let endpoint = service
+        .endpoints()
+        .into_iter()
+        .find(|e| e.name() == name)
+        .unwrap();
+    let request = request.map(|()| RemoteBody(vec![]));
+
+    let response = executor::block_on(endpoint.handle(request, &mut Extensions::new()))?;
+    match response.into_body() {
+        AsyncResponseBody::Empty => Ok(String::new()),
+        AsyncResponseBody::Fixed(body) => Ok(String::from_utf8(body.to_vec()).unwrap()),
+        AsyncResponseBody::Streaming(_) => panic!("unexpected streaming body"),
+    }
+}
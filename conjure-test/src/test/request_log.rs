@@ -0,0 +1,220 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::recording::{self, RecordingClient};
+use crate::test::RemoteBody;
+use crate::types::*;
+use conjure_error::{Error, NotFound};
+use conjure_http::client::{decode_error_response, AsyncService, LoggingClient, Service};
+use conjure_http::request_log::LoggedParam;
+use conjure_http::server::{LayeredService, RequestLogLayer};
+use conjure_http::{PathParams, RequestLog};
+use conjure_macros::{conjure_endpoints, endpoint};
+use conjure_object::Any;
+use futures::executor;
+use http::header::CONTENT_TYPE;
+use http::{Method, Request, Response, StatusCode};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, PartialEq)]
+struct Record {
+    service: String,
+    endpoint: String,
+    method: Method,
+    path: String,
+    status: Option<StatusCode>,
+    params: Vec<(String, Option<Any>)>,
+    error: bool,
+}
+
+#[derive(Clone, Default)]
+struct RecordingSink(Arc<Mutex<Vec<Record>>>);
+
+impl RecordingSink {
+    fn logger(&self) -> impl Fn(&RequestLog<'_>) + Sync + Send + 'static {
+        let records = self.0.clone();
+        move |log| {
+            records.lock().unwrap().push(Record {
+                service: log.service().to_string(),
+                endpoint: log.endpoint().to_string(),
+                method: log.method().clone(),
+                path: log.path().to_string(),
+                status: log.status(),
+                params: log
+                    .params()
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = match value {
+                            LoggedParam::Safe(value) => Some((*value).clone()),
+                            LoggedParam::Redacted => None,
+                        };
+                        (name.to_string(), value)
+                    })
+                    .collect(),
+                error: log.error().is_some(),
+            })
+        }
+    }
+
+    fn take(&self) -> Vec<Record> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+fn safe(value: &str) -> Option<Any> {
+    Some(Any::new(value).unwrap())
+}
+
+#[conjure_endpoints]
+trait RequestLogTestService {
+    #[endpoint(method = GET, path = "/test/params/{safe_path}/{unsafe_path}")]
+    fn params(
+        &self,
+        #[path(safe)] safe_path: String,
+        #[path] unsafe_path: String,
+        #[query(name = "safeQuery", safe)] safe_query: String,
+        #[header(name = "Unsafe-Header")] unsafe_header: String,
+    ) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/test/fail")]
+    fn fail(&self) -> Result<(), Error>;
+}
+
+struct RequestLogTestServiceHandler;
+
+impl RequestLogTestService for RequestLogTestServiceHandler {
+    fn params(&self, _: String, _: String, _: String, _: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fail(&self) -> Result<(), Error> {
+        Err(Error::service_safe("not found", NotFound::new()))
+    }
+}
+
+fn handle(sink: &RecordingSink, name: &str, request: Request<()>) {
+    let service = LayeredService::new(
+        RequestLogTestServiceEndpoints::new(RequestLogTestServiceHandler),
+        RequestLogLayer::new(sink.logger()),
+    );
+    let _ = recording::handle(&service, name, request);
+}
+
+#[test]
+fn server_safe_params() {
+    let sink = RecordingSink::default();
+
+    let mut request = Request::new(());
+    *request.uri_mut() = "/test/params/foo/bar?safeQuery=baz".parse().unwrap();
+    request
+        .headers_mut()
+        .insert("Unsafe-Header", "fizz".parse().unwrap());
+    let mut path_params = PathParams::new();
+    path_params.insert("safe_path", "foo");
+    path_params.insert("unsafe_path", "bar");
+    request.extensions_mut().insert(path_params);
+    handle(&sink, "params", request);
+
+    assert_eq!(
+        sink.take(),
+        [Record {
+            service: "RequestLogTestService".to_string(),
+            endpoint: "params".to_string(),
+            method: Method::GET,
+            path: "/test/params/{safe_path}/{unsafe_path}".to_string(),
+            status: Some(StatusCode::NO_CONTENT),
+            params: vec![
+                ("safe_path".to_string(), safe("foo")),
+                ("unsafe_path".to_string(), None),
+                ("safe_query".to_string(), safe("baz")),
+                ("unsafe_header".to_string(), None),
+            ],
+            error: false,
+        }],
+    );
+}
+
+#[test]
+fn server_error() {
+    let sink = RecordingSink::default();
+
+    let mut request = Request::new(());
+    *request.uri_mut() = "/test/fail".parse().unwrap();
+    handle(&sink, "fail", request);
+
+    let records = sink.take();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].endpoint, "fail");
+    assert_eq!(records[0].status, Some(StatusCode::NOT_FOUND));
+    assert!(records[0].error);
+}
+
+fn not_found() -> Error {
+    let body = conjure_serde::json::to_vec(&conjure_error::encode(&NotFound::new())).unwrap();
+    let response = Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .header(CONTENT_TYPE, "application/json")
+        .body(RemoteBody(body))
+        .unwrap();
+    decode_error_response(response)
+}
+
+#[test]
+fn client_safe_params() {
+    let sink = RecordingSink::default();
+    let client = LoggingClient::new(RecordingClient::default(), sink.logger());
+    TestServiceClient::new(client)
+        .safe_params(
+            "foo",
+            "bar",
+            "baz",
+            "fizz",
+            &SafeStringAlias("buzz".to_string()),
+            &UnsafeStringAlias("bazz".to_string()),
+        )
+        .unwrap();
+
+    assert_eq!(
+        sink.take(),
+        [Record {
+            service: "TestService".to_string(),
+            endpoint: "safeParams".to_string(),
+            method: Method::GET,
+            path: "/test/safeParams/{safePath}/{unsafePath}".to_string(),
+            status: Some(StatusCode::NO_CONTENT),
+            params: vec![
+                ("safeHeader".to_string(), safe("buzz")),
+                ("safePath".to_string(), safe("foo")),
+                ("safeQuery".to_string(), safe("baz")),
+            ],
+            error: false,
+        }],
+    );
+}
+
+#[test]
+fn client_error() {
+    let sink = RecordingSink::default();
+    let client = LoggingClient::new(
+        RecordingClient::with_results(vec![Err(not_found())]),
+        sink.logger(),
+    );
+    executor::block_on(TestServiceAsyncClient::new(client).json_response()).unwrap_err();
+
+    let records = sink.take();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].endpoint, "jsonResponse");
+    assert_eq!(records[0].status, Some(StatusCode::NOT_FOUND));
+    assert!(records[0].params.is_empty());
+    assert!(records[0].error);
+}
@@ -0,0 +1,371 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::recording::RecordingClient;
+use async_trait::async_trait;
+use conjure_error::{Error, ErrorKind};
+use conjure_http::client::{
+    AsyncClient, AsyncRequestBody, AsyncWriteBody, Client, Endpoint, RequestBody, RetryPolicy,
+    RetryingAsyncClient, RetryingClient, RetryingClientBuilder, WriteBody,
+};
+use conjure_http::{Deadline, SafeParams, TraceContext};
+use futures::executor;
+use futures::io::{AsyncWrite, AsyncWriteExt};
+use http::{Method, Request};
+use std::future;
+use std::io::Write;
+use std::iter;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct TestBody {
+    resettable: bool,
+    resets: usize,
+}
+
+impl TestBody {
+    fn new(resettable: bool) -> TestBody {
+        TestBody {
+            resettable,
+            resets: 0,
+        }
+    }
+}
+
+impl<W> WriteBody<W> for TestBody
+where
+    W: Write,
+{
+    fn write_body(&mut self, w: &mut W) -> Result<(), Error> {
+        w.write_all(b"hello").map_err(Error::internal_safe)
+    }
+
+    fn reset(&mut self) -> bool {
+        self.resets += 1;
+        self.resettable
+    }
+}
+
+#[async_trait]
+impl<W> AsyncWriteBody<W> for TestBody
+where
+    W: AsyncWrite + Send,
+{
+    async fn write_body(self: Pin<&mut Self>, mut w: Pin<&mut W>) -> Result<(), Error> {
+        w.write_all(b"hello").await.map_err(Error::internal_safe)
+    }
+
+    async fn reset(mut self: Pin<&mut Self>) -> bool
+    where
+        W: 'async_trait,
+    {
+        self.resets += 1;
+        self.resettable
+    }
+}
+
+fn unavailable() -> Result<(), Error> {
+    Err(Error::unavailable_safe("unavailable"))
+}
+
+fn request<B>(method: Method, body: B) -> Request<B> {
+    let mut request = Request::new(body);
+    *request.method_mut() = method;
+    request
+        .extensions_mut()
+        .insert(Endpoint::new("TestService", None, "testEndpoint", "/test"));
+    request
+}
+
+fn bodies(client: &RecordingClient) -> Vec<Vec<u8>> {
+    client.take().into_iter().map(|sent| sent.body).collect()
+}
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy::new().backoff_slot_size(Duration::from_millis(1))
+}
+
+fn client(results: Vec<Result<(), Error>>) -> (RecordingClient, RetryingClient<RecordingClient>) {
+    let inner = RecordingClient::with_results(results);
+    let client = RetryingClientBuilder::new()
+        .policy(fast_policy())
+        .build(inner.clone());
+    (inner, client)
+}
+
+#[test]
+fn retry_unavailable() {
+    let (inner, client) = client(vec![unavailable(), unavailable()]);
+
+    client
+        .send(request(Method::GET, RequestBody::Empty))
+        .unwrap();
+    assert_eq!(inner.take().len(), 3);
+}
+
+#[test]
+fn retry_throttle() {
+    let (inner, client) = client(vec![Err(Error::throttle_for_safe(
+        "throttled",
+        Duration::from_millis(1),
+    ))]);
+
+    client
+        .send(request(Method::GET, RequestBody::Empty))
+        .unwrap();
+    assert_eq!(inner.take().len(), 2);
+}
+
+#[test]
+fn throttle_exceeding_max_delay() {
+    let (inner, client) = client(vec![Err(Error::throttle_for_safe(
+        "throttled",
+        Duration::from_secs(60),
+    ))]);
+
+    let error = client
+        .send(request(Method::GET, RequestBody::Empty))
+        .unwrap_err();
+    match error.kind() {
+        ErrorKind::Throttle(_) => {}
+        _ => panic!("expected a throttle error"),
+    }
+    assert_eq!(inner.take().len(), 1);
+}
+
+#[test]
+fn max_delay_caps_backoff() {
+    let inner = RecordingClient::with_results(iter::repeat_with(unavailable).take(4).collect());
+    let sleeps = Arc::new(Mutex::new(vec![]));
+    let policy = RetryPolicy::new()
+        .backoff_slot_size(Duration::from_secs(60))
+        .max_delay(Duration::from_millis(10));
+    let client = RetryingClientBuilder::new()
+        .policy(policy)
+        .build_async(inner.clone(), {
+            let sleeps = sleeps.clone();
+            move |duration| {
+                sleeps.lock().unwrap().push(duration);
+                future::ready(())
+            }
+        });
+
+    executor::block_on(client.send(request(Method::GET, AsyncRequestBody::Empty))).unwrap();
+    assert_eq!(inner.take().len(), 5);
+    assert!(sleeps
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|d| *d <= Duration::from_millis(10)));
+}
+
+#[test]
+fn delay_past_deadline() {
+    let (inner, client) = client(vec![Err(Error::throttle_for_safe(
+        "throttled",
+        Duration::from_secs(5),
+    ))]);
+
+    let mut request = request(Method::GET, RequestBody::Empty);
+    request
+        .extensions_mut()
+        .insert(Deadline::after(Duration::from_secs(1)));
+    client.send(request).unwrap_err();
+    assert_eq!(inner.take().len(), 1);
+}
+
+#[test]
+fn retries_exhausted() {
+    let inner = RecordingClient::with_results(iter::repeat_with(unavailable).take(5).collect());
+    let client = RetryingClientBuilder::new()
+        .policy(fast_policy().max_num_retries(2))
+        .build(inner.clone());
+
+    let error = client
+        .send(request(Method::GET, RequestBody::Empty))
+        .unwrap_err();
+    match error.kind() {
+        ErrorKind::Unavailable(_) => {}
+        _ => panic!("expected an unavailable error"),
+    }
+    assert_eq!(inner.take().len(), 3);
+}
+
+#[test]
+fn other_errors_not_retried() {
+    let (inner, client) = client(vec![Err(Error::internal_safe("boom"))]);
+
+    client
+        .send(request(Method::GET, RequestBody::Empty))
+        .unwrap_err();
+    assert_eq!(inner.take().len(), 1);
+}
+
+#[test]
+fn non_idempotent_not_retried() {
+    let (inner, client) = client(vec![unavailable()]);
+
+    client
+        .send(request(Method::POST, RequestBody::Empty))
+        .unwrap_err();
+    assert_eq!(inner.take().len(), 1);
+}
+
+#[test]
+fn endpoint_policy() {
+    let inner = RecordingClient::with_results(vec![unavailable(), unavailable(), unavailable()]);
+    let client = RetryingClientBuilder::new()
+        .policy(fast_policy())
+        .endpoint_policy(
+            "TestService",
+            "testEndpoint",
+            fast_policy().idempotent(true),
+        )
+        .endpoint_policy("TestService", "otherEndpoint", RetryPolicy::never())
+        .build(inner.clone());
+
+    client
+        .send(request(Method::POST, RequestBody::Empty))
+        .unwrap();
+    assert_eq!(inner.take().len(), 4);
+}
+
+#[test]
+fn fixed_body_resent() {
+    let (inner, client) = client(vec![unavailable()]);
+
+    client
+        .send(request(Method::PUT, RequestBody::Fixed("hello".into())))
+        .unwrap();
+    assert_eq!(bodies(&inner), vec![b"hello".to_vec(); 2]);
+}
+
+#[test]
+fn streaming_body_reset() {
+    let (inner, client) = client(vec![unavailable()]);
+
+    let mut body = TestBody::new(true);
+    client
+        .send(request(Method::PUT, RequestBody::Streaming(&mut body)))
+        .unwrap();
+    assert_eq!(body.resets, 1);
+    assert_eq!(bodies(&inner), vec![b"hello".to_vec(); 2]);
+}
+
+#[test]
+fn streaming_body_not_resettable() {
+    let (inner, client) = client(vec![unavailable()]);
+
+    let mut body = TestBody::new(false);
+    let error = client
+        .send(request(Method::PUT, RequestBody::Streaming(&mut body)))
+        .unwrap_err();
+    match error.kind() {
+        ErrorKind::Unavailable(_) => {}
+        _ => panic!("expected an unavailable error"),
+    }
+    assert_eq!(body.resets, 1);
+    assert_eq!(inner.take().len(), 1);
+}
+
+#[test]
+fn extensions_preserved() {
+    let (inner, client) = client(vec![unavailable()]);
+
+    let mut safe_params = SafeParams::new();
+    safe_params.insert("foo", &"bar");
+    let deadline = Deadline::after(Duration::from_secs(60));
+    let trace_context = TraceContext::new("0123456789abcdef", "fedcba9876543210").unwrap();
+
+    let mut request = request(Method::GET, RequestBody::Empty);
+    request.extensions_mut().insert(safe_params.clone());
+    request.extensions_mut().insert(deadline);
+    request.extensions_mut().insert(trace_context.clone());
+    client.send(request).unwrap();
+
+    let requests = inner.take();
+    assert_eq!(requests.len(), 2);
+    for request in requests {
+        assert_eq!(request.safe_params, Some(safe_params.clone()));
+        assert_eq!(request.deadline, Some(deadline));
+        assert_eq!(request.trace_context, Some(trace_context.clone()));
+    }
+}
+
+fn async_client(
+    results: Vec<Result<(), Error>>,
+) -> (
+    RecordingClient,
+    Arc<Mutex<Vec<Duration>>>,
+    RetryingAsyncClient<RecordingClient>,
+) {
+    let inner = RecordingClient::with_results(results);
+    let sleeps = Arc::new(Mutex::new(vec![]));
+    let client = RetryingClientBuilder::new().build_async(inner.clone(), {
+        let sleeps = sleeps.clone();
+        move |duration| {
+            sleeps.lock().unwrap().push(duration);
+            future::ready(())
+        }
+    });
+    (inner, sleeps, client)
+}
+
+#[test]
+fn async_retry_throttle_duration() {
+    let (inner, sleeps, client) = async_client(vec![
+        Err(Error::throttle_for_safe(
+            "throttled",
+            Duration::from_secs(5),
+        )),
+        unavailable(),
+    ]);
+
+    executor::block_on(client.send(request(Method::GET, AsyncRequestBody::Empty))).unwrap();
+    assert_eq!(inner.take().len(), 3);
+
+    let sleeps = sleeps.lock().unwrap();
+    assert_eq!(sleeps.len(), 2);
+    assert_eq!(sleeps[0], Duration::from_secs(5));
+    assert!(sleeps[1] <= Duration::from_millis(500));
+}
+
+#[test]
+fn async_streaming_body_reset() {
+    let (inner, _, client) = async_client(vec![unavailable()]);
+
+    let mut body = TestBody::new(true);
+    executor::block_on(client.send(request(
+        Method::PUT,
+        AsyncRequestBody::Streaming(Pin::new(&mut body) as _),
+    )))
+    .unwrap();
+    assert_eq!(body.resets, 1);
+    assert_eq!(bodies(&inner), vec![b"hello".to_vec(); 2]);
+}
+
+#[test]
+fn async_streaming_body_not_resettable() {
+    let (inner, sleeps, client) = async_client(vec![unavailable()]);
+
+    let mut body = TestBody::new(false);
+    executor::block_on(client.send(request(
+        Method::PUT,
+        AsyncRequestBody::Streaming(Pin::new(&mut body) as _),
+    )))
+    .unwrap_err();
+    assert_eq!(body.resets, 1);
+    assert_eq!(inner.take().len(), 1);
+    assert!(sleeps.lock().unwrap().is_empty());
+}
@@ -0,0 +1,303 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::RemoteBody;
+use conjure_error::Error;
+use conjure_http::server::{
+    ConjureResponseSerializer, Endpoint, EndpointMetadata, PathSegment, ResponseBody, Route,
+    Router, RouterBuilder,
+};
+use conjure_http::PathParams;
+use conjure_macros::{conjure_endpoints, endpoint};
+use http::{Extensions, Method, Request};
+use std::borrow::Cow;
+
+struct TestEndpoint {
+    method: Method,
+    template: &'static str,
+    path: Vec<PathSegment>,
+}
+
+impl TestEndpoint {
+    fn new(method: Method, template: &'static str) -> TestEndpoint {
+        let path = template[1..]
+            .split('/')
+            .map(
+                |s| match s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                    Some(s) => {
+                        let mut it = s.splitn(2, ':');
+                        PathSegment::Parameter {
+                            name: Cow::Owned(it.next().unwrap().to_string()),
+                            regex: it.next().map(|s| Cow::Owned(s.to_string())),
+                        }
+                    }
+                    None => PathSegment::Literal(Cow::Owned(s.to_string())),
+                },
+            )
+            .collect();
+
+        TestEndpoint {
+            method,
+            template,
+            path,
+        }
+    }
+}
+
+impl EndpointMetadata for TestEndpoint {
+    fn method(&self) -> Method {
+        self.method.clone()
+    }
+
+    fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    fn template(&self) -> &str {
+        self.template
+    }
+
+    fn service_name(&self) -> &str {
+        "TestService"
+    }
+
+    fn name(&self) -> &str {
+        self.template
+    }
+
+    fn deprecated(&self) -> Option<&str> {
+        None
+    }
+}
+
+fn router(endpoints: &[(Method, &'static str)]) -> Result<Router<TestEndpoint>, Error> {
+    endpoints
+        .iter()
+        .fold(RouterBuilder::new(), |builder, (method, template)| {
+            builder.endpoint(TestEndpoint::new(method.clone(), template))
+        })
+        .build()
+}
+
+fn route(router: &Router<TestEndpoint>, method: Method, path: &str) -> RouteResult {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(path)
+        .body(())
+        .unwrap();
+    match router.route(&mut request) {
+        Route::Matched(endpoint) => RouteResult::Matched(
+            endpoint.template,
+            request.extensions_mut().remove().unwrap(),
+        ),
+        Route::NotFound => RouteResult::NotFound,
+        Route::MethodNotAllowed(allowed) => RouteResult::MethodNotAllowed(allowed),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum RouteResult {
+    Matched(&'static str, PathParams),
+    NotFound,
+    MethodNotAllowed(Vec<Method>),
+}
+
+fn params(params: &[(&str, &str)]) -> PathParams {
+    let mut path_params = PathParams::new();
+    for (key, value) in params {
+        path_params.insert(*key, *value);
+    }
+    path_params
+}
+
+#[test]
+fn literal_and_parameter_routes() {
+    let router = router(&[
+        (Method::GET, "/foo/bar"),
+        (Method::GET, "/foo/{bar}"),
+        (Method::GET, "/foo/{bar}/baz/{buz}"),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        route(&router, Method::GET, "/foo/bar"),
+        RouteResult::Matched("/foo/bar", params(&[])),
+    );
+    assert_eq!(
+        route(&router, Method::GET, "/foo/hello%20world"),
+        RouteResult::Matched("/foo/{bar}", params(&[("bar", "hello%20world")])),
+    );
+    assert_eq!(
+        route(&router, Method::GET, "/foo/a/baz/b?query=c"),
+        RouteResult::Matched(
+            "/foo/{bar}/baz/{buz}",
+            params(&[("bar", "a"), ("buz", "b")])
+        ),
+    );
+    assert_eq!(route(&router, Method::GET, "/foo"), RouteResult::NotFound);
+    assert_eq!(route(&router, Method::GET, "/foo/"), RouteResult::NotFound);
+    assert_eq!(
+        route(&router, Method::GET, "/foo/a/baz"),
+        RouteResult::NotFound
+    );
+    assert_eq!(route(&router, Method::GET, "/bar/a"), RouteResult::NotFound);
+}
+
+#[test]
+fn method_not_allowed() {
+    let router = router(&[
+        (Method::GET, "/foo/bar"),
+        (Method::PUT, "/foo/bar"),
+        (Method::POST, "/foo/{bar}"),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        route(&router, Method::DELETE, "/foo/bar"),
+        RouteResult::MethodNotAllowed(vec![Method::GET, Method::PUT, Method::POST]),
+    );
+    assert_eq!(
+        route(&router, Method::GET, "/foo/baz"),
+        RouteResult::MethodNotAllowed(vec![Method::POST]),
+    );
+    assert_eq!(
+        route(&router, Method::POST, "/foo/bar"),
+        RouteResult::Matched("/foo/{bar}", params(&[("bar", "bar")])),
+    );
+}
+
+#[test]
+fn parameter_regex() {
+    let router = router(&[
+        (Method::GET, "/items/{id:[0-9]+}"),
+        (Method::GET, "/items/{name}"),
+        (Method::GET, "/files/{path:.+}"),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        route(&router, Method::GET, "/items/123"),
+        RouteResult::Matched("/items/{id:[0-9]+}", params(&[("id", "123")])),
+    );
+    assert_eq!(
+        route(&router, Method::GET, "/items/abc"),
+        RouteResult::Matched("/items/{name}", params(&[("name", "abc")])),
+    );
+    assert_eq!(
+        route(&router, Method::GET, "/files/a"),
+        RouteResult::Matched("/files/{path:.+}", params(&[("path", "a")])),
+    );
+    assert_eq!(
+        route(&router, Method::GET, "/files/a/b/c"),
+        RouteResult::Matched("/files/{path:.+}", params(&[("path", "a/b/c")])),
+    );
+    assert_eq!(
+        route(&router, Method::GET, "/items/1/2"),
+        RouteResult::NotFound
+    );
+}
+
+#[test]
+fn conflicting_routes() {
+    router(&[(Method::GET, "/foo/{bar}"), (Method::GET, "/foo/{baz}")])
+        .err()
+        .unwrap();
+    router(&[(Method::GET, "/foo/bar"), (Method::GET, "/foo/bar")])
+        .err()
+        .unwrap();
+
+    router(&[(Method::GET, "/foo/{bar}"), (Method::POST, "/foo/{baz}")]).unwrap();
+}
+
+#[test]
+fn ambiguous_routes() {
+    router(&[
+        (Method::GET, "/foo/{a:[a-z]+}"),
+        (Method::GET, "/foo/{b:[0-9a-f]+}"),
+    ])
+    .err()
+    .unwrap();
+
+    router(&[(Method::GET, "/foo/{a:[a-z]+}"), (Method::GET, "/foo/{b}")]).unwrap();
+    router(&[(Method::GET, "/foo/{a}/bar"), (Method::GET, "/foo/{b}/baz")]).unwrap();
+}
+
+#[test]
+fn invalid_regex() {
+    router(&[(Method::GET, "/foo/{a:(}")]).err().unwrap();
+}
+
+#[conjure_endpoints]
+trait RoutedService {
+    #[endpoint(method = GET, path = "/routed/{id}", produces = ConjureResponseSerializer)]
+    fn get(&self, #[path] id: String) -> Result<String, Error>;
+
+    #[endpoint(method = DELETE, path = "/routed/{id}")]
+    fn delete(&self, #[path] id: String) -> Result<(), Error>;
+}
+
+struct RoutedServiceHandler;
+
+impl RoutedService for RoutedServiceHandler {
+    fn get(&self, id: String) -> Result<String, Error> {
+        Ok(id)
+    }
+
+    fn delete(&self, _: String) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn service_routes() {
+    let service = RoutedServiceEndpoints::new(RoutedServiceHandler);
+    let router: Router<Box<dyn Endpoint<RemoteBody, Vec<u8>> + Sync + Send>> =
+        RouterBuilder::new().service(&service).build().unwrap();
+
+    let mut request = Request::builder()
+        .method(Method::GET)
+        .uri("/routed/hello%20world")
+        .body(RemoteBody(vec![]))
+        .unwrap();
+    let endpoint = match router.route(&mut request) {
+        Route::Matched(endpoint) => endpoint,
+        _ => panic!("expected a match"),
+    };
+    assert_eq!(endpoint.name(), "get");
+
+    let response = endpoint
+        .handle(request, &mut Extensions::new())
+        .unwrap()
+        .into_body();
+    match response {
+        ResponseBody::Fixed(body) => assert_eq!(&*body, br#""hello world""#),
+        _ => panic!("expected a fixed body"),
+    }
+
+    let mut request = Request::builder()
+        .method(Method::POST)
+        .uri("/routed/foo")
+        .body(RemoteBody(vec![]))
+        .unwrap();
+    match router.route(&mut request) {
+        Route::MethodNotAllowed(allowed) => assert_eq!(allowed, [Method::GET, Method::DELETE]),
+        _ => panic!("expected method not allowed"),
+    }
+
+    RouterBuilder::<Box<dyn Endpoint<RemoteBody, Vec<u8>> + Sync + Send>>::new()
+        .service(&service)
+        .service(&service)
+        .build()
+        .err()
+        .unwrap();
+}
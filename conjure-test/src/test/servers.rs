@@ -16,23 +16,30 @@
 use crate::test::RemoteBody;
 use crate::types::*;
 use async_trait::async_trait;
-use conjure_error::Error;
+use conjure_error::{Error, ErrorKind, NotFound, PermissionDenied};
 use conjure_http::server::{
-    AsyncResponseBody, AsyncService, AsyncWriteBody, ConjureResponseSerializer, DeserializeRequest,
-    FromStrOptionDecoder, FromStrSeqDecoder, RequestContext, ResponseBody, SerializeResponse,
-    Service, WriteBody,
+    async_encode_error, async_encode_error_with_hook, encode_error, encode_error_with_hook,
+    ArgumentMetadata, AsyncResponseBody, AsyncService, AsyncWriteBody, AuthType,
+    ConjureResponseSerializer, DeserializeRequest, EndpointMetadata, FromStrOptionDecoder,
+    FromStrSeqDecoder, LogSafety, NotAcceptable, ParameterType, RequestContext, ResponseBody,
+    SerializeResponse, Service, StreamingConjureResponseSerializer, UnsupportedMediaType,
+    WriteBody,
 };
 use conjure_http::{PathParams, SafeParams};
 use conjure_macros::{conjure_endpoints, endpoint};
-use conjure_object::{BearerToken, ResourceIdentifier};
+use conjure_object::{Any, BearerToken, ResourceIdentifier};
 use futures::executor;
-use http::{Extensions, HeaderMap, Request, Response, Uri};
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Extensions, HeaderMap, HeaderValue, Request, Response, StatusCode, Uri};
 use mockall::mock;
 use mockall::predicate::eq;
-use serde::Serialize;
+use serde::ser::{Error as _, SerializeSeq};
+use serde::{Serialize, Serializer};
 use std::collections::{BTreeMap, BTreeSet};
 use std::io::Write;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 macro_rules! test_service_handler {
     ($(
@@ -121,6 +128,8 @@ test_service_handler! {
 
     fn json_request(&self, body: String) -> Result<(), Error>;
 
+    fn limited_json_request(&self, body: String) -> Result<(), Error>;
+
     fn optional_json_request(&self, body: Option<String>) -> Result<(), Error>;
 
     fn optional_alias_request(&self, body: OptionalAlias) -> Result<(), Error>;
@@ -139,6 +148,8 @@ test_service_handler! {
 
     fn map_json_response(&self) -> Result<BTreeMap<String, String>, Error>;
 
+    fn streaming_list_json_response(&self) -> Result<Vec<String>, Error>;
+
     fn streaming_response(&self) -> Result<StreamingBody, Error>;
 
     fn optional_streaming_response(&self) -> Result<Option<StreamingBody>, Error>;
@@ -243,18 +254,59 @@ where
 
 impl<T> Call<T>
 where
-    T: Service<RemoteBody, Vec<u8>>,
+    T: Service<RemoteBody, Vec<u8>> + AsyncService<RemoteBody, Vec<u8>>,
 {
-    fn send_sync(&self, name: &str) {
-        let endpoint = Service::endpoints(&self.service)
+    fn send_err(&self, name: &str) -> Error {
+        let error = Service::endpoints(&self.service)
             .into_iter()
             .find(|e| e.name() == name)
+            .unwrap()
+            .handle(self.request(), &mut Extensions::new())
+            .err()
             .unwrap();
 
+        let endpoint = AsyncService::endpoints(&self.service)
+            .into_iter()
+            .find(|e| e.name() == name)
+            .unwrap();
+        let async_error =
+            executor::block_on(endpoint.handle(self.request(), &mut Extensions::new()))
+                .err()
+                .unwrap();
+        assert_eq!(error_name(&error), error_name(&async_error));
+
+        error
+    }
+}
+
+fn error_name(error: &Error) -> &str {
+    match error.kind() {
+        ErrorKind::Service(error) => error.error_name(),
+        _ => panic!("expected a service error"),
+    }
+}
+
+impl<T> Call<T> {
+    fn request(&self) -> Request<RemoteBody> {
         let mut request = Request::new(RemoteBody(self.body.clone()));
         *request.uri_mut() = self.uri.clone();
         *request.headers_mut() = self.headers.clone();
         request.extensions_mut().insert(self.path_params.clone());
+        request
+    }
+}
+
+impl<T> Call<T>
+where
+    T: Service<RemoteBody, Vec<u8>>,
+{
+    fn send_sync(&self, name: &str) {
+        let endpoint = Service::endpoints(&self.service)
+            .into_iter()
+            .find(|e| e.name() == name)
+            .unwrap();
+
+        let request = self.request();
 
         let mut extensions = Extensions::new();
         let response = endpoint.handle(request, &mut extensions).unwrap();
@@ -262,8 +314,11 @@ where
             self.safe_params,
             extensions.remove::<SafeParams>().unwrap_or_default()
         );
+        let smile = response.headers().get(CONTENT_TYPE)
+            == Some(&HeaderValue::from_static("application/x-jackson-smile"));
         let body = match response.into_body() {
             ResponseBody::Empty => TestBody::Empty,
+            ResponseBody::Fixed(bytes) if smile => TestBody::Smile(bytes.to_vec()),
             ResponseBody::Fixed(bytes) => {
                 TestBody::Json(String::from_utf8(bytes.to_vec()).unwrap())
             }
@@ -287,10 +342,7 @@ where
             .find(|e| e.name() == name)
             .unwrap();
 
-        let mut request = Request::new(RemoteBody(self.body.clone()));
-        *request.uri_mut() = self.uri.clone();
-        *request.headers_mut() = self.headers.clone();
-        request.extensions_mut().insert(self.path_params.clone());
+        let request = self.request();
 
         let mut extensions = Extensions::new();
         let response = endpoint.handle(request, &mut extensions).await.unwrap();
@@ -298,8 +350,11 @@ where
             self.safe_params,
             extensions.remove::<SafeParams>().unwrap_or_default()
         );
+        let smile = response.headers().get(CONTENT_TYPE)
+            == Some(&HeaderValue::from_static("application/x-jackson-smile"));
         let body = match response.into_body() {
             AsyncResponseBody::Empty => TestBody::Empty,
+            AsyncResponseBody::Fixed(bytes) if smile => TestBody::Smile(bytes.to_vec()),
             AsyncResponseBody::Fixed(bytes) => {
                 TestBody::Json(String::from_utf8(bytes.to_vec()).unwrap())
             }
@@ -335,6 +390,7 @@ impl AsyncWriteBody<Vec<u8>> for StreamingBody {
 enum TestBody {
     Empty,
     Json(String),
+    Smile(Vec<u8>),
     Streaming(Vec<u8>),
 }
 
@@ -477,6 +533,84 @@ fn json_request() {
         .send("jsonRequest");
 }
 
+#[test]
+fn smile_request() {
+    TestServiceHandler::new()
+        .json_request(|body| {
+            assert_eq!(body, "hello world");
+            Ok(())
+        })
+        .call()
+        .body(&conjure_serde::smile::to_vec("hello world").unwrap())
+        .header("Content-Type", "application/x-jackson-smile")
+        .send("jsonRequest");
+}
+
+#[test]
+fn json_request_content_type_params() {
+    for content_type in &[
+        "application/json; charset=utf-8",
+        "Application/JSON",
+        "application/json;charset=\"UTF-8\"",
+    ] {
+        TestServiceHandler::new()
+            .json_request(|body| {
+                assert_eq!(body, "hello world");
+                Ok(())
+            })
+            .call()
+            .body(br#""hello world""#)
+            .header("Content-Type", content_type)
+            .send("jsonRequest");
+    }
+}
+
+#[test]
+fn unsupported_request_content_type() {
+    for content_type in &[
+        "text/plain",
+        "application/json; charset=iso-8859-1",
+        "application/jsonx",
+        "not a media type",
+    ] {
+        let error = TestServiceHandler::new()
+            .call()
+            .body(br#""hello world""#)
+            .header("Content-Type", content_type)
+            .send_err("jsonRequest");
+        assert_eq!(error_name(&error), UnsupportedMediaType::NAME);
+        assert_eq!(
+            encode_error::<Vec<u8>>(&error).status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+}
+
+#[test]
+fn request_size_limit() {
+    TestServiceHandler::new()
+        .limited_json_request(|body| {
+            assert_eq!(body, "hello world");
+            Ok(())
+        })
+        .call()
+        .body(br#""hello world""#)
+        .header("Content-Type", "application/json")
+        .send("limitedJsonRequest");
+
+    let error = TestServiceHandler::new()
+        .call()
+        .body(br#""hello world, hello world""#)
+        .header("Content-Type", "application/json")
+        .send_err("limitedJsonRequest");
+    assert_eq!(error_name(&error), "Default:RequestEntityTooLarge");
+    assert_eq!(error.safe_params()["limit"], Any::new(16usize).unwrap());
+    assert_eq!(
+        encode_error::<Vec<u8>>(&error).status(),
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+}
+
 #[test]
 fn optional_json_request() {
     TestServiceHandler::new()
@@ -574,6 +708,71 @@ fn json_response() {
         .send("jsonResponse");
 }
 
+#[test]
+fn smile_response() {
+    TestServiceHandler::new()
+        .json_response(|| Ok("hello world".to_string()))
+        .call()
+        .header(
+            "Accept",
+            "application/x-jackson-smile, application/json;q=0.9",
+        )
+        .response(TestBody::Smile(
+            conjure_serde::smile::to_vec("hello world").unwrap(),
+        ))
+        .send("jsonResponse");
+
+    TestServiceHandler::new()
+        .json_response(|| Ok("hello world".to_string()))
+        .call()
+        .header("Accept", "text/plain, application/json")
+        .response(TestBody::Json(r#""hello world""#.to_string()))
+        .send("jsonResponse");
+}
+
+#[test]
+fn response_accept_negotiation() {
+    let smile = || TestBody::Smile(conjure_serde::smile::to_vec("hello world").unwrap());
+    let json = || TestBody::Json(r#""hello world""#.to_string());
+    for (accept, response) in [
+        (
+            "application/json;q=0.5, application/x-jackson-smile",
+            smile(),
+        ),
+        ("application/x-jackson-smile;q=0.1, */*", json()),
+        ("application/*", json()),
+        ("APPLICATION/X-JACKSON-SMILE", smile()),
+        ("*/*;q=0.5, application/json;q=0", smile()),
+        ("text/plain, application/json; charset=utf-8", json()),
+    ] {
+        TestServiceHandler::new()
+            .json_response(|| Ok("hello world".to_string()))
+            .call()
+            .header("Accept", accept)
+            .response(response)
+            .send("jsonResponse");
+    }
+}
+
+#[test]
+fn not_acceptable_response() {
+    for accept in &[
+        "text/plain",
+        "application/json;q=0, application/x-jackson-smile;q=0",
+    ] {
+        let error = TestServiceHandler::new()
+            .json_response(|| Ok("hello world".to_string()))
+            .call()
+            .header("Accept", accept)
+            .send_err("jsonResponse");
+        assert_eq!(error_name(&error), NotAcceptable::NAME);
+        assert_eq!(
+            encode_error::<Vec<u8>>(&error).status(),
+            StatusCode::NOT_ACCEPTABLE
+        );
+    }
+}
+
 #[test]
 fn optional_json_response() {
     TestServiceHandler::new()
@@ -602,6 +801,28 @@ fn list_json_response() {
         .send("listJsonResponse");
 }
 
+#[test]
+fn streaming_list_json_response() {
+    TestServiceHandler::new()
+        .streaming_list_json_response(|| Ok(vec!["hello".to_string(), "world".to_string()]))
+        .call()
+        .response(TestBody::Streaming(br#"["hello","world"]"#.to_vec()))
+        .send_sync("streamingListJsonResponse");
+
+    executor::block_on(
+        TestServiceHandler::new()
+            .streaming_list_json_response(|| Ok(vec!["hello".to_string(), "world".to_string()]))
+            .call()
+            .response(TestBody::Streaming(br#"["hello","world"]"#.to_vec()))
+            .send_async("streamingListJsonResponse"),
+    );
+
+    TestServiceHandler::new()
+        .streaming_list_json_response(|| Ok(vec![]))
+        .call()
+        .send("streamingListJsonResponse");
+}
+
 #[test]
 fn set_json_response() {
     TestServiceHandler::new()
@@ -942,6 +1163,56 @@ fn custom_context() {
         .send_sync("context");
 }
 
+#[conjure_endpoints(request_size_limit = 16)]
+trait CustomLimitedService {
+    #[endpoint(method = POST, path = "/test/serviceLimit")]
+    fn service_limit(&self, #[body] body: String) -> Result<(), Error>;
+
+    #[endpoint(method = POST, path = "/test/endpointLimit", request_size_limit = 2 * 16)]
+    fn endpoint_limit(&self, #[body] body: String) -> Result<(), Error>;
+}
+
+struct CustomLimitedServiceHandler;
+
+impl CustomLimitedService for CustomLimitedServiceHandler {
+    fn service_limit(&self, _: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn endpoint_limit(&self, _: String) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_request_size_limit() {
+    let service = CustomLimitedServiceEndpoints::new(CustomLimitedServiceHandler);
+    let call = |name: &str, body: &str| {
+        let endpoint = Service::<RemoteBody, Vec<u8>>::endpoints(&service)
+            .into_iter()
+            .find(|e| e.name() == name)
+            .unwrap();
+        let mut request = Request::new(RemoteBody(serde_json::to_vec(body).unwrap()));
+        request
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        endpoint.handle(request, &mut Extensions::new()).map(|_| ())
+    };
+
+    call("service_limit", "hello world").unwrap();
+    let error = call("service_limit", "hello world, hello world")
+        .err()
+        .unwrap();
+    assert_eq!(error_name(&error), "Default:RequestEntityTooLarge");
+    assert_eq!(error.safe_params()["limit"], Any::new(16usize).unwrap());
+
+    call("endpoint_limit", "hello world, hello world").unwrap();
+    let error = call("endpoint_limit", "hello world, hello world, hello world")
+        .err()
+        .unwrap();
+    assert_eq!(error.safe_params()["limit"], Any::new(32usize).unwrap());
+}
+
 #[conjure_endpoints]
 trait CustomStreamingService<#[request_body] I, #[response_writer] O>
 where
@@ -1022,3 +1293,315 @@ fn custom_streaming_response() {
         .response(TestBody::Streaming(b"hello world".to_vec()))
         .send_sync("streaming_response");
 }
+
+#[conjure_endpoints]
+trait CustomStreamingJsonService<#[response_writer] O>
+where
+    O: Write,
+{
+    #[endpoint(
+        method = GET,
+        path = "/test/streamed",
+        produces = StreamingConjureResponseSerializer
+    )]
+    fn streamed(&self) -> Result<TestList, Error>;
+
+    #[endpoint(
+        method = GET,
+        path = "/test/buffered",
+        produces = StreamingConjureResponseSerializer<32>
+    )]
+    fn buffered(&self) -> Result<TestList, Error>;
+}
+
+struct CustomStreamingJsonServiceHandler {
+    fail: bool,
+}
+
+impl<O> CustomStreamingJsonService<O> for CustomStreamingJsonServiceHandler
+where
+    O: Write,
+{
+    fn streamed(&self) -> Result<TestList, Error> {
+        Ok(TestList { fail: self.fail })
+    }
+
+    fn buffered(&self) -> Result<TestList, Error> {
+        Ok(TestList { fail: self.fail })
+    }
+}
+
+struct TestList {
+    fail: bool,
+}
+
+impl Serialize for TestList {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = s.serialize_seq(Some(2))?;
+        seq.serialize_element("hello")?;
+        seq.serialize_element("world")?;
+        if self.fail {
+            return Err(S::Error::custom("serialization failed"));
+        }
+        seq.end()
+    }
+}
+
+#[test]
+fn custom_streaming_json_response() {
+    let call = |name: &str, fail: bool| {
+        let service =
+            CustomStreamingJsonServiceEndpoints::new(CustomStreamingJsonServiceHandler { fail });
+        let endpoint = Service::<RemoteBody, Vec<u8>>::endpoints(&service)
+            .into_iter()
+            .find(|e| e.name() == name)
+            .unwrap();
+        endpoint.handle(Request::new(RemoteBody(vec![])), &mut Extensions::new())
+    };
+
+    let response = call("streamed", false).unwrap();
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    assert!(!response.headers().contains_key(CONTENT_LENGTH));
+    let mut buf = vec![];
+    match response.into_body() {
+        ResponseBody::Streaming(body) => body.write_body(&mut buf).unwrap(),
+        _ => panic!("expected a streaming body"),
+    }
+    assert_eq!(buf, br#"["hello","world"]"#);
+
+    let response = call("buffered", false).unwrap();
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    assert_eq!(response.headers()[CONTENT_LENGTH], "17");
+    match response.into_body() {
+        ResponseBody::Fixed(body) => assert_eq!(&body[..], br#"["hello","world"]"#),
+        _ => panic!("expected a fixed body"),
+    }
+
+    // once streaming has started, errors can only be reported by the body
+    let response = call("streamed", true).unwrap();
+    match response.into_body() {
+        ResponseBody::Streaming(body) => assert!(body.write_body(&mut vec![]).is_err()),
+        _ => panic!("expected a streaming body"),
+    }
+
+    assert!(call("buffered", true).is_err());
+}
+
+#[conjure_endpoints]
+#[async_trait]
+trait AsyncCustomStreamingJsonService<#[response_writer] O>
+where
+    O: futures::io::AsyncWrite + Send,
+{
+    #[endpoint(
+        method = GET,
+        path = "/test/streamed",
+        produces = StreamingConjureResponseSerializer
+    )]
+    async fn streamed(&self) -> Result<TestList, Error>;
+}
+
+#[async_trait]
+impl<O> AsyncCustomStreamingJsonService<O> for CustomStreamingJsonServiceHandler
+where
+    O: futures::io::AsyncWrite + Send,
+{
+    async fn streamed(&self) -> Result<TestList, Error> {
+        Ok(TestList { fail: self.fail })
+    }
+}
+
+#[test]
+fn async_custom_streaming_json_response() {
+    let call = |fail: bool| {
+        let service =
+            AsyncCustomStreamingJsonServiceEndpoints::new(CustomStreamingJsonServiceHandler {
+                fail,
+            });
+        let endpoint = AsyncService::<RemoteBody, Vec<u8>>::endpoints(&service)
+            .into_iter()
+            .next()
+            .unwrap();
+        let response = executor::block_on(
+            endpoint.handle(Request::new(RemoteBody(vec![])), &mut Extensions::new()),
+        )
+        .unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        assert!(!response.headers().contains_key(CONTENT_LENGTH));
+        let mut buf = vec![];
+        let result = match response.into_body() {
+            AsyncResponseBody::Streaming(body) => {
+                executor::block_on(body.write_body(Pin::new(&mut buf)))
+            }
+            _ => panic!("expected a streaming body"),
+        };
+        (result, buf)
+    };
+
+    let (result, buf) = call(false);
+    result.unwrap();
+    assert_eq!(buf, br#"["hello","world"]"#);
+
+    let (result, _) = call(true);
+    assert!(result.is_err());
+}
+
+fn endpoint_metadata<T>(service: &T, name: &str) -> Box<dyn EndpointMetadata>
+where
+    T: Service<RemoteBody, Vec<u8>>,
+{
+    let endpoint = Service::endpoints(service)
+        .into_iter()
+        .find(|e| e.name() == name)
+        .unwrap();
+    Box::new(endpoint)
+}
+
+#[test]
+fn generated_endpoint_metadata() {
+    let service = TestServiceEndpoints::new(TestServiceHandler::new());
+
+    let endpoint = endpoint_metadata(&service, "headers");
+    assert_eq!(endpoint.auth(), None);
+    assert_eq!(
+        endpoint.args(),
+        &[
+            ArgumentMetadata::new("foo", ParameterType::Header("Some-Custom-Header"), "string"),
+            ArgumentMetadata::new(
+                "bar",
+                ParameterType::Header("Some-Optional-Header"),
+                "optional<integer>",
+            ),
+        ],
+    );
+    assert_eq!(endpoint.returns(), None);
+
+    let endpoint = endpoint_metadata(&service, "safeParams");
+    let args = endpoint.args();
+    assert_eq!(
+        args[0],
+        ArgumentMetadata::new("safePath", ParameterType::Path, "string")
+            .with_safety(LogSafety::Safe)
+            .with_markers(&["com.palantir.logsafe.Safe"]),
+    );
+    assert_eq!(args[1].safety(), None);
+    assert_eq!(args[2].param_type(), ParameterType::Query("safeQueryId"));
+    assert_eq!(args[2].safety(), Some(LogSafety::Safe));
+    assert_eq!(args[4].safety(), Some(LogSafety::Safe));
+    assert_eq!(args[5].safety(), Some(LogSafety::Unsafe));
+
+    let endpoint = endpoint_metadata(&service, "cookieAuth");
+    assert_eq!(endpoint.auth(), Some(AuthType::Cookie("foobar")));
+
+    let endpoint = endpoint_metadata(&service, "mapJsonResponse");
+    assert_eq!(endpoint.returns(), Some("map<string, string>"));
+
+    let endpoint = endpoint_metadata(&service, "limitedJsonRequest");
+    assert_eq!(endpoint.tags(), &["server-request-size-limit:16"]);
+    assert_eq!(endpoint.args()[0].param_type(), ParameterType::Body);
+}
+
+#[test]
+fn custom_endpoint_metadata() {
+    let service = CustomServiceEndpoints::new(MockCustomService::new());
+
+    let endpoint = endpoint_metadata(&service, "query_params");
+    assert_eq!(endpoint.auth(), None);
+    assert_eq!(
+        endpoint.args(),
+        &[
+            ArgumentMetadata::new("normal", ParameterType::Query("normal"), "String"),
+            ArgumentMetadata::new("list", ParameterType::Query("list"), "Vec<i32>"),
+        ],
+    );
+    assert_eq!(endpoint.returns(), None);
+
+    let endpoint = endpoint_metadata(&service, "json_response");
+    assert_eq!(endpoint.returns(), Some("String"));
+
+    let endpoint = endpoint_metadata(&service, "cookie_header");
+    assert_eq!(endpoint.auth(), Some(AuthType::Cookie("foobar")));
+    assert!(endpoint.args().is_empty());
+
+    let endpoint = endpoint_metadata(&service, "safe_params");
+    let args = endpoint.args();
+    assert_eq!(args.len(), 7);
+    assert_eq!(args[0].safety(), Some(LogSafety::Safe));
+    assert_eq!(args[1].safety(), None);
+    assert_eq!(args[6].param_type(), ParameterType::Body);
+
+    let endpoint = endpoint_metadata(&service, "context");
+    assert!(endpoint.args().is_empty());
+}
+
+#[test]
+fn encode_service_error() {
+    let error = Error::service_safe("foo", NotFound::new()).with_safe_param("bar", "baz");
+
+    let response = encode_error::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["Content-Type"], "application/json");
+    let body = match response.into_body() {
+        ResponseBody::Fixed(body) => body,
+        _ => panic!("expected a fixed body"),
+    };
+    let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+    assert_eq!(body["errorCode"], "NOT_FOUND");
+    assert_eq!(body["errorName"], "Default:NotFound");
+
+    let response = async_encode_error::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    match response.into_body() {
+        AsyncResponseBody::Fixed(_) => {}
+        _ => panic!("expected a fixed body"),
+    }
+}
+
+#[test]
+fn encode_throttle_error() {
+    let error = Error::throttle_for_safe("foo", Duration::from_millis(1500));
+    let response = encode_error::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["Retry-After"], "2");
+    match response.into_body() {
+        ResponseBody::Empty => {}
+        _ => panic!("expected an empty body"),
+    }
+
+    let error = Error::throttle_safe("foo");
+    let response = async_encode_error::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(!response.headers().contains_key("Retry-After"));
+}
+
+#[test]
+fn encode_unavailable_error() {
+    let error = Error::unavailable_safe("foo");
+    let response = encode_error::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    match response.into_body() {
+        ResponseBody::Empty => {}
+        _ => panic!("expected an empty body"),
+    }
+}
+
+#[test]
+fn encode_error_hook() {
+    let error = Error::service_safe("foo", PermissionDenied::new()).with_safe_param("bar", "baz");
+
+    let calls = AtomicUsize::new(0);
+    let hook = |error: &Error, status| {
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error.safe_params()["bar"], Any::new("baz").unwrap());
+        calls.fetch_add(1, Ordering::SeqCst);
+    };
+
+    let response = encode_error_with_hook::<Vec<u8>, _>(&error, &hook);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = async_encode_error_with_hook::<Vec<u8>, _>(&error, &hook);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,223 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use async_trait::async_trait;
+use conjure_error::{Error, ErrorKind, PermissionDenied};
+use conjure_http::client::{
+    AsyncService, ConjureResponseDeserializer, DisplaySeqHeaderEncoder, DisplaySeqParamEncoder,
+    Service,
+};
+use conjure_http::server::{ConjureResponseSerializer, FromStrOptionDecoder, FromStrSeqDecoder};
+use conjure_http::testing::{LocalAsyncClient, LocalClient};
+use conjure_macros::{conjure_client, conjure_endpoints, endpoint};
+use conjure_object::Any;
+use futures::executor;
+use http::StatusCode;
+use std::sync::{Arc, Mutex};
+
+#[conjure_client]
+trait LocalTestService {
+    #[endpoint(method = GET, path = "/test/params/{name}", accept = ConjureResponseDeserializer)]
+    fn params(
+        &self,
+        #[path] name: &str,
+        #[query(name = "list", encoder = DisplaySeqParamEncoder)] list: &[i32],
+        #[header(name = "Some-Header", encoder = DisplaySeqHeaderEncoder)] header: Option<&str>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = POST, path = "/test/echo", accept = ConjureResponseDeserializer)]
+    fn echo(&self, #[body] body: &str) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/test/denied")]
+    fn denied(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/test/missing")]
+    fn missing(&self) -> Result<(), Error>;
+
+    #[endpoint(method = DELETE, path = "/test/echo")]
+    fn wrong_method(&self) -> Result<(), Error>;
+}
+
+#[conjure_client]
+#[async_trait]
+trait AsyncLocalTestService {
+    #[endpoint(method = GET, path = "/test/params/{name}", accept = ConjureResponseDeserializer)]
+    async fn params(
+        &self,
+        #[path] name: &str,
+        #[query(name = "list", encoder = DisplaySeqParamEncoder)] list: &[i32],
+        #[header(name = "Some-Header", encoder = DisplaySeqHeaderEncoder)] header: Option<&str>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/test/denied")]
+    async fn denied(&self) -> Result<(), Error>;
+}
+
+#[conjure_endpoints]
+trait LocalTestResource {
+    #[endpoint(method = GET, path = "/test/params/{name}", produces = ConjureResponseSerializer)]
+    fn params(
+        &self,
+        #[path] name: String,
+        #[query(name = "list", decoder = FromStrSeqDecoder<_>)] list: Vec<i32>,
+        #[header(name = "Some-Header", decoder = FromStrOptionDecoder)] header: Option<String>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = POST, path = "/test/echo", produces = ConjureResponseSerializer)]
+    fn echo(&self, #[body] body: String) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/test/denied")]
+    fn denied(&self) -> Result<(), Error>;
+}
+
+#[conjure_endpoints]
+#[async_trait]
+trait AsyncLocalTestResource {
+    #[endpoint(method = GET, path = "/test/params/{name}", produces = ConjureResponseSerializer)]
+    async fn params(
+        &self,
+        #[path] name: String,
+        #[query(name = "list", decoder = FromStrSeqDecoder<_>)] list: Vec<i32>,
+        #[header(name = "Some-Header", decoder = FromStrOptionDecoder)] header: Option<String>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/test/denied")]
+    async fn denied(&self) -> Result<(), Error>;
+}
+
+struct LocalTestResourceHandler;
+
+fn format_params(name: String, list: Vec<i32>, header: Option<String>) -> String {
+    format!("{} {:?} {:?}", name, list, header)
+}
+
+impl LocalTestResource for LocalTestResourceHandler {
+    fn params(
+        &self,
+        name: String,
+        list: Vec<i32>,
+        header: Option<String>,
+    ) -> Result<String, Error> {
+        Ok(format_params(name, list, header))
+    }
+
+    fn echo(&self, body: String) -> Result<String, Error> {
+        Ok(body)
+    }
+
+    fn denied(&self) -> Result<(), Error> {
+        Err(Error::service_safe("denied", PermissionDenied::new()))
+    }
+}
+
+#[async_trait]
+impl AsyncLocalTestResource for LocalTestResourceHandler {
+    async fn params(
+        &self,
+        name: String,
+        list: Vec<i32>,
+        header: Option<String>,
+    ) -> Result<String, Error> {
+        Ok(format_params(name, list, header))
+    }
+
+    async fn denied(&self) -> Result<(), Error> {
+        Err(Error::service_safe("denied", PermissionDenied::new()))
+    }
+}
+
+fn client() -> LocalTestServiceClient<LocalClient> {
+    let client = LocalClient::builder()
+        .service(&LocalTestResourceEndpoints::new(LocalTestResourceHandler))
+        .build()
+        .unwrap();
+    LocalTestServiceClient::new(client)
+}
+
+fn async_client() -> AsyncLocalTestServiceClient<LocalAsyncClient> {
+    let client = LocalAsyncClient::builder()
+        .service(&AsyncLocalTestResourceEndpoints::new(
+            LocalTestResourceHandler,
+        ))
+        .build()
+        .unwrap();
+    AsyncLocalTestServiceClient::new(client)
+}
+
+fn assert_service_error(error: &Error, name: &str) {
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(e.error_name(), name),
+        _ => panic!("expected a service error"),
+    }
+}
+
+#[test]
+fn params() {
+    let client = client();
+    assert_eq!(
+        client.params("a/b c", &[1, 2], Some("hello")).unwrap(),
+        r#"a/b c [1, 2] Some("hello")"#,
+    );
+    assert_eq!(client.params("foo", &[], None).unwrap(), "foo [] None");
+}
+
+#[test]
+fn body() {
+    assert_eq!(client().echo("hello world").unwrap(), "hello world");
+}
+
+#[test]
+fn service_error() {
+    let status = Arc::new(Mutex::new(None));
+    let client = LocalClient::builder()
+        .service(&LocalTestResourceEndpoints::new(LocalTestResourceHandler))
+        .error_hook({
+            let status = status.clone();
+            move |_: &Error, s| *status.lock().unwrap() = Some(s)
+        })
+        .build()
+        .unwrap();
+    let client = LocalTestServiceClient::new(client);
+
+    let error = client.denied().unwrap_err();
+    assert_service_error(&error, "Default:PermissionDenied");
+    assert_eq!(*status.lock().unwrap(), Some(StatusCode::FORBIDDEN));
+}
+
+#[test]
+fn not_found() {
+    let error = client().missing().unwrap_err();
+    assert_service_error(&error, "Default:NotFound");
+}
+
+#[test]
+fn method_not_allowed() {
+    let error = client().wrong_method().unwrap_err();
+    assert_service_error(&error, "Default:Internal");
+    assert_eq!(error.safe_params()["status"], Any::new(405u16).unwrap());
+}
+
+#[test]
+fn async_params() {
+    let client = async_client();
+    assert_eq!(
+        executor::block_on(client.params("a/b c", &[1, 2], Some("hello"))).unwrap(),
+        r#"a/b c [1, 2] Some("hello")"#,
+    );
+}
+
+#[test]
+fn async_service_error() {
+    let error = executor::block_on(async_client().denied()).unwrap_err();
+    assert_service_error(&error, "Default:PermissionDenied");
+}
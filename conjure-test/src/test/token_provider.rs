@@ -0,0 +1,166 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::recording::RecordingClient;
+use crate::token_provider_types::*;
+use async_trait::async_trait;
+use conjure_error::Error;
+use conjure_http::client::AsyncTokenProvider;
+use conjure_macros::{conjure_client, endpoint};
+use conjure_object::BearerToken;
+use futures::executor;
+use http::header::{AUTHORIZATION, COOKIE};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+fn token(token: &str) -> Arc<BearerToken> {
+    Arc::new(BearerToken::new(token).unwrap())
+}
+
+#[derive(Default)]
+struct CountingProvider {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl AsyncTokenProvider for CountingProvider {
+    async fn token(&self) -> Result<BearerToken, Error> {
+        let n = self.calls.fetch_add(1, Ordering::SeqCst);
+        futures::future::ready(()).await;
+        Ok(BearerToken::new(&format!("token{}", n)).unwrap())
+    }
+}
+
+#[test]
+fn generated_header_auth() {
+    let client = RecordingClient::default();
+    TestServiceClient::new(client.clone(), token("fizzbuzz"))
+        .header_auth()
+        .unwrap();
+
+    let requests = client.take();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers[AUTHORIZATION], "Bearer fizzbuzz");
+}
+
+#[test]
+fn generated_cookie_auth() {
+    let client = RecordingClient::default();
+    executor::block_on(
+        TestServiceAsyncClient::new(client.clone(), token("fizzbuzz")).cookie_auth(),
+    )
+    .unwrap();
+
+    let requests = client.take();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers[COOKIE], "foobar=fizzbuzz");
+    assert!(!requests[0].headers.contains_key(AUTHORIZATION));
+}
+
+#[test]
+fn provider_called_per_request() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider = {
+        let calls = calls.clone();
+        move || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            Ok(BearerToken::new(&format!("token{}", n)).unwrap())
+        }
+    };
+
+    let client = RecordingClient::default();
+    let service = TestServiceClient::new(client.clone(), Arc::new(provider));
+    service.header_auth().unwrap();
+    service.header_auth().unwrap();
+
+    let requests = client.take();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(requests[0].headers[AUTHORIZATION], "Bearer token0");
+    assert_eq!(requests[1].headers[AUTHORIZATION], "Bearer token1");
+}
+
+#[test]
+fn provider_error() {
+    let client = RecordingClient::default();
+    let provider = || Err(Error::internal_safe("token unavailable"));
+    TestServiceClient::new(client.clone(), Arc::new(provider))
+        .header_auth()
+        .unwrap_err();
+
+    assert!(client.take().is_empty());
+}
+
+#[conjure_client(token_provider)]
+trait TokenProviderService {
+    #[endpoint(method = GET, path = "/header", auth)]
+    fn header(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/cookie", auth(cookie_name = "foobar"))]
+    fn cookie(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/unauthenticated")]
+    fn unauthenticated(&self) -> Result<(), Error>;
+}
+
+#[conjure_client(token_provider)]
+#[async_trait]
+trait AsyncTokenProviderService {
+    #[endpoint(method = GET, path = "/header", auth)]
+    async fn header(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/cookie", auth(cookie_name = "foobar"))]
+    async fn cookie(&self) -> Result<(), Error>;
+}
+
+#[test]
+fn custom_token_provider() {
+    let client = RecordingClient::default();
+    let service = TokenProviderServiceClient::new(client.clone(), token("fizzbuzz"));
+    service.header().unwrap();
+    service.cookie().unwrap();
+    service.unauthenticated().unwrap();
+
+    let requests = client.take();
+    assert_eq!(requests[0].headers[AUTHORIZATION], "Bearer fizzbuzz");
+    assert_eq!(requests[1].headers[COOKIE], "foobar=fizzbuzz");
+    assert!(!requests[2].headers.contains_key(AUTHORIZATION));
+    assert!(!requests[2].headers.contains_key(COOKIE));
+}
+
+#[test]
+fn custom_async_token_provider() {
+    let client = RecordingClient::default();
+    let service = AsyncTokenProviderServiceClient::new(client.clone(), token("fizzbuzz"));
+    executor::block_on(service.header()).unwrap();
+    executor::block_on(service.cookie()).unwrap();
+
+    let requests = client.take();
+    assert_eq!(requests[0].headers[AUTHORIZATION], "Bearer fizzbuzz");
+    assert_eq!(requests[1].headers[COOKIE], "foobar=fizzbuzz");
+}
+
+#[test]
+fn async_provider() {
+    let client = RecordingClient::default();
+    let provider = Arc::new(CountingProvider::default());
+
+    let service = TestServiceAsyncClient::new(client.clone(), provider.clone());
+    executor::block_on(service.header_auth()).unwrap();
+    let service = AsyncTokenProviderServiceClient::new(client.clone(), provider.clone());
+    executor::block_on(service.header()).unwrap();
+
+    let requests = client.take();
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+    assert_eq!(requests[0].headers[AUTHORIZATION], "Bearer token0");
+    assert_eq!(requests[1].headers[AUTHORIZATION], "Bearer token1");
+}
@@ -0,0 +1,231 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use async_trait::async_trait;
+use conjure_error::{Error, PermissionDenied};
+use conjure_http::server::tower::{ConjureService, ResponseBody};
+use conjure_http::server::{
+    AsyncResponseBody, AsyncSerializeResponse, AsyncWriteBody, ConjureResponseSerializer,
+};
+use conjure_http::SafeParams;
+use conjure_macros::{conjure_endpoints, endpoint};
+use futures::executor;
+use futures::io::{AsyncWrite, AsyncWriteExt};
+use http::header::{ALLOW, CONTENT_TYPE};
+use http::{HeaderMap, Method, Request, Response, StatusCode};
+use http_body::{Body, Full};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+
+#[conjure_endpoints]
+#[async_trait]
+trait TowerTestService<#[response_writer] O>
+where
+    O: AsyncWrite + Send,
+{
+    #[endpoint(method = GET, path = "/hello/{name}", produces = ConjureResponseSerializer)]
+    async fn hello(&self, #[path(safe)] name: String) -> Result<String, Error>;
+
+    #[endpoint(method = POST, path = "/echo", produces = ConjureResponseSerializer)]
+    async fn echo(&self, #[body] body: String) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/denied")]
+    async fn denied(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/stream/{len}", produces = StreamingSerializer)]
+    async fn stream(&self, #[path] len: usize) -> Result<TestBodyWriter, Error>;
+
+    #[endpoint(method = GET, path = "/streamFailure", produces = StreamingSerializer)]
+    async fn stream_failure(&self) -> Result<TestBodyWriter, Error>;
+}
+
+struct TowerTestServiceHandler;
+
+#[async_trait]
+impl<O> TowerTestService<O> for TowerTestServiceHandler
+where
+    O: AsyncWrite + Send,
+{
+    async fn hello(&self, name: String) -> Result<String, Error> {
+        Ok(format!("hello {}", name))
+    }
+
+    async fn echo(&self, body: String) -> Result<String, Error> {
+        Ok(body)
+    }
+
+    async fn denied(&self) -> Result<(), Error> {
+        Err(Error::service_safe("denied", PermissionDenied::new()))
+    }
+
+    async fn stream(&self, len: usize) -> Result<TestBodyWriter, Error> {
+        Ok(TestBodyWriter { len, fail: false })
+    }
+
+    async fn stream_failure(&self) -> Result<TestBodyWriter, Error> {
+        Ok(TestBodyWriter {
+            len: 10,
+            fail: true,
+        })
+    }
+}
+
+struct TestBodyWriter {
+    len: usize,
+    fail: bool,
+}
+
+#[async_trait]
+impl<O> AsyncWriteBody<O> for TestBodyWriter
+where
+    O: AsyncWrite + Send,
+{
+    async fn write_body(self: Box<Self>, mut w: Pin<&mut O>) -> Result<(), Error> {
+        for i in 0..self.len {
+            w.write_all(&[i as u8])
+                .await
+                .map_err(Error::internal_safe)?;
+        }
+        if self.fail {
+            return Err(Error::internal_safe("body failed"));
+        }
+        w.flush().await.map_err(Error::internal_safe)
+    }
+}
+
+enum StreamingSerializer {}
+
+impl<T, O> AsyncSerializeResponse<T, O> for StreamingSerializer
+where
+    T: AsyncWriteBody<O> + 'static + Send,
+{
+    fn serialize(_: &HeaderMap, value: T) -> Result<Response<AsyncResponseBody<O>>, Error> {
+        Ok(Response::new(AsyncResponseBody::Streaming(Box::new(value))))
+    }
+}
+
+fn service() -> ConjureService<Full<&'static [u8]>> {
+    ConjureService::builder()
+        .service(&TowerTestServiceEndpoints::new(TowerTestServiceHandler))
+        .build()
+        .unwrap()
+}
+
+fn request(method: Method, uri: &str, body: &'static [u8]) -> Request<Full<&'static [u8]>> {
+    let mut request = Request::new(Full::new(body));
+    *request.method_mut() = method;
+    *request.uri_mut() = uri.parse().unwrap();
+    request
+}
+
+fn call(
+    service: ConjureService<Full<&'static [u8]>>,
+    request: Request<Full<&'static [u8]>>,
+) -> Response<ResponseBody> {
+    executor::block_on(service.oneshot(request)).unwrap()
+}
+
+fn read_body(body: &mut ResponseBody) -> Vec<u8> {
+    let mut buf = vec![];
+    while let Some(chunk) = executor::block_on(body.data()) {
+        buf.extend_from_slice(&chunk.unwrap());
+    }
+    buf
+}
+
+#[test]
+fn routed_request() {
+    let response = call(service(), request(Method::GET, "/hello/world", b""));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    let mut safe_params = SafeParams::new();
+    safe_params.insert("name", &"world");
+    assert_eq!(
+        response.extensions().get::<SafeParams>(),
+        Some(&safe_params)
+    );
+    let mut body = response.into_body();
+    assert_eq!(read_body(&mut body), br#""hello world""#);
+    assert!(body.is_end_stream());
+}
+
+#[test]
+fn request_body() {
+    let mut request = request(Method::POST, "/echo", br#""hello world""#);
+    request
+        .headers_mut()
+        .insert(CONTENT_TYPE, "application/json".parse().unwrap());
+    let response = call(service(), request);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(read_body(&mut response.into_body()), br#""hello world""#);
+}
+
+#[test]
+fn endpoint_error() {
+    let errors = Arc::new(Mutex::new(vec![]));
+    let service = ConjureService::builder()
+        .service(&TowerTestServiceEndpoints::new(TowerTestServiceHandler))
+        .error_hook({
+            let errors = errors.clone();
+            move |_: &Error, status| errors.lock().unwrap().push(status)
+        })
+        .build()
+        .unwrap();
+
+    let response = call(service, request(Method::GET, "/denied", b""));
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body = read_body(&mut response.into_body());
+    let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+    assert_eq!(body["errorName"], "Default:PermissionDenied");
+    assert_eq!(*errors.lock().unwrap(), [StatusCode::FORBIDDEN]);
+}
+
+#[test]
+fn not_found() {
+    let response = call(service(), request(Method::GET, "/goodbye", b""));
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = read_body(&mut response.into_body());
+    let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+    assert_eq!(body["errorName"], "Default:NotFound");
+}
+
+#[test]
+fn method_not_allowed() {
+    let response = call(service(), request(Method::DELETE, "/hello/world", b""));
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[ALLOW], "GET");
+    assert!(response.into_body().is_end_stream());
+}
+
+#[test]
+fn streaming_response() {
+    let len = 20_000;
+    let response = call(
+        service(),
+        request(Method::GET, &format!("/stream/{}", len), b""),
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = read_body(&mut response.into_body());
+    assert_eq!(body, (0..len).map(|i| i as u8).collect::<Vec<_>>());
+}
+
+#[test]
+fn streaming_response_failure() {
+    let response = call(service(), request(Method::GET, "/streamFailure", b""));
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut body = response.into_body();
+    let error = executor::block_on(body.data()).unwrap().err().unwrap();
+    assert_eq!(error.error().cause().to_string(), "body failed");
+    assert!(executor::block_on(body.data()).is_none());
+}
@@ -0,0 +1,257 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::test::recording::{self, token, RecordingClient};
+use crate::types::*;
+use conjure_error::Error;
+use conjure_http::client::{AsyncService, Service, TracingClient};
+use conjure_http::server::{ConjureResponseSerializer, LayeredService, RequestContext, TraceLayer};
+use conjure_http::trace::{B3Propagator, TracePropagator, W3cPropagator};
+use conjure_http::TraceContext;
+use conjure_macros::{conjure_endpoints, endpoint};
+use futures::executor;
+use http::{HeaderMap, Request};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const SPAN_ID: &str = "00f067aa0ba902b7";
+
+fn context() -> TraceContext {
+    TraceContext::new(TRACE_ID, SPAN_ID).unwrap()
+}
+
+fn headers(headers: &[(&'static str, &str)]) -> HeaderMap {
+    headers
+        .iter()
+        .map(|(k, v)| (k.parse().unwrap(), v.parse().unwrap()))
+        .collect()
+}
+
+#[test]
+fn invalid_ids() {
+    assert!(TraceContext::new("", SPAN_ID).is_none());
+    assert!(TraceContext::new(TRACE_ID, "00000000000000000").is_none());
+    assert!(TraceContext::new("00000000000000000000000000000000", SPAN_ID).is_none());
+    assert!(TraceContext::new("4bf92f3577b34da6a3ce929d0e0e473g", SPAN_ID).is_none());
+    assert!(TraceContext::new(&TRACE_ID[..16], SPAN_ID).is_some());
+}
+
+#[test]
+fn w3c() {
+    let context = context().with_sampled(Some(true));
+    let mut map = HeaderMap::new();
+    W3cPropagator.inject(&context, &mut map);
+    assert_eq!(
+        map["traceparent"],
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+    );
+    assert_eq!(W3cPropagator.extract(&map), Some(context));
+
+    let map = headers(&[(
+        "traceparent",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+    )]);
+    assert_eq!(
+        W3cPropagator.extract(&map),
+        Some(self::context().with_sampled(Some(false))),
+    );
+
+    let map = headers(&[(
+        "traceparent",
+        "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+    )]);
+    assert_eq!(W3cPropagator.extract(&map), None);
+
+    let map = headers(&[(
+        "traceparent",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra",
+    )]);
+    assert_eq!(W3cPropagator.extract(&map), None);
+
+    let map = headers(&[(
+        "traceparent",
+        "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra",
+    )]);
+    assert!(W3cPropagator.extract(&map).is_some());
+}
+
+#[test]
+fn w3c_short_trace_id() {
+    let context = TraceContext::new(&TRACE_ID[16..], SPAN_ID).unwrap();
+    let mut map = HeaderMap::new();
+    W3cPropagator.inject(&context, &mut map);
+    assert_eq!(
+        map["traceparent"],
+        "00-0000000000000000a3ce929d0e0e4736-00f067aa0ba902b7-00",
+    );
+}
+
+#[test]
+fn b3() {
+    let context = context().with_sampled(Some(false));
+    let mut map = HeaderMap::new();
+    B3Propagator.inject(&context, &mut map);
+    assert_eq!(map["X-B3-TraceId"], TRACE_ID);
+    assert_eq!(map["X-B3-SpanId"], SPAN_ID);
+    assert_eq!(map["X-B3-Sampled"], "0");
+    assert_eq!(B3Propagator.extract(&map), Some(context));
+
+    let map = headers(&[
+        ("X-B3-TraceId", TRACE_ID),
+        ("X-B3-SpanId", SPAN_ID),
+        ("X-B3-Flags", "1"),
+    ]);
+    assert_eq!(
+        B3Propagator.extract(&map),
+        Some(self::context().with_sampled(Some(true))),
+    );
+
+    let map = headers(&[(
+        "b3",
+        "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1-05e3ac9a4f6e3b90",
+    )]);
+    assert_eq!(
+        B3Propagator.extract(&map),
+        Some(self::context().with_sampled(Some(true))),
+    );
+
+    let map = headers(&[("b3", "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7")]);
+    assert_eq!(B3Propagator.extract(&map), Some(self::context()));
+}
+
+#[test]
+fn from_headers() {
+    let map = headers(&[
+        (
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ),
+        ("X-B3-TraceId", "a3ce929d0e0e4736"),
+        ("X-B3-SpanId", "a3ce929d0e0e4736"),
+    ]);
+    assert_eq!(
+        TraceContext::from_headers(&map),
+        Some(context().with_sampled(Some(true))),
+    );
+
+    let map = headers(&[("X-B3-TraceId", TRACE_ID), ("X-B3-SpanId", SPAN_ID)]);
+    assert_eq!(TraceContext::from_headers(&map), Some(context()));
+
+    assert_eq!(TraceContext::from_headers(&HeaderMap::new()), None);
+}
+
+#[conjure_endpoints]
+trait TraceTestService {
+    #[endpoint(method = GET, path = "/test/trace", produces = ConjureResponseSerializer)]
+    fn trace(&self, #[context] context: RequestContext<'_>) -> Result<Option<String>, Error>;
+}
+
+struct TraceTestServiceHandler;
+
+impl TraceTestService for TraceTestServiceHandler {
+    fn trace(&self, context: RequestContext<'_>) -> Result<Option<String>, Error> {
+        Ok(context.trace_context().map(|c| c.span_id().to_string()))
+    }
+}
+
+fn handle<P>(propagator: Option<P>, headers: HeaderMap) -> String
+where
+    P: TracePropagator + 'static + Sync + Send,
+{
+    let service = TraceTestServiceEndpoints::new(TraceTestServiceHandler);
+    let mut request = Request::new(());
+    *request.headers_mut() = headers;
+
+    match propagator {
+        Some(propagator) => recording::handle(
+            &LayeredService::new(service, TraceLayer::new(propagator)),
+            "trace",
+            request,
+        ),
+        None => recording::handle(&service, "trace", request),
+    }
+    .unwrap()
+}
+
+#[test]
+fn server_trace_layer() {
+    let map = headers(&[
+        (
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ),
+        ("X-B3-TraceId", TRACE_ID),
+        ("X-B3-SpanId", "a3ce929d0e0e4736"),
+    ]);
+    assert_eq!(handle(Some(B3Propagator), map), r#""a3ce929d0e0e4736""#);
+}
+
+#[test]
+fn server_trace_context_without_layer() {
+    let map = headers(&[(
+        "traceparent",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+    )]);
+    assert_eq!(handle(None::<W3cPropagator>, map), r#""00f067aa0ba902b7""#);
+
+    assert_eq!(handle(None::<W3cPropagator>, HeaderMap::new()), "null");
+}
+
+#[test]
+fn client_injects_context() {
+    let client = RecordingClient::default();
+    let context = context().with_sampled(Some(true));
+    TestServiceClient::new(TracingClient::new(
+        client.clone(),
+        W3cPropagator,
+        context.clone(),
+    ))
+    .header_auth(&token())
+    .unwrap();
+
+    let requests = client.take();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].headers["traceparent"],
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+    );
+    assert_eq!(requests[0].trace_context, Some(context));
+}
+
+#[test]
+fn async_client_injects_context() {
+    let client = RecordingClient::default();
+    let source = || Some(context());
+    executor::block_on(
+        TestServiceAsyncClient::new(TracingClient::new(client.clone(), B3Propagator, source))
+            .header_auth(&token()),
+    )
+    .unwrap();
+
+    let requests = client.take();
+    assert_eq!(requests[0].headers["X-B3-TraceId"], TRACE_ID);
+    assert_eq!(requests[0].headers["X-B3-SpanId"], SPAN_ID);
+    assert!(!requests[0].headers.contains_key("X-B3-Sampled"));
+}
+
+#[test]
+fn client_without_context() {
+    let client = RecordingClient::default();
+    let source = || None;
+    TestServiceClient::new(TracingClient::new(client.clone(), W3cPropagator, source))
+        .header_auth(&token())
+        .unwrap();
+
+    let requests = client.take();
+    assert!(!requests[0].headers.contains_key("traceparent"));
+    assert_eq!(requests[0].trace_context, None);
+}
@@ -0,0 +1,315 @@
+// Copyright 2023 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use conjure_codegen::{Config, ValidationError};
+use std::fs;
+use std::path::Path;
+
+const STRING: &str = r#"{"type": "primitive", "primitive": "STRING"}"#;
+
+fn reference(name: &str) -> String {
+    format!(
+        r#"{{"type": "reference", "reference": {{"name": "{}", "package": "com.test"}}}}"#,
+        name
+    )
+}
+
+fn type_name(name: &str) -> String {
+    format!(r#"{{"name": "{}", "package": "com.test"}}"#, name)
+}
+
+fn object(name: &str, fields: &[(&str, &str)]) -> String {
+    let fields = fields
+        .iter()
+        .map(|(name, ty)| format!(r#"{{"fieldName": "{}", "type": {}}}"#, name, ty))
+        .collect::<Vec<_>>();
+    format!(
+        r#"{{"type": "object", "object": {{"typeName": {}, "fields": [{}]}}}}"#,
+        type_name(name),
+        fields.join(", "),
+    )
+}
+
+fn constant(name: &str, ty: &str, value: &str) -> String {
+    format!(
+        r#"{{"type": "constant", "constant": {{"typeName": {}, "type": "{}", "value": "{}"}}}}"#,
+        type_name(name),
+        ty,
+        value,
+    )
+}
+
+fn map(key: &str, value: &str) -> String {
+    format!(
+        r#"{{"type": "map", "map": {{"keyType": {}, "valueType": {}}}}}"#,
+        key, value
+    )
+}
+
+fn endpoint(path: &str, path_args: &[&str]) -> String {
+    tagged_endpoint(path, path_args, &[])
+}
+
+fn tagged_endpoint(path: &str, path_args: &[&str], tags: &[&str]) -> String {
+    let args = path_args
+        .iter()
+        .map(|name| {
+            format!(
+                r#"{{"argName": "{}", "type": {}, "paramType": {{"type": "path", "path": {{}}}}, "markers": []}}"#,
+                name, STRING
+            )
+        })
+        .collect::<Vec<_>>();
+    let tags = tags
+        .iter()
+        .map(|tag| format!(r#""{}""#, tag))
+        .collect::<Vec<_>>();
+    format!(
+        r#"{{"endpointName": "test", "httpMethod": "GET", "httpPath": "{}", "args": [{}], "markers": [], "tags": [{}]}}"#,
+        path,
+        args.join(", "),
+        tags.join(", "),
+    )
+}
+
+fn service(endpoints: &[String]) -> String {
+    format!(
+        r#"{{"serviceName": {}, "endpoints": [{}]}}"#,
+        type_name("TestService"),
+        endpoints.join(", "),
+    )
+}
+
+fn validate(name: &str, types: &[String], services: &[String]) -> Vec<String> {
+    let ir = format!(
+        r#"{{"version": 1, "errors": [], "types": [{}], "services": [{}]}}"#,
+        types.join(", "),
+        services.join(", "),
+    );
+    let path = Path::new(env!("OUT_DIR")).join(format!("validation-{}.json", name));
+    fs::write(&path, ir).unwrap();
+
+    let error = match Config::new().generate_module(path) {
+        Ok(_) => return vec![],
+        Err(error) => error,
+    };
+    error
+        .downcast_ref::<ValidationError>()
+        .unwrap()
+        .errors()
+        .iter()
+        .map(|e| e.to_string())
+        .collect()
+}
+
+#[test]
+fn valid_ir() {
+    let errors = validate(
+        "valid",
+        &[
+            object("Foo", &[("bar", &reference("Bar")), ("baz", STRING)]),
+            object("Bar", &[]),
+            constant("Uuid", "UUID", "01234567-89ab-cdef-0123-456789abcdef"),
+        ],
+        &[service(&[endpoint("/foo/{bar}", &["bar"])])],
+    );
+    assert_eq!(errors, Vec::<String>::new());
+}
+
+#[test]
+fn unknown_references() {
+    let errors = validate(
+        "unknown",
+        &[object(
+            "Foo",
+            &[
+                ("bar", &reference("Bar")),
+                ("baz", &map(STRING, &reference("Baz"))),
+            ],
+        )],
+        &[],
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "object com.test.Foo, field bar: unknown type com.test.Bar",
+            "object com.test.Foo, field baz: unknown type com.test.Baz",
+        ],
+    );
+}
+
+#[test]
+fn duplicate_names() {
+    let errors = validate(
+        "duplicate",
+        &[
+            object("Foo", &[("fooBar", STRING), ("foo_bar", STRING)]),
+            object("foo", &[]),
+            constant("fooBar", "STRING", "a"),
+            constant("FOO_BAR", "STRING", "b"),
+        ],
+        &[],
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "object com.test.Foo: fields `fooBar` and `foo_bar` both generate the name `foo_bar`",
+            "object com.test.foo: the name `foo` conflicts with object com.test.Foo",
+            "object com.test.foo: the name `Foo` conflicts with object com.test.Foo",
+            "constant com.test.FOO_BAR: the name `foo_bar` conflicts with constant com.test.fooBar",
+            "constant com.test.FOO_BAR: the name `FOO_BAR` conflicts with constant com.test.fooBar",
+        ],
+    );
+}
+
+#[test]
+fn invalid_map_keys() {
+    let list =
+        r#"{"type": "list", "list": {"itemType": {"type": "primitive", "primitive": "STRING"}}}"#;
+    let errors = validate(
+        "map-keys",
+        &[
+            object(
+                "Foo",
+                &[
+                    ("valid", &map(STRING, STRING)),
+                    ("list", &map(list, STRING)),
+                    ("object", &map(&reference("Foo"), STRING)),
+                ],
+            ),
+            format!(
+                r#"{{"type": "alias", "alias": {{"typeName": {}, "alias": {}}}}}"#,
+                type_name("Alias"),
+                list,
+            ),
+            object("Bar", &[("alias", &map(&reference("Alias"), STRING))]),
+        ],
+        &[],
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "object com.test.Foo, field list: `list<string>` cannot be used as a map key",
+            "object com.test.Foo, field object: object com.test.Foo cannot be used as a map key",
+            "object com.test.Bar, field alias: `list<string>` cannot be used as a map key",
+        ],
+    );
+}
+
+fn alias(name: &str, ty: &str) -> String {
+    format!(
+        r#"{{"type": "alias", "alias": {{"typeName": {}, "alias": {}}}}}"#,
+        type_name(name),
+        ty,
+    )
+}
+
+#[test]
+fn alias_cycles() {
+    let arg = format!(
+        r#"{{"argName": "foo", "type": {}, "paramType": {{"type": "path", "path": {{}}}}, "markers": []}}"#,
+        reference("Foo"),
+    );
+    let endpoint = format!(
+        r#"{{"endpointName": "test", "httpMethod": "GET", "httpPath": "/{{foo}}", "args": [{}], "markers": []}}"#,
+        arg,
+    );
+    let errors = validate(
+        "alias-cycles",
+        &[
+            alias("Foo", &reference("Bar")),
+            alias("Bar", &reference("Foo")),
+            alias("Baz", &reference("Baz")),
+            object("Object", &[("map", &map(&reference("Foo"), STRING))]),
+        ],
+        &[service(&[endpoint])],
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "alias com.test.Foo: alias cycle",
+            "alias com.test.Bar: alias cycle",
+            "alias com.test.Baz: alias cycle",
+        ],
+    );
+}
+
+#[test]
+fn invalid_constants() {
+    let errors = validate(
+        "constants",
+        &[
+            constant("Integer", "INTEGER", "1.5"),
+            constant("Double", "DOUBLE", "1.5"),
+            constant("Datetime", "DATETIME", "yesterday"),
+            constant("Rid", "RID", "ri.service.instance.type.locator"),
+            constant("Uuid", "UUID", "not-a-uuid"),
+            constant("Binary", "BINARY", "aGVsbG8="),
+        ],
+        &[],
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "constant com.test.Integer: invalid `integer` value `1.5`",
+            "constant com.test.Datetime: invalid `datetime` value `yesterday`",
+            "constant com.test.Uuid: invalid `uuid` value `not-a-uuid`",
+            "constant com.test.Binary: `binary` constants are not supported",
+        ],
+    );
+}
+
+#[test]
+fn path_mismatches() {
+    let errors = validate(
+        "paths",
+        &[],
+        &[service(&[
+            endpoint("/foo/{bar}/{bar}", &["bar"]),
+            endpoint("/foo/{bar}", &["baz"]),
+        ])],
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "service com.test.TestService: endpoints `test` and `test` both generate the name \
+             `test`",
+            "service com.test.TestService, endpoint test: path parameter `bar` appears multiple \
+             times",
+            "service com.test.TestService, endpoint test: path parameter `bar` has no \
+             corresponding argument",
+            "service com.test.TestService, endpoint test: path argument `baz` does not appear in \
+             the path `/foo/{bar}`",
+        ],
+    );
+}
+
+#[test]
+fn invalid_tags() {
+    let errors = validate(
+        "tags",
+        &[],
+        &[service(&[tagged_endpoint(
+            "/foo",
+            &[],
+            &["server-request-size-limit:lots"],
+        )])],
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "service com.test.TestService, endpoint test: invalid `server-request-size-limit` tag: \
+             invalid digit found in string",
+        ],
+    );
+}
@@ -1,3 +1,4 @@
 #![allow(warnings)]
 pub mod another;
 pub mod product;
+pub mod test;
@@ -0,0 +1 @@
+pub const CONSTANT_BOOLEAN: bool = true;
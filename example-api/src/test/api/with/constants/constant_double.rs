@@ -0,0 +1 @@
+pub const CONSTANT_DOUBLE: f64 = 123.123;
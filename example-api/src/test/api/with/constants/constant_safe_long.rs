@@ -0,0 +1,3 @@
+pub const CONSTANT_SAFE_LONG: conjure_object::SafeLong = conjure_object::private::safe_long(
+    123123123,
+);
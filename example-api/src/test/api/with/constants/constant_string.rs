@@ -0,0 +1 @@
+pub const CONSTANT_STRING: &str = "hello";
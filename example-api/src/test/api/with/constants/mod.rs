@@ -0,0 +1,15 @@
+#[doc(inline)]
+pub use self::constant_string::CONSTANT_STRING;
+#[doc(inline)]
+pub use self::constant_boolean::CONSTANT_BOOLEAN;
+#[doc(inline)]
+pub use self::constant_double::CONSTANT_DOUBLE;
+#[doc(inline)]
+pub use self::constant_integer::CONSTANT_INTEGER;
+#[doc(inline)]
+pub use self::constant_safe_long::CONSTANT_SAFE_LONG;
+pub mod constant_string;
+pub mod constant_boolean;
+pub mod constant_double;
+pub mod constant_integer;
+pub mod constant_safe_long;